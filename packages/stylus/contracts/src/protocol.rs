@@ -10,19 +10,24 @@
 //! Note: this code is a conversion and has not been audited.
 //!
 
+// The generated ABI exporter chains one iterator per public method.
+#![recursion_limit = "512"]
+
 extern crate alloc;
 
 use alloc::string::String;
 use alloc::vec::Vec;
 use alloy_sol_types::sol;
 use stylus_sdk::{
-    alloy_primitives::{Address, U256, U64, U32, U8, I8, FixedBytes},
-    prelude::*,
+    alloy_primitives::{Address, U256, U64, U32, U16, U8, I8, I32, FixedBytes, Uint},
+    abi::Bytes,
+    call::RawCall,
     crypto::keccak,
-    // call::Call, // COMMENTED OUT - not needed without USDC transfers
+    prelude::*,
     function_selector,
+    ArbResult,
 };
-use stylus_sdk::stylus_core::{log, calls::errors::Error as CallError};
+use stylus_sdk::stylus_core::{log, calls::{CallAccess, errors::Error as CallError}};
 
 // ====================================
 //          STORAGE STRUCTS          
@@ -31,43 +36,203 @@ use stylus_sdk::stylus_core::{log, calls::errors::Error as CallError};
 sol_storage! {
     #[entrypoint]
     pub struct ProtocolContract {
+        // Slots are fixed as of storage v1, so new fields only go at the end. The unversioned
+        // original layout had other fields in these slots and can't be migrated (see migrate)
         address owner;
         address usdc_token;
         
         uint256 contract_balance;
+        uint256 total_judge_rewards; // sum of all judge balances still owed
+        uint256 total_escrowed;      // fees and bonds held for disputes not yet settled
         uint64 dispute_count;
         uint8 number_of_votes;
         uint256 dispute_price;
+        int32 min_reputation;
+        uint64 panel_timeout;
+        uint64 commit_period;
+        uint64 judge_cooldown;   // seconds between panel assignments, 0 = disabled
+        uint64 withdraw_delay;   // timelock on owner treasury withdrawals
+        uint256 queued_withdrawal;
+        uint64 withdrawal_unlock_time;
+        bool stake_weighted_rewards;
+        uint16 judge_reward_bps;
+        bool migration_locked;   // set once judge imports from a previous deployment are done
+        bool strict_majority;    // panel sizes must be odd so a full panel can't tie
+        
+        bool defense_bond_required;
+        uint256 defense_bond_amount;
+        uint64 defense_bond_window;
         
         mapping(address => Judge) judges;
+        mapping(uint256 => address) judge_list; // registration order, indices never move
+        uint256 judge_count;
         mapping(uint64 => Dispute) disputes;
+        mapping(address => mapping(uint64 => uint64)) deal_disputes; // creator => deal id => dispute id
+        mapping(address => uint256) nonces; // per-signer nonce for signed commits and sponsorship consents
+        
+        mapping(uint64 => Category) categories;
+        mapping(uint256 => uint64) category_list;
+        uint256 category_count;
+        
+        mapping(uint256 => RewardTier) reward_tiers; // ascending by min_reputation
+        uint8 reward_tier_count;
+        
+        uint32 storage_version;  // layout version, bumped one step at a time by migrate()
+        address reputation_oracle; // external reputation registry, zero = use internal reputation
+        uint256 judge_bond;        // locked by each judge joining a panel, 0 = disabled
+        uint16 judge_bond_slash_bps; // share of a losing judge's bond forfeited to the winners
+        
+        mapping(uint256 => uint64) open_for_registration; // ids of disputes waiting for judges, unordered
+        uint256 open_for_registration_count;
+        
+        mapping(address => bool) require_requester_ack; // marketplaces whose disputes need the requester's confirmation
+        uint64 ack_window;       // time a requester has to confirm such a dispute
+        
+        // Monotonic counters for get_protocol_metrics
+        uint64 disputes_resolved;    // ruling, no ruling, default judgment or settlement
+        uint64 disputes_expired;     // expired or voided
+        uint256 total_fees_collected;     // dispute fees received at creation
+        uint256 total_judge_rewards_paid; // rewards (incl. redistributed slashed bonds) and settlement compensation
+        
+        uint64 appeal_window;    // how long a ruling's rewards and reputation changes stay locked, 0 = settleable at once
+        bool allow_contract_judges; // let multisigs and DAOs register as judges
+        address guardian;        // emergency key: may stop things, never move funds or change economics
+        mapping(address => bool) sponsors;  // marketplaces trusted to open disputes on a requester's behalf
+        uint64 migration_cursor; // next dispute the running migration step backfills, 0 before its first page
+        uint256 total_staked;    // sum of all judge stakes
+        uint64 reveal_period;    // time after the commit deadline to reveal, 0 = DEFAULT_REVEAL_PERIOD
+        bool paused;             // no new disputes, judges or panel seats while set
+        #[borrow]
+        Facets facets;           // where the selectors this build doesn't implement are forwarded
+    }
+    
+    pub struct Facets {
+        mapping(bytes4 => address) routes; // selector => facet contract it is delegatecalled into
+        bool in_facet_call;      // set while call_facet runs an internal step in another facet
+    }
+    
+    pub struct RewardTier {
+        int32 min_reputation;
+        uint16 multiplier_bps;   // reward weight, 10_000 = 100%
+    }
+    
+    pub struct Category {
+        uint256 price;
+        uint8 panel_size;
+        bool exists;
     }
     
     pub struct Judge {
         address judge_address;
         uint256 balance;
         int8 reputation;
+        bool active;
+        uint256 stake;
+        uint64 last_assignment_time;
+        bool is_contract;        // had code when registering; excluded from signed commits
+        mapping(uint256 => JudgeHistoryEntry) history; // one entry per settled panel seat, in settlement order
+        uint256 history_count;
+        uint64 open_seats;       // panel seats not yet settled; the stake is locked while any are
+        bool banned;             // set by the owner or guardian; a banned judge can't become available again
+    }
+    
+    pub struct JudgeHistoryEntry {
+        uint64 dispute_id;
+        bool vote_revealed;
+        bool was_majority;
+        int8 reputation_delta;
+        uint256 reward_amount;   // reward share credited for the seat (to the delegate, if delegated)
     }
     
     pub struct Dispute {
-        uint32 dispute_id;
+        // Slots are fixed as of storage v1, like ProtocolContract's: newer fields go at the end
+        // and a retired field stays in place so nothing after it moves. The v1 fields keep
+        // their unpacked order, so most of them still take a slot of their own; only the group
+        // added at v2 shares slots
+        uint32 dispute_id;       // deal id as first stored; superseded by deal_id below
+        uint64 category_id;
+        uint256 price;           // fee received at creation (category price, less any token transfer fee)
+        uint256 escrow_amount;   // fee and bonds still held for this dispute, zero once closed and its panel settled
+        uint8 required_votes;    // snapshot of the category panel size at creation
+        uint64 created_at;
+        uint64 round_started_at; // when the current round's panel started forming
+        uint64 commit_deadline;  // set when the panel fills
+        bool stake_weighted;     // reward distribution mode in force at creation
+        uint16 judge_reward_bps; // share of the fee paid to judges, snapshot at creation
+        uint256 bond_amount;     // defense bond the beneficiary must post, 0 if not required
+        uint64 bond_deadline;
+        bool bond_posted;
+        bool defaulted;          // resolved for the requester because the bond was never posted
         address contract_address;
         address requester;
         address beneficiary;
         mapping(uint256 => address) able_to_vote;
+        mapping(uint256 => int8) reputation_at_assignment_v1; // retired at v2 for the int32 snapshot below
+        mapping(uint256 => uint256) stake_at_assignment;   // judge stake when joining the panel
+        mapping(uint256 => uint16) multiplier_at_assignment; // reward tier multiplier when joining the panel
+        mapping(address => uint256) panel_index;           // panel index + 1, 0 if not on the panel
+        mapping(address => address) delegate_of;           // panel judge => delegate voting for their seat
+        mapping(address => address) delegated_by;          // delegate => panel judge whose seat they hold
         uint256 able_to_vote_count;
         mapping(uint256 => address) voters;
         uint256 voters_count;
         mapping(uint256 => bytes32) vote_commits; // hash(vote, secret)
-        mapping(uint256 => bool) revealed;
-        mapping(uint256 => bool) vote_plain;      // real vote revealed later
+        mapping(address => uint256) commit_index;  // commit index + 1, 0 if not committed
+        mapping(uint256 => bool) revealed;        // retired at v2: a non-zero vote_plain marks the reveal
+        mapping(uint256 => uint8) vote_plain;     // revealed vote (VOTE_FOR / VOTE_AGAINST / VOTE_ABSTAIN), 0 until revealed
         uint256 commits_count;
         uint256 reveals_count;
         uint8 votes_for;
         uint8 votes_against;
+        uint8 votes_abstain;
         bool waiting_for_judges;
         bool is_open;
         bool resolved;
+        bool expired;
+        bool no_ruling;          // resolved without a winner (everyone abstained), fee refunded
+        uint64 resolved_at;      // when the dispute resolved or expired, 0 while pending
+        uint8 resolution_kind;   // RESOLUTION_* path that closed the dispute
+        address settlement_proposer;   // party with a pending settlement proposal
+        bool settlement_requester_wins; // outcome proposed (or agreed, once settled)
+        bool settled;            // resolved by agreement between the parties
+        uint8 round;             // re-vote round after ties, 0 for the first panel
+        mapping(address => bool) served; // judges from earlier rounds, barred from re-joining
+        
+        // Added at v2, small fields first so they share two slots
+        address fee_payer;       // who paid the fee and receives refunds, when not the party of record
+        uint64 panel_unlock_at;  // when the pending panel settlement can be released
+        bool commit_phase_closed; // commit set frozen after the deadline with seats still missing
+        bool pending_ack;        // waiting for the requester to confirm a marketplace-created dispute
+        bool panel_pending;      // ruling's rewards and reputation changes not applied yet
+        bool reward_by_stake;    // winners' shares weighted by stake (stake-weighted mode with stake at risk)
+        uint64 ack_deadline;     // confirmation deadline while pending_ack
+        uint16 judge_bond_slash_bps; // share of a losing judge's bond forfeited, snapshot at creation
+        uint256 nominal_price;   // category price charged at creation
+        uint256 pending_rewards; // judge rewards held until the appeal window lapses
+        uint256 reward_pool;     // judges' share of the fee plus forfeited bonds, fixed at the ruling
+        uint256 reward_weight;   // total weight of the winning seats the pool is split over
+        uint256 bonds_locked;    // judge bonds still held across the panel
+        uint256 committed_bonds; // bonds of the seats that committed this round
+        uint256 seats_settled;   // panel seats already settled after the ruling
+        uint256 open_for_registration_slot; // position in the open-for-registration index + 1, 0 if not listed
+        mapping(uint256 => int32) reputation_at_assignment; // judge reputation when joining the panel
+        mapping(uint256 => uint256) bond_at_assignment;      // judge bond locked for the seat, 0 once settled
+        mapping(uint256 => bool) seat_settled;               // seat's bond, reward and reputation applied
+        mapping(uint8 => uint256) side_weight;       // per vote: sum of revealed seats' tier multipliers
+        mapping(uint8 => uint256) side_stake;        // per vote: sum of revealed seats' stakes
+        mapping(uint8 => uint256) side_stake_weight; // per vote: sum of stake times multiplier
+        mapping(uint8 => uint256) side_slash;        // per vote: bond forfeited by those seats if they lose
+        mapping(address => string) proofs; // latest proof submitted by (or relayed for) each party
+        mapping(bytes32 => bool) revealed_commits; // every commitment ever revealed, kept across re-vote rounds
+        
+        // Appeals
+        uint64 appeal_of;        // dispute whose ruling this one appeals, 0 if not an appeal
+        uint64 appealed_by;      // appeal filed against this dispute's ruling, 0 if none
+        bool overturned;         // ruling reversed on appeal
+        
+        uint64 deal_id;          // full deal id; zero on disputes opened before it was added
+        uint256 carried_bonds;   // bonds of tied rounds' seats, held until the final panel settles
+        uint64 reveal_deadline;  // set with the commit deadline; finalize_dispute closes the vote after it
     }
 }
 
@@ -77,8 +242,50 @@ sol_storage! {
 
 sol! {
     event JudgeRegistered(address indexed judge);
-    event DisputeCreated(uint256 indexed dispute_id, address indexed requester, address indexed contract_address);
-    event DisputeResolved(uint256 indexed dispute_id, address winner);
+    event ContractJudgesAllowed(bool allowed);
+    event JudgeImported(address indexed judge, int32 reputation);
+    event JudgeReplaced(uint256 indexed dispute_id, address indexed old_judge, address indexed new_judge);
+    event JudgeBondUpdated(uint256 amount, uint16 slash_bps);
+    event JudgeBondForfeited(uint256 indexed dispute_id, address indexed judge, uint256 amount);
+    event JudgesSettled(uint256 indexed dispute_id, uint256 seats_settled, uint256 paid);
+    event DisputeCreated(uint256 indexed dispute_id, uint64 deal_id, address indexed requester, address beneficiary, address indexed contract_address);
+    event DisputeOpened(uint256 indexed dispute_id, uint64 commit_deadline);
+    event DisputeResolved(uint256 indexed dispute_id, address winner, bool requester_won, uint8 votes_for, uint8 votes_against);
+    event SettlementProposed(uint256 indexed dispute_id, address indexed proposer, bool requester_wins);
+    event DisputeSettled(uint256 indexed dispute_id, bool requester_wins);
+    event DisputeRevote(uint256 indexed dispute_id, uint8 round);
+    event DisputeAppealed(uint256 indexed dispute_id, uint256 indexed appeal_id, address indexed appellant);
+    event AppealConcluded(uint256 indexed dispute_id, uint256 indexed appeal_id, bool overturned);
+    event DefenseBondPosted(uint256 indexed dispute_id, address indexed beneficiary, uint256 amount);
+    event DefaultJudgment(uint256 indexed dispute_id, address indexed requester);
+    event VoteDelegated(uint256 indexed dispute_id, address indexed from, address indexed to);
+    event DisputeRefunded(uint256 indexed dispute_id, address refunded_to, uint256 amount);
+    event DisputeExpired(uint256 indexed dispute_id, address refunded_to, uint256 amount);
+    event CommitPhaseClosed(uint256 indexed dispute_id, uint256 commits, bool quorate);
+    event RevealPhaseClosed(uint256 indexed dispute_id, uint256 reveals);
+    event MinReputationUpdated(int32 min_reputation);
+    event ReputationOracleUpdated(address indexed old_oracle, address indexed new_oracle);
+    event NumberOfVotesUpdated(uint8 old_number, uint8 new_number);
+    event JudgeAvailabilityChanged(address indexed judge, bool active);
+    event JudgeStaked(address indexed judge, uint256 amount, uint256 stake);
+    event JudgeUnstaked(address indexed judge, uint256 amount, uint256 stake);
+    event CategoryUpdated(uint64 indexed category_id, uint256 price, uint8 panel_size);
+    event FeeTokenUpdated(address indexed old_token, address indexed new_token);
+    event SponsorUpdated(address indexed sponsor, bool allowed);
+    event RequesterAckRequired(address indexed marketplace, bool required);
+    event DisputeConfirmed(uint256 indexed dispute_id, address indexed requester);
+    event DisputeVoided(uint256 indexed dispute_id, address refunded_to, uint256 amount);
+    event ProofSubmitted(uint256 indexed dispute_id, address indexed party, address indexed submitted_by, string proof);
+    event DisputeSponsored(uint256 indexed dispute_id, address indexed sponsor, address indexed requester);
+    event TokensRescued(address indexed token, address indexed to, uint256 amount);
+    event WithdrawalQueued(uint256 amount, uint64 unlock_time);
+    event WithdrawalCancelled(uint256 amount);
+    event OwnerWithdrawn(address indexed to, uint256 amount);
+    event Migrated(uint32 from, uint32 to);
+    event GuardianUpdated(address indexed old_guardian, address indexed new_guardian);
+    event PauseChanged(address indexed by, bool paused);
+    event JudgeBanned(address indexed judge, bool banned);
+    event FacetUpdated(address indexed facet, bytes4[] selectors);
     
     error NotOwner();
     error AlreadyRegistered();
@@ -94,35 +301,320 @@ sol! {
     error DisputeNotOpen();
     error JudgeAlreadyVoted();
     error MustBeGreaterThanZero();
+    error InsufficientBalance();
     error DisputeNotResolvedYet();
     error NoBalanceToWithdraw();
     error NoUSDCToWithdraw();
+    error TooManyIds();
+    error InvalidCategory();
+    error CategoryDoesNotExist();
+    error JudgeNotActive();
+    error ProtocolPaused();
+    error JudgeIsBanned();
+    error DisputeIsExpired();
+    error PanelTimeoutNotReached();
+    error AllVotesCommitted();
+    error CommitPhaseNotEnded();
+    error RevealPhaseNotEnded();
+    error CommitPhaseEnded();
+    error CommitPhaseAlreadyClosed();
+    error InvalidVote();
+    error NoRuling();
+    error DisputeTied();
+    error InvalidDelegate();
+    error SeatAlreadyDelegated();
+    error NoBondRequired();
+    error BondAlreadyPosted();
+    error BondWindowClosed();
+    error BondWindowNotEnded();
+    error DefenseBondNotPosted();
+    error InvalidBps();
+    error InvalidSignature();
+    error SignatureExpired();
+    error JudgeOnCooldown();
+    error CommitMismatch();
+    error InvalidAddress();
+    error OutstandingClaims();
+    error RescueExceedsAvailable();
+    error NotAParty();
+    error SettlementBlocked();
+    error JudgeAlreadyServed();
+    error JudgeNotOnPanel();
+    error JudgeAlreadyOnPanel();
+    error LengthMismatch();
+    error ReputationOutOfRange();
+    error MigrationIsLocked();
+    error InvalidPanelSize();
+    error CounterOverflow();
+    error UnsupportedStorageVersion();
+    error MigrationGap();
+    error StakeLocked();
+    error InvalidRewardTiers();
+    error DisputeAlreadyExistsForDeal();
+    error WithdrawalAlreadyQueued();
+    error NoWithdrawalQueued();
+    error WithdrawalLocked();
+    error DelayTooShort();
+    error DisputeNotConfirmed();
+    error DisputeNotPendingAck();
+    error AckWindowClosed();
+    error AckWindowNotEnded();
+    error NotOriginatingContract();
+    error NoPendingSettlement();
+    error WeakSecret();
+    error CommitmentAlreadyRevealed();
+    error ContractJudgesNotAllowed();
+    error DisputeDoesNotExist();
+    error NotOwnerOrGuardian();
+    error SettlementLocked();
+    error AppealNotAllowed();
+    error AppealWindowClosed();
+    error AlreadyInitialized();
+    error UnknownSelector();
+    error NotAFacetCall();
+}
+
+// ====================================
+//          VIEW STRUCTS          
+// ====================================
+
+sol! {
+    #[derive(AbiType)]
+    struct ProtocolMetrics {
+        uint64 disputes_created;
+        uint64 disputes_open;
+        uint64 disputes_resolved;
+        uint64 disputes_expired;
+        uint256 total_fees_collected;
+        uint256 total_judge_rewards_paid;
+        uint256 treasury_surplus;
+    }
+    
+    #[derive(AbiType)]
+    struct JudgeHistoryInfo {
+        uint64 dispute_id;
+        bool vote_revealed;
+        bool was_majority;
+        uint256 reward_amount;
+        int8 reputation_delta;
+    }
+    
+    #[derive(AbiType)]
+    struct DisputeFinancials {
+        uint256 nominal_price;
+        uint256 amount_received;
+        address fee_payer;
+        uint16 protocol_fee_bps;
+        uint16 judge_reward_bps;
+        uint256 escrow_remaining;
+    }
+    
+    #[derive(AbiType)]
+    struct DisputeInfo {
+        uint64 dispute_id;
+        uint64 deal_id;
+        uint64 category_id;
+        address contract_address;
+        address requester;
+        address beneficiary;
+        uint256 price;
+        uint8 required_votes;
+        uint64 created_at;
+        uint64 commit_deadline;
+        uint8 phase;
+        bool waiting_for_judges;
+        bool is_open;
+        bool resolved;
+        bool expired;
+        bool no_ruling;
+        uint8 votes_for;
+        uint8 votes_against;
+        uint8 votes_abstain;
+        uint64 resolved_at;
+        uint8 resolution_kind;
+    }
+    
+    #[derive(AbiType)]
+    struct JudgeInfo {
+        address judge_address;
+        uint256 balance;
+        int32 reputation;
+        bool active;
+        uint256 stake;
+        uint64 last_assignment_time;
+        bool is_contract;
+    }
+}
+
+// Make the view structs visible to `export-abi` so bindings pick them up
+#[cfg(feature = "export-abi")]
+macro_rules! impl_inner_types {
+    ($($name:ident),*) => {
+        $(
+            impl stylus_sdk::abi::export::internal::InnerTypes for $name {
+                fn inner_types() -> Vec<stylus_sdk::abi::export::internal::InnerType> {
+                    use alloy_sol_types::SolStruct;
+                    // eip712 root type is "Name(type a,type b,...)"
+                    let root = <$name as SolStruct>::eip712_root_type();
+                    let fields = &root[stringify!($name).len() + 1..root.len() - 1];
+                    alloc::vec![stylus_sdk::abi::export::internal::InnerType {
+                        name: alloc::format!("struct {} {{ {}; }}", stringify!($name), fields.replace(',', "; ")),
+                        id: core::any::TypeId::of::<$name>(),
+                    }]
+                }
+            }
+        )*
+    };
 }
 
+#[cfg(feature = "export-abi")]
+impl_inner_types!(ProtocolMetrics, JudgeHistoryInfo, DisputeFinancials, DisputeInfo, JudgeInfo);
+
 // ====================================
 //          ERROR TYPES          
 // ====================================
 
-#[derive(SolidityError)]
-pub enum ProtocolError {
-    NotOwner(NotOwner),
-    AlreadyRegistered(AlreadyRegistered),
-    NotAJudge(NotAJudge),
-    NotTheRequester(NotTheRequester),
-    ProofCannotBeEmpty(ProofCannotBeEmpty),
-    NotTheBeneficiary(NotTheBeneficiary),
-    NotEnoughReputation(NotEnoughReputation),
-    JudgesAlreadyAssigned(JudgesAlreadyAssigned),
-    JudgeAlreadyRegistered(JudgeAlreadyRegistered),
-    JudgeNotAllowedToVote(JudgeNotAllowedToVote),
-    DisputeAlreadyResolved(DisputeAlreadyResolved),
-    DisputeNotOpen(DisputeNotOpen),
-    JudgeAlreadyVoted(JudgeAlreadyVoted),
-    MustBeGreaterThanZero(MustBeGreaterThanZero),
-    DisputeNotResolvedYet(DisputeNotResolvedYet),
-    NoBalanceToWithdraw(NoBalanceToWithdraw),
-    NoUSDCToWithdraw(NoUSDCToWithdraw),
-    CallFailed(CallFailed),
+// Stands in for #[derive(SolidityError)]. Every error here is argument-free, so the revert data
+// is the bare selector; the derive would instead inline a full ABI encoder per variant, which
+// every facet build pays for.
+macro_rules! selector_errors {
+    ($vis:vis enum $name:ident { $($variant:ident($error:ident)),* $(,)? }) => {
+        $vis enum $name {
+            $($variant($error)),*
+        }
+
+        $(
+            impl From<$error> for $name {
+                fn from(value: $error) -> Self {
+                    $name::$variant(value)
+                }
+            }
+        )*
+
+        impl From<$name> for Vec<u8> {
+            fn from(err: $name) -> Self {
+                use alloy_sol_types::SolError;
+                let selector = match err {
+                    $($name::$variant(_) => <$error as SolError>::SELECTOR),*
+                };
+                selector.to_vec()
+            }
+        }
+
+        impl $name {
+            /// Error a facet reverted with, read back from its revert data
+            fn from_revert(data: &[u8]) -> Option<Self> {
+                use alloy_sol_types::SolError;
+                let selectors = [$(<$error as SolError>::SELECTOR),*];
+                let index = selectors.iter().position(|selector| data == selector)?;
+                [$($name::$variant($error {})),*].into_iter().nth(index)
+            }
+        }
+
+        #[cfg(feature = "export-abi")]
+        impl stylus_sdk::abi::export::internal::InnerTypes for $name {
+            fn inner_types() -> Vec<stylus_sdk::abi::export::internal::InnerType> {
+                use alloy_sol_types::SolError;
+                alloc::vec![
+                    $(stylus_sdk::abi::export::internal::InnerType {
+                        name: alloc::format!("error {};", <$error as SolError>::SIGNATURE),
+                        id: core::any::TypeId::of::<$error>(),
+                    }),*
+                ]
+            }
+        }
+    };
+}
+
+selector_errors! {
+    pub enum ProtocolError {
+        NotOwner(NotOwner),
+        AlreadyRegistered(AlreadyRegistered),
+        NotAJudge(NotAJudge),
+        NotTheRequester(NotTheRequester),
+        ProofCannotBeEmpty(ProofCannotBeEmpty),
+        NotTheBeneficiary(NotTheBeneficiary),
+        NotEnoughReputation(NotEnoughReputation),
+        JudgesAlreadyAssigned(JudgesAlreadyAssigned),
+        JudgeAlreadyRegistered(JudgeAlreadyRegistered),
+        JudgeNotAllowedToVote(JudgeNotAllowedToVote),
+        DisputeAlreadyResolved(DisputeAlreadyResolved),
+        DisputeNotOpen(DisputeNotOpen),
+        JudgeAlreadyVoted(JudgeAlreadyVoted),
+        MustBeGreaterThanZero(MustBeGreaterThanZero),
+        InsufficientBalance(InsufficientBalance),
+        DisputeNotResolvedYet(DisputeNotResolvedYet),
+        NoBalanceToWithdraw(NoBalanceToWithdraw),
+        NoUSDCToWithdraw(NoUSDCToWithdraw),
+        TooManyIds(TooManyIds),
+        InvalidCategory(InvalidCategory),
+        CategoryDoesNotExist(CategoryDoesNotExist),
+        JudgeNotActive(JudgeNotActive),
+        ProtocolPaused(ProtocolPaused),
+        JudgeIsBanned(JudgeIsBanned),
+        DisputeIsExpired(DisputeIsExpired),
+        PanelTimeoutNotReached(PanelTimeoutNotReached),
+        AllVotesCommitted(AllVotesCommitted),
+        CommitPhaseNotEnded(CommitPhaseNotEnded),
+        RevealPhaseNotEnded(RevealPhaseNotEnded),
+        CommitPhaseEnded(CommitPhaseEnded),
+        CommitPhaseAlreadyClosed(CommitPhaseAlreadyClosed),
+        InvalidVote(InvalidVote),
+        NoRuling(NoRuling),
+        DisputeTied(DisputeTied),
+        InvalidDelegate(InvalidDelegate),
+        SeatAlreadyDelegated(SeatAlreadyDelegated),
+        NoBondRequired(NoBondRequired),
+        BondAlreadyPosted(BondAlreadyPosted),
+        BondWindowClosed(BondWindowClosed),
+        BondWindowNotEnded(BondWindowNotEnded),
+        DefenseBondNotPosted(DefenseBondNotPosted),
+        InvalidBps(InvalidBps),
+        InvalidSignature(InvalidSignature),
+        SignatureExpired(SignatureExpired),
+        JudgeOnCooldown(JudgeOnCooldown),
+        CommitMismatch(CommitMismatch),
+        InvalidAddress(InvalidAddress),
+        OutstandingClaims(OutstandingClaims),
+        RescueExceedsAvailable(RescueExceedsAvailable),
+        NotAParty(NotAParty),
+        SettlementBlocked(SettlementBlocked),
+        JudgeAlreadyServed(JudgeAlreadyServed),
+        JudgeNotOnPanel(JudgeNotOnPanel),
+        JudgeAlreadyOnPanel(JudgeAlreadyOnPanel),
+        LengthMismatch(LengthMismatch),
+        ReputationOutOfRange(ReputationOutOfRange),
+        MigrationIsLocked(MigrationIsLocked),
+        InvalidPanelSize(InvalidPanelSize),
+        CounterOverflow(CounterOverflow),
+        UnsupportedStorageVersion(UnsupportedStorageVersion),
+        MigrationGap(MigrationGap),
+        StakeLocked(StakeLocked),
+        InvalidRewardTiers(InvalidRewardTiers),
+        DisputeAlreadyExistsForDeal(DisputeAlreadyExistsForDeal),
+        WithdrawalAlreadyQueued(WithdrawalAlreadyQueued),
+        NoWithdrawalQueued(NoWithdrawalQueued),
+        WithdrawalLocked(WithdrawalLocked),
+        DelayTooShort(DelayTooShort),
+        DisputeNotConfirmed(DisputeNotConfirmed),
+        DisputeNotPendingAck(DisputeNotPendingAck),
+        AckWindowClosed(AckWindowClosed),
+        AckWindowNotEnded(AckWindowNotEnded),
+        NotOriginatingContract(NotOriginatingContract),
+        NoPendingSettlement(NoPendingSettlement),
+        WeakSecret(WeakSecret),
+        CommitmentAlreadyRevealed(CommitmentAlreadyRevealed),
+        ContractJudgesNotAllowed(ContractJudgesNotAllowed),
+        DisputeDoesNotExist(DisputeDoesNotExist),
+        NotOwnerOrGuardian(NotOwnerOrGuardian),
+        SettlementLocked(SettlementLocked),
+        AppealNotAllowed(AppealNotAllowed),
+        AppealWindowClosed(AppealWindowClosed),
+        AlreadyInitialized(AlreadyInitialized),
+        UnknownSelector(UnknownSelector),
+        NotAFacetCall(NotAFacetCall),
+        CallFailed(CallFailed),
+    }
 }
 
 sol! {
@@ -137,6 +629,7 @@ impl From<CallError> for ProtocolError {
 }
 
 // Implement From for old stylus_sdk::call::Error (deprecated but still used by ERC20)
+#[allow(deprecated)]
 impl From<stylus_sdk::call::Error> for ProtocolError {
     fn from(_error: stylus_sdk::call::Error) -> Self {
         ProtocolError::CallFailed(CallFailed {})
@@ -148,41 +641,173 @@ impl From<stylus_sdk::call::Error> for ProtocolError {
 // ====================================
 
 const USDC_DECIMALS: u8 = 6;
+const MAX_BATCH_SIZE: usize = 100;
+const DEFAULT_PANEL_TIMEOUT: u64 = 7 * 24 * 60 * 60; // 7 days
+const DEFAULT_COMMIT_PERIOD: u64 = 3 * 24 * 60 * 60; // 3 days
+const DEFAULT_REVEAL_PERIOD: u64 = 2 * 24 * 60 * 60; // 2 days
+const DEFAULT_ACK_WINDOW: u64 = 3 * 24 * 60 * 60; // 3 days
+const MIN_WITHDRAW_DELAY: u64 = 24 * 60 * 60; // 24 hours
+const BPS_DENOMINATOR: u16 = 10_000;
+const DEFAULT_JUDGE_REWARD_BPS: u16 = 8_000; // 80% to judges, 20% to treasury
+const SETTLEMENT_REFUND_BPS: u16 = 5_000; // half the fee goes back when parties settle
+const SETTLEMENT_COMPENSATION_BPS: u16 = 1_000; // split among judges who already committed
+const MAX_VOTE_ROUNDS: u8 = 2; // panels tried before a tie ends without a ruling
+const MIN_SECRET_LENGTH: usize = 16; // bytes; shorter secrets make commits brute-forceable
+const MAX_PANEL_SIZE: u8 = 25; // keeps u8 tallies and per-dispute loops bounded
+const STORAGE_VERSION: u32 = 2; // bump with a matching step in migrate() when the layout changes
+const MAX_REWARD_TIERS: usize = 3;
+
+// Vote options for reveal_votes
+const VOTE_FOR: u8 = 1; // requester/payer wins
+const VOTE_AGAINST: u8 = 2; // beneficiary/seller wins
+const VOTE_ABSTAIN: u8 = 3;
+
+// Dispute phases returned by get_dispute_phase
+const PHASE_NOT_FOUND: u8 = 0;
+const PHASE_WAITING_FOR_JUDGES: u8 = 1;
+const PHASE_COMMIT: u8 = 2;
+const PHASE_REVEAL: u8 = 3;
+const PHASE_RESOLVED: u8 = 4;
+// 5 is reserved for Cancelled
+const PHASE_EXPIRED: u8 = 6;
+const PHASE_PENDING_ACK: u8 = 7;
+
+// How a dispute was closed, recorded in resolution_kind (0 while pending)
+const RESOLUTION_RULING: u8 = 1;
+const RESOLUTION_NO_RULING: u8 = 2;
+const RESOLUTION_DEFAULT: u8 = 3;
+const RESOLUTION_SETTLEMENT: u8 = 4;
+const RESOLUTION_EXPIRED: u8 = 5;
+const RESOLUTION_VOIDED: u8 = 6;
+
+// Dispute outcomes returned by get_dispute_outcome
+const OUTCOME_PENDING: u8 = 0;
+const OUTCOME_REQUESTER_WON: u8 = 1;
+const OUTCOME_BENEFICIARY_WON: u8 = 2;
+const OUTCOME_NO_RULING: u8 = 3; // every judge abstained, fee refunded
+const OUTCOME_TIED: u8 = 4;      // the last vote round tied, fee refunded
+const OUTCOME_EXPIRED: u8 = 5;   // the panel never formed, too few judges committed, or none revealed by the deadline
+
+// EIP-712 signed commits (commit_vote_by_sig)
+const EIP712_NAME: &str = "Lancer Protocol";
+const EIP712_VERSION: &str = "1";
+const EIP712_DOMAIN_TYPE: &str = "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+const COMMIT_TYPE: &str = "Commit(uint64 disputeId,bytes32 commitHash,uint256 nonce,uint256 deadline)";
+const SPONSOR_TYPE: &str = "Sponsor(uint64 dealId,address beneficiary,uint64 categoryId,address sponsor,uint256 nonce,uint256 deadline)";
+const ECRECOVER: Address = Address::new([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+// Interface detection (supports_interface); ids are the XOR of the member selectors
+const ERC165_INTERFACE_ID: [u8; 4] = function_selector!("supportsInterface", FixedBytes<4>);
+const DISPUTE_INTERFACE_ID: [u8; 4] = xor_selectors(&[
+    function_selector!("createDispute", u64, Address, String, u64),
+    function_selector!("getDisputeWinner", u64),
+    function_selector!("getDisputeOutcome", u64),
+    function_selector!("checkIfDisputeIsResolved", u64),
+    function_selector!("getDisputeIdForDeal", Address, u64),
+]);
+
+const fn xor_selectors(selectors: &[[u8; 4]]) -> [u8; 4] {
+    let mut id = [0u8; 4];
+    let mut i = 0;
+    while i < selectors.len() {
+        let mut b = 0;
+        while b < 4 {
+            id[b] ^= selectors[i][b];
+            b += 1;
+        }
+        i += 1;
+    }
+    id
+}
 
 // ====================================
 //      EXTERNAL INTERFACE CALLS          
 // ====================================
 
-sol_interface! {
-    interface IERC20 {
-        function transferFrom(address from, address to, uint256 amount) external returns (bool);
-        function transfer(address to, uint256 amount) external returns (bool);
-        function balanceOf(address account) external view returns (uint256);
+// permit() takes more arguments than clippy allows in the generated bindings
+#[allow(clippy::too_many_arguments)]
+mod interfaces {
+    use stylus_sdk::prelude::*;
+    
+    sol_interface! {
+        interface IERC20 {
+            function transferFrom(address from, address to, uint256 amount) external returns (bool);
+            function transfer(address to, uint256 amount) external returns (bool);
+            function balanceOf(address account) external view returns (uint256);
+            function allowance(address owner, address spender) external view returns (uint256);
+            function permit(address owner, address spender, uint256 value, uint256 deadline, uint8 v, bytes32 r, bytes32 s) external;
+        }
+        
+        interface IReputationOracle {
+            function getReputation(address judge) external view returns (int256);
+        }
+    }
+}
+
+pub use interfaces::{IERC20, IReputationOracle};
+
+// Internal steps one facet hands to another through call_facet
+mod steps {
+    alloy_sol_types::sol! {
+        function resolveRound(uint64 dispute_id);
     }
 }
 
 // ====================================
-//        IMPLEMENTATION          
+//          FACET ROUTING          
 // ====================================
 
+// The contract is deployed as a router (built without any facet feature) plus one contract per
+// facet feature in Cargo.toml, each built with only its own block of methods below. The router
+// owns all storage and delegatecalls every selector it doesn't implement into the facet
+// registered for it with set_facet.
 #[public]
+impl Facets {
+    /// Forward a call to the facet registered for its selector
+    #[fallback]
+    pub fn forward(&mut self, input: &[u8]) -> ArbResult {
+        let selector = FixedBytes::<4>::try_from(input.get(..4).unwrap_or_default()).unwrap_or_default();
+        let facet = self.routes.get(selector);
+        if facet == Address::ZERO {
+            return Err(ProtocolError::UnknownSelector(UnknownSelector {}).into());
+        }
+        unsafe { RawCall::new_delegate().clear_storage_cache().call(facet, input) }
+    }
+}
+
+// ====================================
+//        ROUTER          
+// ====================================
+
+#[cfg_attr(not(feature = "facet"), public, inherit(Facets))]
 impl ProtocolContract {
-    
-    // ====================================
-    //           CONSTRUCTOR          
-    // ====================================
-    
-    /// Initialize the protocol contract
+    /// Initialize the protocol contract. Callable once; an existing owner or storage version means
+    /// the contract is already set up, and a zero owner would leave it open to be initialized again
     pub fn init(
         &mut self,
         owner: Address,
         usdc: Address,
     ) -> Result<(), ProtocolError> {
+        if self.owner.get() != Address::ZERO || self.storage_version.get() != U32::ZERO {
+            return Err(ProtocolError::AlreadyInitialized(AlreadyInitialized {}));
+        }
+        if owner == Address::ZERO || usdc == Address::ZERO {
+            return Err(ProtocolError::InvalidAddress(InvalidAddress {}));
+        }
+        
         self.owner.set(owner);
         self.usdc_token.set(usdc);
         self.contract_balance.set(U256::ZERO);
         self.dispute_count.set(U64::from(1));
         self.number_of_votes.set(U8::from(5));
+        self.min_reputation.set(I32::ZERO);
+        self.panel_timeout.set(U64::from(DEFAULT_PANEL_TIMEOUT));
+        self.commit_period.set(U64::from(DEFAULT_COMMIT_PERIOD));
+        self.reveal_period.set(U64::from(DEFAULT_REVEAL_PERIOD));
+        self.ack_window.set(U64::from(DEFAULT_ACK_WINDOW));
+        self.judge_reward_bps.set(U16::from(DEFAULT_JUDGE_REWARD_BPS));
+        self.withdraw_delay.set(U64::from(MIN_WITHDRAW_DELAY));
+        self.storage_version.set(U32::from(STORAGE_VERSION));
         
         // 50 USDC with 6 decimals
         let dispute_price = U256::from(50u64) * U256::from(10u64.pow(USDC_DECIMALS as u32));
@@ -191,116 +816,111 @@ impl ProtocolContract {
         Ok(())
     }
     
-    // ====================================
-    //        ONLY-OWNER FUNCTIONS          
-    // ====================================
-    
-    /// Update the number of votes required to resolve a dispute
-    pub fn update_number_of_votes(&mut self, new_number: u8) -> Result<(), ProtocolError> {
-        if self.__stylus_host.msg_sender() != self.owner.get() {
-            return Err(ProtocolError::NotOwner(NotOwner {}));
-        }
-        
-        if new_number == 0 {
-            return Err(ProtocolError::MustBeGreaterThanZero(MustBeGreaterThanZero {}));
-        }
+    /// Route selectors to a facet contract (only owner; a zero facet removes the routes).
+    /// Selectors the router implements itself never reach the routes.
+    pub fn set_facet(&mut self, facet: Address, selectors: Vec<FixedBytes<4>>) -> Result<(), ProtocolError> {
+        self.only_owner()?;
         
-        self.number_of_votes.set(U8::from(new_number));
-        Ok(())
-    }
-    
-    /// Withdraw available USDC (excludes judge rewards)
-    pub fn withdraw(&mut self) -> Result<(), ProtocolError> {
-        let sender = self.__stylus_host.msg_sender();
-        if sender != self.owner.get() {
-            return Err(ProtocolError::NotOwner(NotOwner {}));
+        for selector in &selectors {
+            self.facets.routes.setter(*selector).set(facet);
         }
         
-        // COMMENTED OUT FOR TESTING - USDC transfer logic
-        // let usdc = self.usdc_token.get();
-        // let contract_addr = self.__stylus_host.contract_address();
-        // let token = IERC20::new(usdc);
-        // let call = Call::new_in(self);
-        // let balance = token.balance_of(call, contract_addr)?;
-        
-        // let contract_balance = self.contract_balance.get();
-        
-        // if balance <= contract_balance {
-        //     return Err(ProtocolError::NoUSDCToWithdraw(NoUSDCToWithdraw {}));
-        // }
-        
-        // let amount_to_withdraw = balance - contract_balance;
-        
-        // Reset contract balance
-        self.contract_balance.set(U256::ZERO);
-        
-        // Transfer to owner
-        // let token2 = IERC20::new(usdc);
-        // let call2 = Call::new_in(self);
-        // let success = token2.transfer(call2, sender, amount_to_withdraw)?;
-        
-        // if !success {
-        //     return Err(ProtocolError::CallFailed(CallFailed {}));
-        // }
+        log(&self.__stylus_host, FacetUpdated { facet, selectors });
         
         Ok(())
     }
     
-    // ====================================
-    //         EXTERNAL FUNCTIONS          
-    // ====================================
+    /// Get the contract version
+    pub fn version(&self) -> String {
+        String::from(env!("CARGO_PKG_VERSION"))
+    }
     
-    /// Register as a judge
-    pub fn register_as_judge(&mut self) -> Result<(), ProtocolError> {
-        let sender = self.__stylus_host.msg_sender();
-        let judge = self.judges.get(sender);
-        
-        if judge.judge_address.get() != Address::ZERO {
-            return Err(ProtocolError::AlreadyRegistered(AlreadyRegistered {}));
-        }
-        
-        let mut new_judge = self.judges.setter(sender);
-        new_judge.judge_address.set(sender);
-        new_judge.balance.set(U256::ZERO);
-        new_judge.reputation.set(I8::ZERO);
-        
-        log(&self.__stylus_host, JudgeRegistered { judge: sender });
-        
-        Ok(())
+    /// Get owner address
+    pub fn owner(&self) -> Address {
+        self.owner.get()
     }
     
+    /// Get the facet a selector is routed to (zero if none)
+    pub fn facet_address(&self, selector: FixedBytes<4>) -> Address {
+        self.facets.routes.get(selector)
+    }
+}
+
+// ====================================
+//        DISPUTES FACET          
+// ====================================
+
+#[cfg_attr(feature = "disputes", public)]
+impl ProtocolContract {
     /// Create a dispute (called by Marketplace contract)
+    /// The marketplace must have approved Protocol to spend the category price
     pub fn create_dispute(
         &mut self,
         deal_id: u64,
         requester: Address,
         _proof: String,
+        category_id: u64,
     ) -> Result<(), ProtocolError> {
+        let (price, required_votes) = self.category_terms(category_id)?;
+        let now = self.__stylus_host.block_timestamp();
+        let stake_weighted = self.stake_weighted_rewards.get();
+        let judge_reward_bps = self.judge_reward_bps.get();
+        let judge_bond_slash_bps = self.judge_bond_slash_bps.get();
+        let (bond_amount, bond_deadline) = self.defense_bond_terms(now);
+        
+        // The marketplace pays the fee; like direct disputes, only what arrives is escrowed
+        let nominal_price = price;
+        let price = self.pull_tokens(self.__stylus_host.msg_sender(), price)?;
+        
         let dispute_id = self.dispute_count.get();
         let dispute_id_u64 = u64::from_le_bytes(dispute_id.to_le_bytes());
+        self.record_deal(self.__stylus_host.msg_sender(), deal_id, dispute_id)?;
         
+        // Dispute ids are never reused, so fields left out here are already zero
         let mut dispute = self.disputes.setter(dispute_id);
-        dispute.dispute_id.set(U32::from(deal_id));
+        dispute.deal_id.set(U64::from(deal_id));
+        if let Ok(legacy_deal_id) = u32::try_from(deal_id) {
+            dispute.dispute_id.set(U32::from(legacy_deal_id));
+        }
+        dispute.category_id.set(U64::from(category_id));
+        dispute.nominal_price.set(nominal_price);
+        dispute.price.set(price);
+        dispute.required_votes.set(required_votes);
+        dispute.created_at.set(U64::from(now));
+        dispute.round_started_at.set(U64::from(now));
+        dispute.stake_weighted.set(stake_weighted);
+        dispute.judge_reward_bps.set(judge_reward_bps);
+        dispute.judge_bond_slash_bps.set(judge_bond_slash_bps);
+        dispute.bond_amount.set(bond_amount);
+        dispute.bond_deadline.set(bond_deadline);
         dispute.requester.set(requester);
         dispute.beneficiary.set(Address::ZERO); // TODO: Get from marketplace
         dispute.contract_address.set(self.__stylus_host.msg_sender());
-        dispute.waiting_for_judges.set(true);
-        dispute.is_open.set(false);
-        dispute.resolved.set(false);
-        dispute.votes_for.set(U8::ZERO);
-        dispute.votes_against.set(U8::ZERO);
-        dispute.able_to_vote_count.set(U256::ZERO);
-        dispute.voters_count.set(U256::ZERO);
+        
+        // Marketplaces in ack mode need the requester to confirm before judges can join
+        if self.require_requester_ack.get(self.__stylus_host.msg_sender()) {
+            let ack_deadline = U64::from(now) + self.ack_window.get();
+            let mut dispute = self.disputes.setter(dispute_id);
+            dispute.pending_ack.set(true);
+            dispute.ack_deadline.set(ack_deadline);
+        } else {
+            self.set_waiting_for_judges(dispute_id_u64, true);
+        }
         
         log(&self.__stylus_host, DisputeCreated {
             dispute_id: U256::from(dispute_id_u64),
+            deal_id,
             requester,
+            beneficiary: Address::ZERO,
             contract_address: self.__stylus_host.msg_sender(),
         });
         
         // Increment counter
         let current_counter = self.dispute_count.get();
         self.dispute_count.set(current_counter + U64::from(1));
+        self.add_escrow(dispute_id_u64, price);
+        let fees_collected = self.total_fees_collected.get();
+        self.total_fees_collected.set(fees_collected + price);
         
         Ok(())
     }
@@ -312,125 +932,625 @@ impl ProtocolContract {
         deal_id: u64,
         beneficiary: Address,
         _proof: String,
+        category_id: u64,
+    ) -> Result<(), ProtocolError> {
+        let sender = self.__stylus_host.msg_sender();
+        self.open_direct_dispute(sender, sender, deal_id, beneficiary, category_id)?;
+        Ok(())
+    }
+    
+    /// Create a dispute directly, approving the fee with an EIP-2612 permit in the same transaction.
+    /// A failed permit is tolerated when the allowance already covers the fee, so a permit
+    /// front-run from the mempool can't block the dispute; otherwise it reverts with CallFailed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_dispute_with_permit(
+        &mut self,
+        deal_id: u64,
+        beneficiary: Address,
+        proof: String,
+        category_id: u64,
+        deadline: U256,
+        v: u8,
+        r: FixedBytes<32>,
+        s: FixedBytes<32>,
     ) -> Result<(), ProtocolError> {
         let sender = self.__stylus_host.msg_sender();
-        
-        // COMMENTED OUT FOR TESTING - USDC transfer logic
-        // Transfer dispute fee from sender to this contract
-        // let usdc = self.usdc_token.get();
-        // let dispute_price = self.dispute_price.get();
         let contract_addr = self.__stylus_host.contract_address();
+        let (price, _) = self.category_terms(category_id)?;
         
-        // let token = IERC20::new(usdc);
-        // let call = Call::new_in(self);
+        let token = IERC20::new(self.usdc_token.get());
+        if token.permit(&mut *self, sender, contract_addr, price, deadline, v, r, s).is_err()
+            && token.allowance(&*self, sender, contract_addr)? < price
+        {
+            return Err(ProtocolError::CallFailed(CallFailed {}));
+        }
         
-        // Transfer USDC from sender to protocol
-        // token.transfer_from(call, sender, contract_addr, dispute_price)?;
+        self.create_dispute_direct(deal_id, beneficiary, proof, category_id)
+    }
+}
+
+// ====================================
+//        SPONSORS FACET          
+// ====================================
+
+#[cfg_attr(feature = "sponsors", public)]
+impl ProtocolContract {
+    /// Create a dispute for `requester` with the caller paying the fee. The requester stays
+    /// the party of record; refunds go back to the caller. The caller must be a whitelisted
+    /// sponsor, or present the requester's EIP-712 consent (empty signature for sponsors).
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_dispute_for(
+        &mut self,
+        deal_id: u64,
+        requester: Address,
+        beneficiary: Address,
+        _proof: String,
+        category_id: u64,
+        deadline: U256,
+        signature: Bytes,
+    ) -> Result<(), ProtocolError> {
+        let sender = self.__stylus_host.msg_sender();
         
-        // Create dispute
-        let dispute_id = self.dispute_count.get();
-        let dispute_id_u64 = u64::from_le_bytes(dispute_id.to_le_bytes());
+        if requester == Address::ZERO {
+            return Err(ProtocolError::InvalidAddress(InvalidAddress {}));
+        }
         
-        let mut dispute = self.disputes.setter(dispute_id);
-        dispute.dispute_id.set(U32::from(deal_id));
-        dispute.requester.set(sender);
-        dispute.beneficiary.set(beneficiary);
-        dispute.contract_address.set(contract_addr);
-        dispute.waiting_for_judges.set(true);
-        dispute.is_open.set(false);
-        dispute.resolved.set(false);
-        dispute.votes_for.set(U8::ZERO);
-        dispute.votes_against.set(U8::ZERO);
-        dispute.able_to_vote_count.set(U256::ZERO);
-        dispute.voters_count.set(U256::ZERO);
-        dispute.commits_count.set(U256::ZERO);
-        dispute.reveals_count.set(U256::ZERO);
+        if !self.sponsors.get(sender) {
+            if U256::from(self.__stylus_host.block_timestamp()) > deadline {
+                return Err(ProtocolError::SignatureExpired(SignatureExpired {}));
+            }
+            
+            let nonce = self.nonces.get(requester);
+            let digest = self.sponsor_digest(deal_id, beneficiary, category_id, sender, nonce, deadline);
+            if self.recover_signer(digest, &signature)? != requester {
+                return Err(ProtocolError::InvalidSignature(InvalidSignature {}));
+            }
+            self.nonces.setter(requester).set(nonce + U256::from(1u64));
+        }
         
-        log(&self.__stylus_host, DisputeCreated {
-            dispute_id: U256::from(dispute_id_u64),
-            requester: sender,
-            contract_address: contract_addr,
-        });
+        let dispute_id = self.open_direct_dispute(sender, requester, deal_id, beneficiary, category_id)?;
         
-        // Increment counter
-        let current_counter = self.dispute_count.get();
-        self.dispute_count.set(current_counter + U64::from(1));
+        log(&self.__stylus_host, DisputeSponsored {
+            dispute_id: U256::from(dispute_id),
+            sponsor: sender,
+            requester,
+        });
         
         Ok(())
     }
     
-    /// Update dispute proofs for payer
+    /// Allow or revoke a marketplace opening disputes for requesters without their signature (only owner)
+    pub fn set_sponsor(&mut self, sponsor: Address, allowed: bool) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        self.sponsors.setter(sponsor).set(allowed);
+        
+        log(&self.__stylus_host, SponsorUpdated { sponsor, allowed });
+        
+        Ok(())
+    }
+}
+
+// ====================================
+//        PROOFS FACET          
+// ====================================
+
+#[cfg_attr(feature = "proofs", public)]
+impl ProtocolContract {
+    /// Update dispute proofs for payer (the requester must be the caller)
     pub fn update_dispute_for_payer(
         &mut self,
         dispute_id: u64,
         requester: Address,
-        _proof: String,
+        proof: String,
     ) -> Result<(), ProtocolError> {
         let dispute = self.disputes.get(U64::from(dispute_id));
         
-        if dispute.requester.get() != requester {
+        if dispute.requester.get() != requester || self.__stylus_host.msg_sender() != requester {
             return Err(ProtocolError::NotTheRequester(NotTheRequester {}));
         }
         
-        if _proof.is_empty() {
-            return Err(ProtocolError::ProofCannotBeEmpty(ProofCannotBeEmpty {}));
-        }
-        
-        if dispute.resolved.get() {
-            return Err(ProtocolError::DisputeAlreadyResolved(DisputeAlreadyResolved {}));
-        }
-        
-        // Note: In production, you'd store the proof in storage or emit an event
-        // For now, we just validate the inputs
-        
-        Ok(())
+        self.record_proof(dispute_id, requester, proof)
     }
     
-    /// Update dispute proofs for beneficiary
+    /// Update dispute proofs for beneficiary (the beneficiary must be the caller)
     pub fn update_dispute_for_beneficiary(
         &mut self,
         dispute_id: u64,
         beneficiary: Address,
-        _proof: String,
+        proof: String,
     ) -> Result<(), ProtocolError> {
         let dispute = self.disputes.get(U64::from(dispute_id));
         
-        if dispute.beneficiary.get() != beneficiary {
+        if dispute.beneficiary.get() != beneficiary || self.__stylus_host.msg_sender() != beneficiary {
             return Err(ProtocolError::NotTheBeneficiary(NotTheBeneficiary {}));
         }
         
-        if _proof.is_empty() {
-            return Err(ProtocolError::ProofCannotBeEmpty(ProofCannotBeEmpty {}));
+        self.record_proof(dispute_id, beneficiary, proof)
+    }
+    
+    /// Submit a proof for the requester through the marketplace that opened the dispute
+    pub fn relay_proof_for_requester(&mut self, dispute_id: u64, proof: String) -> Result<(), ProtocolError> {
+        self.check_relayer(dispute_id)?;
+        let requester = self.disputes.get(U64::from(dispute_id)).requester.get();
+        self.record_proof(dispute_id, requester, proof)
+    }
+    
+    /// Submit a proof for the beneficiary through the marketplace that opened the dispute
+    pub fn relay_proof_for_beneficiary(&mut self, dispute_id: u64, proof: String) -> Result<(), ProtocolError> {
+        self.check_relayer(dispute_id)?;
+        let beneficiary = self.disputes.get(U64::from(dispute_id)).beneficiary.get();
+        // Marketplace disputes don't record a beneficiary yet
+        if beneficiary == Address::ZERO {
+            return Err(ProtocolError::InvalidAddress(InvalidAddress {}));
         }
+        self.record_proof(dispute_id, beneficiary, proof)
+    }
+}
+
+// ====================================
+//        DISPUTE PARTIES FACET          
+// ====================================
+
+#[cfg_attr(feature = "dispute-parties", public)]
+impl ProtocolContract {
+    /// Require (or stop requiring) the named requester to confirm disputes a marketplace
+    /// opens through create_dispute before judges can register (only owner)
+    pub fn set_require_requester_ack(&mut self, marketplace: Address, required: bool) -> Result<(), ProtocolError> {
+        self.only_owner()?;
         
-        if dispute.resolved.get() {
-            return Err(ProtocolError::DisputeAlreadyResolved(DisputeAlreadyResolved {}));
-        }
+        self.require_requester_ack.setter(marketplace).set(required);
         
-        // Note: Store proof or emit event in production
+        log(&self.__stylus_host, RequesterAckRequired { marketplace, required });
         
         Ok(())
     }
     
-    /// Register to vote on a dispute
-    pub fn register_to_vote(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+    /// Confirm a dispute a marketplace opened in your name, opening it to judges.
+    /// The panel timeout and any defense bond window start from confirmation.
+    pub fn confirm_dispute(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+        if self.dispute_phase(dispute_id) != PHASE_PENDING_ACK {
+            return Err(ProtocolError::DisputeNotPendingAck(DisputeNotPendingAck {}));
+        }
+        
+        let now = self.__stylus_host.block_timestamp();
         let sender = self.__stylus_host.msg_sender();
+        let (_, bond_deadline) = self.defense_bond_terms(now);
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
         
-        // SIMPLIFIED FOR TESTING - Just add to able_to_vote list
-        let mut dispute_mut = self.disputes.setter(U64::from(dispute_id));
-        let current_count = dispute_mut.able_to_vote_count.get();
-        dispute_mut.able_to_vote.setter(current_count).set(sender);
-        dispute_mut.able_to_vote_count.set(current_count + U256::from(1u64));
+        if dispute.requester.get() != sender {
+            return Err(ProtocolError::NotTheRequester(NotTheRequester {}));
+        }
         
-        // Open dispute when we have 5 judges
-        if current_count + U256::from(1u64) >= U256::from(5u64) {
-            dispute_mut.waiting_for_judges.set(false);
-            dispute_mut.is_open.set(true);
+        if U64::from(now) > dispute.ack_deadline.get() {
+            return Err(ProtocolError::AckWindowClosed(AckWindowClosed {}));
         }
         
-        Ok(())
-    }
-    
+        dispute.pending_ack.set(false);
+        dispute.round_started_at.set(U64::from(now));
+        if dispute.bond_amount.get() != U256::ZERO {
+            dispute.bond_deadline.set(bond_deadline);
+        }
+        self.set_waiting_for_judges(dispute_id, true);
+        
+        log(&self.__stylus_host, DisputeConfirmed {
+            dispute_id: U256::from(dispute_id),
+            requester: sender,
+        });
+        
+        Ok(())
+    }
+    
+    /// Post the defense bond to contest a dispute (beneficiary only, within the bond window)
+    pub fn post_defense_bond(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+        let sender = self.__stylus_host.msg_sender();
+        let now = self.__stylus_host.block_timestamp();
+        let dispute = self.disputes.getter(U64::from(dispute_id));
+        
+        if dispute.beneficiary.get() != sender {
+            return Err(ProtocolError::NotTheBeneficiary(NotTheBeneficiary {}));
+        }
+        
+        let amount = dispute.bond_amount.get();
+        if amount == U256::ZERO {
+            return Err(ProtocolError::NoBondRequired(NoBondRequired {}));
+        }
+        
+        if dispute.bond_posted.get() {
+            return Err(ProtocolError::BondAlreadyPosted(BondAlreadyPosted {}));
+        }
+        
+        if dispute.resolved.get() || dispute.expired.get() || U64::from(now) > dispute.bond_deadline.get() {
+            return Err(ProtocolError::BondWindowClosed(BondWindowClosed {}));
+        }
+        
+        // Only what actually arrives backs the bond
+        let amount = self.pull_tokens(sender, amount)?;
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+        dispute.bond_amount.set(amount);
+        dispute.bond_posted.set(true);
+        self.add_escrow(dispute_id, amount);
+        
+        log(&self.__stylus_host, DefenseBondPosted {
+            dispute_id: U256::from(dispute_id),
+            beneficiary: sender,
+            amount,
+        });
+        
+        Ok(())
+    }
+    
+    /// Resolve a dispute for the requester when the beneficiary never posted the bond (callable by anyone)
+    /// The requester's fee is refunded since no judges ruled.
+    pub fn default_judgment(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+        let now = self.__stylus_host.block_timestamp();
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+        
+        if dispute.bond_amount.get() == U256::ZERO {
+            return Err(ProtocolError::NoBondRequired(NoBondRequired {}));
+        }
+        
+        if dispute.bond_posted.get() {
+            return Err(ProtocolError::BondAlreadyPosted(BondAlreadyPosted {}));
+        }
+        
+        if dispute.resolved.get() {
+            return Err(ProtocolError::DisputeAlreadyResolved(DisputeAlreadyResolved {}));
+        }
+        
+        if dispute.expired.get() {
+            return Err(ProtocolError::DisputeIsExpired(DisputeIsExpired {}));
+        }
+        
+        if dispute.pending_ack.get() {
+            return Err(ProtocolError::DisputeNotConfirmed(DisputeNotConfirmed {}));
+        }
+        
+        if U64::from(now) <= dispute.bond_deadline.get() {
+            return Err(ProtocolError::BondWindowNotEnded(BondWindowNotEnded {}));
+        }
+        
+        dispute.defaulted.set(true);
+        let requester = dispute.requester.get();
+        self.close_dispute(dispute_id, RESOLUTION_DEFAULT);
+        
+        self.refund_fee(dispute_id)?;
+        self.return_judge_bonds(dispute_id);
+        
+        log(&self.__stylus_host, DefaultJudgment {
+            dispute_id: U256::from(dispute_id),
+            requester,
+        });
+        log(&self.__stylus_host, DisputeResolved {
+            dispute_id: U256::from(dispute_id),
+            winner: requester,
+            requester_won: true,
+            votes_for: 0,
+            votes_against: 0,
+        });
+        
+        Ok(())
+    }
+}
+
+// ====================================
+//        CLOSING FACET          
+// ====================================
+
+#[cfg_attr(feature = "closing", public)]
+impl ProtocolContract {
+    /// Void a dispute that hasn't closed yet (owner or guardian), for when its vote can't be
+    /// trusted to finish. Everything goes back where it came from: the fee to its payer, the
+    /// defense bond to the beneficiary and every judge bond to its judge.
+    pub fn emergency_resolve(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+        self.only_owner_or_guardian()?;
+        
+        match self.dispute_phase(dispute_id) {
+            PHASE_NOT_FOUND => return Err(ProtocolError::DisputeNotOpen(DisputeNotOpen {})),
+            PHASE_RESOLVED => return Err(ProtocolError::DisputeAlreadyResolved(DisputeAlreadyResolved {})),
+            PHASE_EXPIRED => return Err(ProtocolError::DisputeIsExpired(DisputeIsExpired {})),
+            _ => {}
+        }
+        
+        self.close_dispute(dispute_id, RESOLUTION_VOIDED);
+        
+        let (refund_to, amount) = self.refund_fee(dispute_id)?;
+        self.refund_bond(dispute_id)?;
+        self.return_judge_bonds(dispute_id);
+        
+        log(&self.__stylus_host, DisputeVoided {
+            dispute_id: U256::from(dispute_id),
+            refunded_to: refund_to,
+            amount,
+        });
+        
+        Ok(())
+    }
+    
+    /// Void a dispute its requester never confirmed, refunding the fee to the marketplace
+    /// (callable by anyone once the confirmation window has passed)
+    pub fn void_unconfirmed_dispute(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+        if self.dispute_phase(dispute_id) != PHASE_PENDING_ACK {
+            return Err(ProtocolError::DisputeNotPendingAck(DisputeNotPendingAck {}));
+        }
+        
+        let now = self.__stylus_host.block_timestamp();
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        
+        if U64::from(now) <= dispute.ack_deadline.get() {
+            return Err(ProtocolError::AckWindowNotEnded(AckWindowNotEnded {}));
+        }
+        
+        self.close_dispute(dispute_id, RESOLUTION_VOIDED);
+        
+        let (refund_to, amount) = self.refund_fee(dispute_id)?;
+        self.refund_bond(dispute_id)?;
+        
+        log(&self.__stylus_host, DisputeVoided {
+            dispute_id: U256::from(dispute_id),
+            refunded_to: refund_to,
+            amount,
+        });
+        
+        Ok(())
+    }
+    
+    /// Settle a dispute by agreement: one party proposes the outcome, the other accepts it.
+    /// Part of the fee is refunded and judges who already committed are compensated.
+    pub fn settle_dispute(&mut self, dispute_id: u64, requester_wins: bool) -> Result<(), ProtocolError> {
+        let sender = self.__stylus_host.msg_sender();
+        
+        match self.dispute_phase(dispute_id) {
+            PHASE_NOT_FOUND => return Err(ProtocolError::DisputeNotOpen(DisputeNotOpen {})),
+            PHASE_RESOLVED => return Err(ProtocolError::DisputeAlreadyResolved(DisputeAlreadyResolved {})),
+            PHASE_EXPIRED => return Err(ProtocolError::DisputeIsExpired(DisputeIsExpired {})),
+            _ => {}
+        }
+        
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+        if sender != dispute.requester.get() && sender != dispute.beneficiary.get() {
+            return Err(ProtocolError::NotAParty(NotAParty {}));
+        }
+        
+        // Once a vote is revealed the parties could settle around a known outcome
+        if dispute.reveals_count.get() != U256::ZERO {
+            return Err(ProtocolError::SettlementBlocked(SettlementBlocked {}));
+        }
+        
+        // A new proposal replaces any pending one unless the counterparty agrees to it
+        let proposer = dispute.settlement_proposer.get();
+        if proposer == Address::ZERO || proposer == sender || dispute.settlement_requester_wins.get() != requester_wins {
+            dispute.settlement_proposer.set(sender);
+            dispute.settlement_requester_wins.set(requester_wins);
+            
+            log(&self.__stylus_host, SettlementProposed {
+                dispute_id: U256::from(dispute_id),
+                proposer: sender,
+                requester_wins,
+            });
+            return Ok(());
+        }
+        
+        dispute.settlement_proposer.set(Address::ZERO);
+        dispute.settled.set(true);
+        let winner = if requester_wins { dispute.requester.get() } else { dispute.beneficiary.get() };
+        let votes_for = u8::from_le_bytes(dispute.votes_for.get().to_le_bytes());
+        let votes_against = u8::from_le_bytes(dispute.votes_against.get().to_le_bytes());
+        self.close_dispute(dispute_id, RESOLUTION_SETTLEMENT);
+        
+        self.settle_fee(dispute_id)?;
+        self.refund_bond(dispute_id)?;
+        self.return_judge_bonds(dispute_id);
+        
+        log(&self.__stylus_host, DisputeSettled {
+            dispute_id: U256::from(dispute_id),
+            requester_wins,
+        });
+        log(&self.__stylus_host, DisputeResolved {
+            dispute_id: U256::from(dispute_id),
+            winner,
+            requester_won: requester_wins,
+            votes_for,
+            votes_against,
+        });
+        
+        Ok(())
+    }
+}
+
+// ====================================
+//        PANELS FACET          
+// ====================================
+
+#[cfg_attr(feature = "panels", public)]
+impl ProtocolContract {
+    /// Register to vote on a dispute
+    pub fn register_to_vote(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+        self.when_not_paused()?;
+        let sender = self.__stylus_host.msg_sender();
+        
+        if self.judges.get(sender).judge_address.get() == Address::ZERO {
+            return Err(ProtocolError::NotAJudge(NotAJudge {}));
+        }
+        
+        match self.dispute_phase(dispute_id) {
+            PHASE_WAITING_FOR_JUDGES => {}
+            PHASE_NOT_FOUND => return Err(ProtocolError::DisputeNotOpen(DisputeNotOpen {})),
+            PHASE_EXPIRED => return Err(ProtocolError::DisputeIsExpired(DisputeIsExpired {})),
+            PHASE_PENDING_ACK => return Err(ProtocolError::DisputeNotConfirmed(DisputeNotConfirmed {})),
+            _ => return Err(ProtocolError::JudgesAlreadyAssigned(JudgesAlreadyAssigned {})),
+        }
+        
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        
+        // Panels only form once a required defense bond is posted
+        if dispute.bond_amount.get() != U256::ZERO && !dispute.bond_posted.get() {
+            return Err(ProtocolError::DefenseBondNotPosted(DefenseBondNotPosted {}));
+        }
+        
+        // Registrations stop once the panel is full; later transactions in the same block revert here
+        if dispute.able_to_vote_count.get() >= U256::from(dispute.required_votes.get()) {
+            return Err(ProtocolError::JudgesAlreadyAssigned(JudgesAlreadyAssigned {}));
+        }
+        
+        if Self::panel_slot(&dispute, sender).is_some() {
+            return Err(ProtocolError::JudgeAlreadyOnPanel(JudgeAlreadyOnPanel {}));
+        }
+        
+        // Re-vote panels are drawn only from judges who haven't sat on this dispute, and
+        // appeal panels from judges who didn't sit on the ruling under appeal
+        let appeal_of = u64::from_le_bytes(dispute.appeal_of.get().to_le_bytes());
+        if self.sat_on_dispute(dispute_id, sender) || (appeal_of != 0 && self.sat_on_dispute(appeal_of, sender)) {
+            return Err(ProtocolError::JudgeAlreadyServed(JudgeAlreadyServed {}));
+        }
+        
+        // A delegate can't hold their own seat plus a delegated one
+        if dispute.delegated_by.get(sender) != Address::ZERO {
+            return Err(ProtocolError::JudgeAlreadyOnPanel(JudgeAlreadyOnPanel {}));
+        }
+        
+        self.check_panel_eligibility(sender)?;
+        
+        let now = U64::from(self.__stylus_host.block_timestamp());
+        let commit_deadline = now + self.commit_period.get();
+        let reveal_deadline = commit_deadline + self.reveal_period_or_default();
+        
+        let current_count = dispute.able_to_vote_count.get();
+        let new_count = Self::checked_increment(current_count)?;
+        let seat = Self::round_base(&dispute) + current_count;
+        self.seat_judge(dispute_id, seat, sender)?;
+        let mut dispute_mut = self.disputes.setter(U64::from(dispute_id));
+        dispute_mut.able_to_vote_count.set(new_count);
+        
+        // Open dispute when the panel is complete
+        let required_votes = dispute_mut.required_votes.get();
+        if new_count >= U256::from(required_votes) {
+            dispute_mut.is_open.set(true);
+            dispute_mut.commit_deadline.set(commit_deadline);
+            dispute_mut.reveal_deadline.set(reveal_deadline);
+            self.set_waiting_for_judges(dispute_id, false);
+            
+            log(&self.__stylus_host, DisputeOpened {
+                dispute_id: U256::from(dispute_id),
+                commit_deadline: u64::from_le_bytes(commit_deadline.to_le_bytes()),
+            });
+        }
+        
+        Ok(())
+    }
+}
+
+// ====================================
+//        JUDGES FACET          
+// ====================================
+
+#[cfg_attr(feature = "judges", public)]
+impl ProtocolContract {
+    /// Register as a judge
+    pub fn register_as_judge(&mut self) -> Result<(), ProtocolError> {
+        self.when_not_paused()?;
+        let sender = self.__stylus_host.msg_sender();
+        let judge = self.judges.get(sender);
+        
+        if judge.judge_address.get() != Address::ZERO {
+            return Err(ProtocolError::AlreadyRegistered(AlreadyRegistered {}));
+        }
+        
+        // Code size is 0 for a contract calling from its constructor, so this only
+        // keeps out deployed contracts
+        let is_contract = self.__stylus_host.code_size(sender) > 0;
+        if is_contract && !self.allow_contract_judges.get() {
+            return Err(ProtocolError::ContractJudgesNotAllowed(ContractJudgesNotAllowed {}));
+        }
+        
+        let mut new_judge = self.judges.setter(sender);
+        new_judge.judge_address.set(sender);
+        new_judge.balance.set(U256::ZERO);
+        new_judge.reputation.set(I8::ZERO);
+        new_judge.active.set(true);
+        new_judge.is_contract.set(is_contract);
+        self.list_judge(sender);
+        
+        log(&self.__stylus_host, JudgeRegistered { judge: sender });
+        
+        Ok(())
+    }
+    
+    /// Set judge availability; inactive judges can't join new panels
+    pub fn set_availability(&mut self, active: bool) -> Result<(), ProtocolError> {
+        let sender = self.__stylus_host.msg_sender();
+        let mut judge = self.judges.setter(sender);
+        
+        if judge.judge_address.get() == Address::ZERO {
+            return Err(ProtocolError::NotAJudge(NotAJudge {}));
+        }
+        
+        if active && judge.banned.get() {
+            return Err(ProtocolError::JudgeIsBanned(JudgeIsBanned {}));
+        }
+        
+        judge.active.set(active);
+        
+        log(&self.__stylus_host, JudgeAvailabilityChanged { judge: sender, active });
+        
+        Ok(())
+    }
+    
+    /// Add fee tokens to the judge's stake; panels snapshot it when the judge joins
+    pub fn judge_stake(&mut self, amount: U256) -> Result<(), ProtocolError> {
+        let sender = self.__stylus_host.msg_sender();
+        if self.judges.get(sender).judge_address.get() == Address::ZERO {
+            return Err(ProtocolError::NotAJudge(NotAJudge {}));
+        }
+        
+        if amount == U256::ZERO {
+            return Err(ProtocolError::MustBeGreaterThanZero(MustBeGreaterThanZero {}));
+        }
+        
+        // Only what arrived counts, in case the token takes a transfer fee
+        let received = self.pull_tokens(sender, amount)?;
+        let mut judge = self.judges.setter(sender);
+        let stake = judge.stake.get() + received;
+        judge.stake.set(stake);
+        let total_staked = self.total_staked.get();
+        self.total_staked.set(total_staked + received);
+        
+        log(&self.__stylus_host, JudgeStaked { judge: sender, amount: received, stake });
+        
+        Ok(())
+    }
+    
+    /// Withdraw part of the judge's stake. Locked while the judge has a panel seat that
+    /// hasn't been settled, so a stake can't be pulled after it weighted a reward share.
+    pub fn judge_unstake(&mut self, amount: U256) -> Result<(), ProtocolError> {
+        let sender = self.__stylus_host.msg_sender();
+        let judge = self.judges.get(sender);
+        if judge.judge_address.get() == Address::ZERO {
+            return Err(ProtocolError::NotAJudge(NotAJudge {}));
+        }
+        
+        if amount == U256::ZERO {
+            return Err(ProtocolError::MustBeGreaterThanZero(MustBeGreaterThanZero {}));
+        }
+        
+        if judge.open_seats.get() != U64::ZERO {
+            return Err(ProtocolError::StakeLocked(StakeLocked {}));
+        }
+        
+        let stake = judge.stake.get();
+        if amount > stake {
+            return Err(ProtocolError::InsufficientBalance(InsufficientBalance {}));
+        }
+        
+        self.judges.setter(sender).stake.set(stake - amount);
+        let total_staked = self.total_staked.get();
+        self.total_staked.set(total_staked - amount);
+        
+        log(&self.__stylus_host, JudgeUnstaked { judge: sender, amount, stake: stake - amount });
+        
+        self.push_tokens(sender, amount)
+    }
+    
     // /// Vote on a dispute
     // pub fn vote(&mut self, dispute_id: u64, support: bool) -> Result<(), ProtocolError> {
     //     let sender = msg::sender();
@@ -567,116 +1687,69 @@ impl ProtocolContract {
     // }
     
 
-    pub fn commit_vote(&mut self, dispute_id: u64, commit_hash: FixedBytes<32>) -> Result<(), ProtocolError> {
+    /// Delegate your panel seat on a dispute to another judge (before committing)
+    /// The delegate commits and reveals for the seat and receives its reward;
+    /// the reputation change stays with the delegating judge.
+    pub fn delegate_vote(&mut self, dispute_id: u64, delegate: Address) -> Result<(), ProtocolError> {
+        self.when_not_paused()?;
         let sender = self.__stylus_host.msg_sender();
-        let mut dispute = self.disputes.setter(U64::from(dispute_id));
-
-        // SIMPLIFIED FOR TESTING - Skip all validation
-        let commits = dispute.commits_count.get();
         
-        // Store commit
-        dispute.voters.setter(commits).set(sender);
-        dispute.vote_commits.setter(commits).set(commit_hash);
-        dispute.commits_count.set(commits + U256::from(1u64));
-
-        Ok(())
-    }
-
-
-    /// Reveal a single judge's vote (called by each judge individually)
-    pub fn reveal_votes(
-        &mut self,
-        dispute_id: u64,
-        vote: bool,
-        _secret: Vec<u8>
-    ) -> Result<(), ProtocolError> {
-        let sender = self.__stylus_host.msg_sender();
-        let mut dispute = self.disputes.setter(U64::from(dispute_id));
-
-        // SIMPLIFIED FOR TESTING - Skip all validation
-        // Find the judge's commit index
-        let commit_count = dispute.commits_count.get();
-        let mut judge_index: Option<u64> = None;
+        if delegate == sender || self.judges.get(delegate).judge_address.get() == Address::ZERO {
+            return Err(ProtocolError::InvalidDelegate(InvalidDelegate {}));
+        }
         
-        for i in 0..commit_count.as_limbs()[0] {
-            let voter = dispute.voters.get(U256::from(i));
-            if voter == sender {
-                judge_index = Some(i);
-                break;
-            }
+        // A delegate takes the seat, so they must pass the same activity, reputation and cooldown bar
+        self.check_panel_eligibility(delegate)?;
+        
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        
+        if dispute.resolved.get() {
+            return Err(ProtocolError::DisputeAlreadyResolved(DisputeAlreadyResolved {}));
         }
-
-        let idx = match judge_index {
-            Some(i) => i,
-            None => return Err(ProtocolError::JudgeNotAllowedToVote(JudgeNotAllowedToVote {})),
-        };
-
-        // Mark as revealed and store the vote
-        dispute.revealed.setter(U256::from(idx)).set(true);
-        dispute.vote_plain.setter(U256::from(idx)).set(vote);
         
-        // Update vote counts
-        let current_reveals = dispute.reveals_count.get();
-        dispute.reveals_count.set(current_reveals + U256::from(1u64));
+        if dispute.expired.get() {
+            return Err(ProtocolError::DisputeIsExpired(DisputeIsExpired {}));
+        }
         
-        if vote {
-            let current_for = dispute.votes_for.get();
-            dispute.votes_for.set(current_for + U8::from(1u8));
-        } else {
-            let current_against = dispute.votes_against.get();
-            dispute.votes_against.set(current_against + U8::from(1u8));
+        if Self::panel_slot(&dispute, sender).is_none() {
+            return Err(ProtocolError::JudgeNotAllowedToVote(JudgeNotAllowedToVote {}));
         }
-
-        // Check if all votes are revealed (hardcode 5 for testing)
-        if current_reveals + U256::from(1u64) >= U256::from(5u64) {
-            // All votes revealed - resolve the dispute
-            dispute.is_open.set(false);
-            dispute.resolved.set(true);
-
-            let votes_for = dispute.votes_for.get();
-            let votes_against = dispute.votes_against.get();
-
-            let requester = dispute.requester.get();
-            let beneficiary = dispute.beneficiary.get();
-
-            if votes_for > votes_against {
-                log(&self.__stylus_host, DisputeResolved {
-                    dispute_id: U256::from(dispute_id),
-                    winner: requester,
-                });
-            } else {
-                log(&self.__stylus_host, DisputeResolved {
-                    dispute_id: U256::from(dispute_id),
-                    winner: beneficiary,
-                });
-            }
+        
+        if Self::commit_slot(&dispute, sender).is_some() {
+            return Err(ProtocolError::JudgeAlreadyVoted(JudgeAlreadyVoted {}));
         }
-
-        Ok(())
-    }
-
-    
-    /// Get dispute winner (called by Marketplace to execute result)
-    /// Returns true if requester (payer) wins, false if beneficiary (seller) wins
-    pub fn get_dispute_winner(&self, dispute_id: u64) -> Result<bool, ProtocolError> {
-        let dispute = self.disputes.get(U64::from(dispute_id));
         
-        if !dispute.resolved.get() {
-            return Err(ProtocolError::DisputeNotResolvedYet(DisputeNotResolvedYet {}));
+        if dispute.delegate_of.get(sender) != Address::ZERO {
+            return Err(ProtocolError::SeatAlreadyDelegated(SeatAlreadyDelegated {}));
         }
         
-        let votes_for = u8::from_le_bytes(dispute.votes_for.get().to_le_bytes());
-        let votes_against = u8::from_le_bytes(dispute.votes_against.get().to_le_bytes());
+        // A delegate can't hold their own seat plus a delegated one
+        if Self::panel_slot(&dispute, delegate).is_some() || dispute.delegated_by.get(delegate) != Address::ZERO {
+            return Err(ProtocolError::InvalidDelegate(InvalidDelegate {}));
+        }
         
-        // votes_for means vote for requester/payer
-        // votes_against means vote for beneficiary/seller
-        // Return true if requester wins (votes_for > votes_against)
-        Ok(votes_for > votes_against)
-    }
-    
-    /// Execute dispute result - kept for backward compatibility, delegates to get_dispute_winner
-    pub fn execute_dispute_result(&self, dispute_id: u64) -> Result<bool, ProtocolError> {
-        self.get_dispute_winner(dispute_id)
+        // Judges from a tied round, or from the panel under appeal, can't come back through a delegation either
+        let appeal_of = u64::from_le_bytes(dispute.appeal_of.get().to_le_bytes());
+        if self.sat_on_dispute(dispute_id, delegate) || (appeal_of != 0 && self.sat_on_dispute(appeal_of, delegate)) {
+            return Err(ProtocolError::JudgeAlreadyServed(JudgeAlreadyServed {}));
+        }
+        
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+        dispute.delegate_of.setter(sender).set(delegate);
+        dispute.delegated_by.setter(delegate).set(sender);
+        // Delegates sit on this round too, so a re-vote panel can't draw them
+        dispute.served.setter(delegate).set(true);
+        // and taking the seat starts their cooldown like any other assignment
+        let now = U64::from(self.__stylus_host.block_timestamp());
+        self.judges.setter(delegate).last_assignment_time.set(now);
+        
+        log(&self.__stylus_host, VoteDelegated {
+            dispute_id: U256::from(dispute_id),
+            from: sender,
+            to: delegate,
+        });
+        
+        Ok(())
     }
     
     /// Judge withdraw their balance
@@ -693,84 +1766,3072 @@ impl ProtocolContract {
             return Err(ProtocolError::NoBalanceToWithdraw(NoBalanceToWithdraw {}));
         }
         
-        // Reset balance
-        let mut judge_mut = self.judges.setter(sender);
-        judge_mut.balance.set(U256::ZERO);
+        self.withdraw_judge_balance(sender, balance)
+    }
+    
+    /// Judge withdraw part of their balance, leaving the rest in the contract
+    pub fn judge_withdraw_amount(&mut self, amount: U256) -> Result<(), ProtocolError> {
+        let sender = self.__stylus_host.msg_sender();
+        
+        if self.judges.get(sender).judge_address.get() == Address::ZERO {
+            return Err(ProtocolError::NotAJudge(NotAJudge {}));
+        }
+        
+        if amount == U256::ZERO {
+            return Err(ProtocolError::MustBeGreaterThanZero(MustBeGreaterThanZero {}));
+        }
+        
+        self.withdraw_judge_balance(sender, amount)
+    }
+}
+
+// ====================================
+//        REVEAL FACET          
+// ====================================
+
+#[cfg_attr(feature = "reveal", public)]
+impl ProtocolContract {
+    /// Reveal a single judge's vote (called by each judge individually)
+    pub fn reveal_votes(
+        &mut self,
+        dispute_id: u64,
+        vote: u8,
+        secret: Vec<u8>
+    ) -> Result<(), ProtocolError> {
+        let sender = self.__stylus_host.msg_sender();
+        
+        if !(VOTE_FOR..=VOTE_ABSTAIN).contains(&vote) {
+            return Err(ProtocolError::InvalidVote(InvalidVote {}));
+        }
+        
+        if secret.len() < MIN_SECRET_LENGTH {
+            return Err(ProtocolError::WeakSecret(WeakSecret {}));
+        }
+        
+        // Reveals open once every judge committed or the commit deadline passed. Past the
+        // deadline the first reveal freezes a quorate commit set itself; an inquorate one can
+        // only be expired through close_commit_phase
+        let now = U64::from(self.__stylus_host.block_timestamp());
+        let deadline_passed = now >= self.disputes.get(U64::from(dispute_id)).commit_deadline.get();
+        match self.dispute_phase(dispute_id) {
+            PHASE_REVEAL => {}
+            PHASE_COMMIT if deadline_passed => {
+                if !self.commit_set_quorate(dispute_id) {
+                    return Err(ProtocolError::CommitPhaseEnded(CommitPhaseEnded {}));
+                }
+                self.freeze_commit_set(dispute_id);
+            }
+            PHASE_COMMIT => return Err(ProtocolError::CommitPhaseNotEnded(CommitPhaseNotEnded {})),
+            PHASE_RESOLVED => return Err(ProtocolError::DisputeAlreadyResolved(DisputeAlreadyResolved {})),
+            _ => return Err(ProtocolError::DisputeNotOpen(DisputeNotOpen {})),
+        }
+        
+        let seat = self.voting_seat(dispute_id, sender)?;
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+
+        // Find the seat's commit index
+        let Some(idx) = Self::commit_slot(&dispute, seat) else {
+            return Err(ProtocolError::JudgeNotAllowedToVote(JudgeNotAllowedToVote {}));
+        };
+        
+        // A stored vote doubles as the revealed flag (votes are never 0)
+        if dispute.vote_plain.get(idx) != U8::ZERO {
+            return Err(ProtocolError::JudgeAlreadyVoted(JudgeAlreadyVoted {}));
+        }
+        
+        let commitment = Self::commit_hash(dispute_id, seat, vote, &secret);
+        if dispute.vote_commits.get(idx) != commitment {
+            return Err(ProtocolError::CommitMismatch(CommitMismatch {}));
+        }
+        
+        if dispute.revealed_commits.get(commitment) {
+            return Err(ProtocolError::CommitmentAlreadyRevealed(CommitmentAlreadyRevealed {}));
+        }
+        dispute.revealed_commits.setter(commitment).set(true);
+
+        dispute.vote_plain.setter(idx).set(U8::from(vote));
         
-        // COMMENTED OUT FOR TESTING - USDC transfer logic
-        // Transfer USDC
-        // let usdc = self.usdc_token.get();
-        // let token = IERC20::new(usdc);
-        // let call = Call::new_in(self);
-        // let success = token.transfer(call, sender, balance)?;
+        // Running per-side totals let the ruling size the reward pool without walking the panel
+        let panel_seat = dispute.panel_index.get(seat) - U256::from(1u64);
+        let bond = dispute.bond_at_assignment.get(panel_seat);
+        if vote != VOTE_ABSTAIN {
+            let side = U8::from(vote);
+            let stake = dispute.stake_at_assignment.get(panel_seat);
+            let multiplier = U256::from(Self::seat_multiplier(dispute.multiplier_at_assignment.get(panel_seat)));
+            let slash_bps = U256::from(dispute.judge_bond_slash_bps.get());
+            let side_weight = dispute.side_weight.get(side);
+            dispute.side_weight.setter(side).set(side_weight + multiplier);
+            let side_stake = dispute.side_stake.get(side);
+            dispute.side_stake.setter(side).set(side_stake + stake);
+            let side_stake_weight = dispute.side_stake_weight.get(side);
+            dispute.side_stake_weight.setter(side).set(side_stake_weight + stake * multiplier);
+            let side_slash = dispute.side_slash.get(side);
+            dispute.side_slash.setter(side).set(side_slash + bond * slash_bps / U256::from(BPS_DENOMINATOR));
+        }
         
-        // if !success {
-        //     return Err(ProtocolError::CallFailed(CallFailed {}));
-        // }
+        // Update vote counts
+        let reveals = Self::checked_increment(dispute.reveals_count.get())?;
+        dispute.reveals_count.set(reveals);
         
+        // Abstentions count as reveals but not toward either side
+        let mut votes_for = dispute.votes_for.get();
+        let mut votes_against = dispute.votes_against.get();
+        match vote {
+            VOTE_FOR => {
+                votes_for = Self::checked_increment(votes_for)?;
+                dispute.votes_for.set(votes_for);
+            }
+            VOTE_AGAINST => {
+                votes_against = Self::checked_increment(votes_against)?;
+                dispute.votes_against.set(votes_against);
+            }
+            _ => {
+                let votes_abstain = Self::checked_increment(dispute.votes_abstain.get())?;
+                dispute.votes_abstain.set(votes_abstain);
+            }
+        }
+
+        // Resolve once every committed vote is revealed, or as soon as one side holds a strict
+        // majority of the full panel (the outstanding reveals can't change the result)
+        let required_votes = dispute.required_votes.get();
+        let quorum = required_votes / U8::from(2u8);
+        let decided = votes_for > quorum || votes_against > quorum;
+        if decided || reveals >= dispute.commits_count.get() {
+            self.resolve(dispute_id)?;
+        }
+
         Ok(())
     }
-    
-    // ====================================
-    //        VIEW FUNCTIONS          
-    // ====================================
-    
-    /// Get owner address
-    pub fn owner(&self) -> Address {
-        self.owner.get()
+}
+
+// ====================================
+//        VOTING FACET          
+// ====================================
+
+#[cfg_attr(feature = "voting", public)]
+impl ProtocolContract {
+    pub fn commit_vote(&mut self, dispute_id: u64, commit_hash: FixedBytes<32>) -> Result<(), ProtocolError> {
+        let sender = self.__stylus_host.msg_sender();
+        self.record_commit(dispute_id, sender, commit_hash)
     }
     
-    /// Get dispute count
-    pub fn dispute_count(&self) -> u64 {
-        u64::from_le_bytes(self.dispute_count.get().to_le_bytes())
+    /// Commit a vote on behalf of `judge` using their EIP-712 signature, so a relayer can pay the gas
+    pub fn commit_vote_by_sig(
+        &mut self,
+        dispute_id: u64,
+        commit_hash: FixedBytes<32>,
+        judge: Address,
+        deadline: U256,
+        signature: Bytes,
+    ) -> Result<(), ProtocolError> {
+        if U256::from(self.__stylus_host.block_timestamp()) > deadline {
+            return Err(ProtocolError::SignatureExpired(SignatureExpired {}));
+        }
+        
+        // Contract judges can't produce ECDSA signatures; they commit directly
+        if self.judges.get(judge).is_contract.get() {
+            return Err(ProtocolError::InvalidSignature(InvalidSignature {}));
+        }
+        
+        let nonce = self.nonces.get(judge);
+        let digest = self.commit_digest(dispute_id, commit_hash, nonce, deadline);
+        if judge == Address::ZERO || self.recover_signer(digest, &signature)? != judge {
+            return Err(ProtocolError::InvalidSignature(InvalidSignature {}));
+        }
+        self.nonces.setter(judge).set(nonce + U256::from(1u64));
+        
+        self.record_commit(dispute_id, judge, commit_hash)
+    }
+}
+
+// ====================================
+//        RESOLUTION FACET          
+// ====================================
+
+#[cfg_attr(feature = "resolution", public)]
+impl ProtocolContract {
+    /// Tally a dispute's round on behalf of another facet (see call_facet). Reverts for any
+    /// outside caller.
+    pub fn resolve_round(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+        self.only_facet_call()?;
+        self.tally(dispute_id)
+    }
+}
+
+// ====================================
+//        REPLACEMENTS FACET          
+// ====================================
+
+#[cfg_attr(feature = "replacements", public)]
+impl ProtocolContract {
+    /// Swap a panel judge who hasn't committed for another eligible judge (owner or guardian).
+    /// The replaced judge loses a point of reputation for abandoning the seat. Only while the
+    /// new judge can still commit: once the deadline passed or the commit set was frozen, the
+    /// seat stays empty and close_commit_phase has already penalized it.
+    pub fn replace_judge(&mut self, dispute_id: u64, old_judge: Address, new_judge: Address) -> Result<(), ProtocolError> {
+        self.only_owner_or_guardian()?;
+        
+        let phase = self.dispute_phase(dispute_id);
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        let now = U64::from(self.__stylus_host.block_timestamp());
+        match phase {
+            PHASE_WAITING_FOR_JUDGES => {}
+            PHASE_COMMIT if now < dispute.commit_deadline.get() => {}
+            PHASE_COMMIT | PHASE_REVEAL => return Err(ProtocolError::CommitPhaseEnded(CommitPhaseEnded {})),
+            PHASE_RESOLVED => return Err(ProtocolError::DisputeAlreadyResolved(DisputeAlreadyResolved {})),
+            PHASE_EXPIRED => return Err(ProtocolError::DisputeIsExpired(DisputeIsExpired {})),
+            _ => return Err(ProtocolError::DisputeNotOpen(DisputeNotOpen {})),
+        }
+        
+        let Some(seat) = Self::panel_slot(&dispute, old_judge) else {
+            return Err(ProtocolError::JudgeNotOnPanel(JudgeNotOnPanel {}));
+        };
+        
+        if Self::commit_slot(&dispute, old_judge).is_some() {
+            return Err(ProtocolError::JudgeAlreadyVoted(JudgeAlreadyVoted {}));
+        }
+        
+        if self.judges.get(new_judge).judge_address.get() == Address::ZERO {
+            return Err(ProtocolError::NotAJudge(NotAJudge {}));
+        }
+        
+        // A delegate already votes for a seat, so they can't take another one
+        if Self::panel_slot(&dispute, new_judge).is_some() || dispute.delegated_by.get(new_judge) != Address::ZERO {
+            return Err(ProtocolError::JudgeAlreadyOnPanel(JudgeAlreadyOnPanel {}));
+        }
+        
+        let appeal_of = u64::from_le_bytes(dispute.appeal_of.get().to_le_bytes());
+        if self.sat_on_dispute(dispute_id, new_judge) || (appeal_of != 0 && self.sat_on_dispute(appeal_of, new_judge)) {
+            return Err(ProtocolError::JudgeAlreadyServed(JudgeAlreadyServed {}));
+        }
+        
+        self.check_panel_eligibility(new_judge)?;
+        
+        // Free the old seat, including any delegation, and bar the old judge from re-joining
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+        dispute.panel_index.setter(old_judge).set(U256::ZERO);
+        dispute.served.setter(old_judge).set(true);
+        let delegate = dispute.delegate_of.get(old_judge);
+        if delegate != Address::ZERO {
+            dispute.delegated_by.setter(delegate).set(Address::ZERO);
+            dispute.delegate_of.setter(old_judge).set(Address::ZERO);
+        }
+        
+        // An abandoned seat's bond goes to the treasury
+        let forfeited = self.settle_seat_bond(dispute_id, seat, BPS_DENOMINATOR);
+        let current_contract_balance = self.contract_balance.get();
+        self.contract_balance.set(current_contract_balance + forfeited);
+        self.release_escrow(dispute_id, forfeited);
+        self.release_seat(old_judge);
+        
+        self.seat_judge(dispute_id, seat, new_judge)?;
+        
+        let mut judge = self.judges.setter(old_judge);
+        let current_rep = judge.reputation.get();
+        judge.reputation.set(current_rep.saturating_sub(I8::ONE));
+        
+        log(&self.__stylus_host, JudgeReplaced {
+            dispute_id: U256::from(dispute_id),
+            old_judge,
+            new_judge,
+        });
+        
+        Ok(())
+    }
+}
+
+// ====================================
+//        PAYOUTS FACET          
+// ====================================
+
+#[cfg_attr(feature = "payouts", public)]
+impl ProtocolContract {
+    /// Release a ruling's pending judge rewards and reputation changes once the appeal
+    /// window has passed (callable by anyone). Settles every seat left in one call; use
+    /// settle_judges to spread a large panel over several transactions.
+    pub fn settle_original_panel(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+        self.settle_seats(dispute_id, 0, u64::MAX)
     }
     
-    /// Get number of votes required
-    pub fn number_of_votes(&self) -> u8 {
-        u8::from_le_bytes(self.number_of_votes.get().to_le_bytes())
+    /// Settle panel seats `start` to `start + count - 1` of a closed vote (callable by anyone):
+    /// bonds returned or forfeited, rewards credited and reputation moved. Seats already
+    /// settled are skipped, so overlapping calls never pay twice. Seat indices run across
+    /// rounds: round `r` seats its panel from index `r * 25`, after the tied rounds' seats.
+    pub fn settle_judges(&mut self, dispute_id: u64, start: u64, count: u64) -> Result<(), ProtocolError> {
+        self.settle_seats(dispute_id, start, count)
+    }
+}
+
+// ====================================
+//        SETTLEMENT FACET          
+// ====================================
+
+#[cfg_attr(feature = "settlement", public)]
+impl ProtocolContract {
+    /// Expire a dispute that never filled its panel and refund the fee (callable by anyone)
+    pub fn expire_dispute(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+        let now = self.__stylus_host.block_timestamp();
+        let panel_timeout = self.panel_timeout.get();
+        
+        match self.dispute_phase(dispute_id) {
+            PHASE_WAITING_FOR_JUDGES => {}
+            PHASE_NOT_FOUND => return Err(ProtocolError::DisputeNotOpen(DisputeNotOpen {})),
+            PHASE_RESOLVED => return Err(ProtocolError::DisputeAlreadyResolved(DisputeAlreadyResolved {})),
+            PHASE_EXPIRED => return Err(ProtocolError::DisputeIsExpired(DisputeIsExpired {})),
+            _ => return Err(ProtocolError::JudgesAlreadyAssigned(JudgesAlreadyAssigned {})),
+        }
+        
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        
+        if U64::from(now) < dispute.round_started_at.get() + panel_timeout {
+            return Err(ProtocolError::PanelTimeoutNotReached(PanelTimeoutNotReached {}));
+        }
+        
+        self.expire(dispute_id)
     }
     
-    /// Get dispute price
-    pub fn dispute_price(&self) -> U256 {
-        self.dispute_price.get()
+    /// Close the commit phase once its deadline passed with seats still uncommitted (callable
+    /// by anyone). Judges who never committed lose 1 reputation. If a strict majority of the
+    /// panel committed, the commit set is frozen and reveals open (the first reveal past the
+    /// deadline does this too); otherwise the dispute expires, the fee is refunded and the
+    /// silent judges' bonds go to the treasury.
+    pub fn close_commit_phase(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+        match self.dispute_phase(dispute_id) {
+            PHASE_COMMIT => {}
+            PHASE_REVEAL => {
+                let dispute = self.disputes.get(U64::from(dispute_id));
+                if dispute.commit_phase_closed.get() {
+                    return Err(ProtocolError::CommitPhaseAlreadyClosed(CommitPhaseAlreadyClosed {}));
+                }
+                return Err(ProtocolError::AllVotesCommitted(AllVotesCommitted {}));
+            }
+            PHASE_RESOLVED => return Err(ProtocolError::DisputeAlreadyResolved(DisputeAlreadyResolved {})),
+            PHASE_EXPIRED => return Err(ProtocolError::DisputeIsExpired(DisputeIsExpired {})),
+            _ => return Err(ProtocolError::DisputeNotOpen(DisputeNotOpen {})),
+        }
+        
+        let now = U64::from(self.__stylus_host.block_timestamp());
+        if now < self.disputes.get(U64::from(dispute_id)).commit_deadline.get() {
+            return Err(ProtocolError::CommitPhaseNotEnded(CommitPhaseNotEnded {}));
+        }
+        
+        if !self.freeze_commit_set(dispute_id) {
+            self.forfeit_silent_bonds(dispute_id);
+            return self.expire(dispute_id);
+        }
+        Ok(())
     }
     
-    /// Check if dispute is resolved
-    pub fn check_if_dispute_is_resolved(&self, dispute_id: u64) -> bool {
+    /// Close the vote once the reveal deadline passed (callable by anyone), so judges who
+    /// committed and went silent can't hold a dispute open. A commit phase nobody closed is
+    /// closed first. Seats that committed but never revealed lose their bond to the treasury
+    /// and 1 reputation, then the revealed votes are tallied as they stand: a majority rules,
+    /// a tie goes to a re-vote (or no ruling in the last round), and a dispute with no reveal
+    /// at all expires with the fee refunded.
+    pub fn finalize_dispute(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+        let phase = self.dispute_phase(dispute_id);
+        match phase {
+            PHASE_COMMIT | PHASE_REVEAL => {}
+            PHASE_RESOLVED => return Err(ProtocolError::DisputeAlreadyResolved(DisputeAlreadyResolved {})),
+            PHASE_EXPIRED => return Err(ProtocolError::DisputeIsExpired(DisputeIsExpired {})),
+            _ => return Err(ProtocolError::DisputeNotOpen(DisputeNotOpen {})),
+        }
+        
+        let now = U64::from(self.__stylus_host.block_timestamp());
+        if now < self.reveal_deadline_of(dispute_id) {
+            return Err(ProtocolError::RevealPhaseNotEnded(RevealPhaseNotEnded {}));
+        }
+        
+        if phase == PHASE_COMMIT && !self.freeze_commit_set(dispute_id) {
+            self.forfeit_silent_bonds(dispute_id);
+            return self.expire(dispute_id);
+        }
+        
+        self.forfeit_unrevealed_bonds(dispute_id);
+        let reveals = self.disputes.get(U64::from(dispute_id)).reveals_count.get();
+        log(&self.__stylus_host, RevealPhaseClosed {
+            dispute_id: U256::from(dispute_id),
+            reveals,
+        });
+        
+        if reveals == U256::ZERO {
+            return self.expire(dispute_id);
+        }
+        self.resolve(dispute_id)
+    }
+}
+
+// ====================================
+//        APPEALS FACET          
+// ====================================
+
+#[cfg_attr(feature = "appeals", public)]
+impl ProtocolContract {
+    /// Appeal a ruling within the appeal window (losing party only). The appellant pays the
+    /// category price for a new dispute between the same parties, judged by a panel with
+    /// nobody from the original one. The original panel's settlement waits for the appeal:
+    /// if it reverses the ruling, the original rewards go to the appeal panel and the original
+    /// reputation changes are inverted. Appeals are final and can't be appealed. An appeal
+    /// that stalls is closed like any dispute (expire_dispute, close_commit_phase or
+    /// finalize_dispute, all callable by anyone); closing without a ruling keeps the original
+    /// one and unlocks its panel.
+    pub fn appeal(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+        let sender = self.__stylus_host.msg_sender();
         let dispute = self.disputes.get(U64::from(dispute_id));
-        dispute.resolved.get()
+        
+        if dispute.resolution_kind.get() != U8::from(RESOLUTION_RULING)
+            || dispute.appeal_of.get() != U64::ZERO
+            || dispute.appealed_by.get() != U64::ZERO
+        {
+            return Err(ProtocolError::AppealNotAllowed(AppealNotAllowed {}));
+        }
+        
+        // With no appeal window the panel settles as soon as the ruling lands
+        let now = U64::from(self.__stylus_host.block_timestamp());
+        if !dispute.panel_pending.get() || now >= dispute.panel_unlock_at.get() {
+            return Err(ProtocolError::AppealWindowClosed(AppealWindowClosed {}));
+        }
+        
+        let requester = dispute.requester.get();
+        let beneficiary = dispute.beneficiary.get();
+        let requester_won = dispute.votes_for.get() > dispute.votes_against.get();
+        if sender != if requester_won { beneficiary } else { requester } {
+            return Err(ProtocolError::NotAParty(NotAParty {}));
+        }
+        
+        let deal_id = self.deal_id_of(dispute_id);
+        let category_id = u64::from_le_bytes(dispute.category_id.get().to_le_bytes());
+        let appeal_id = self.open_dispute_record(sender, requester, deal_id, beneficiary, category_id)?;
+        
+        self.disputes.setter(U64::from(appeal_id)).appeal_of.set(U64::from(dispute_id));
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+        dispute.appealed_by.set(U64::from(appeal_id));
+        dispute.panel_unlock_at.set(U64::MAX);
+        
+        log(&self.__stylus_host, DisputeAppealed {
+            dispute_id: U256::from(dispute_id),
+            appeal_id: U256::from(appeal_id),
+            appellant: sender,
+        });
+        
+        Ok(())
     }
     
-    /// Get judge info
-    pub fn get_judge(&self, judge_address: Address) -> (Address, U256, i8) {
-        let judge = self.judges.get(judge_address);
-        (
-            judge.judge_address.get(),
-            judge.balance.get(),
-            i8::from_le_bytes(judge.reputation.get().to_le_bytes()),
-        )
+    /// Execute dispute result - kept for backward compatibility, delegates to get_dispute_winner
+    pub fn execute_dispute_result(&self, dispute_id: u64) -> Result<bool, ProtocolError> {
+        self.get_dispute_winner(dispute_id)
+    }
+}
+
+// ====================================
+//        CONFIG FACET          
+// ====================================
+
+#[cfg_attr(feature = "config", public)]
+impl ProtocolContract {
+    /// Update the default panel size (only owner).
+    /// Disputes snapshot their panel size at creation, so open disputes keep the size they started with.
+    pub fn update_number_of_votes(&mut self, new_number: u8) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        if new_number == 0 {
+            return Err(ProtocolError::MustBeGreaterThanZero(MustBeGreaterThanZero {}));
+        }
+        
+        self.validate_panel_size(new_number)?;
+        
+        let old_number = u8::from_le_bytes(self.number_of_votes.get().to_le_bytes());
+        self.number_of_votes.set(U8::from(new_number));
+        
+        log(&self.__stylus_host, NumberOfVotesUpdated { old_number, new_number });
+        
+        Ok(())
     }
     
-    /// Get dispute basic info
-    pub fn get_dispute(&self, dispute_id: u64) -> (u32, Address, Address, Address, bool, bool, bool) {
-        let dispute = self.disputes.get(U64::from(dispute_id));
-        (
-            u32::from_le_bytes(dispute.dispute_id.get().to_le_bytes()),
-            dispute.contract_address.get(),
-            dispute.requester.get(),
-            dispute.beneficiary.get(),
-            dispute.waiting_for_judges.get(),
-            dispute.is_open.get(),
-            dispute.resolved.get(),
-        )
+    /// Require odd panel sizes for new settings (only owner)
+    pub fn set_strict_majority(&mut self, enabled: bool) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        self.strict_majority.set(enabled);
+        Ok(())
     }
     
-    /// Get dispute vote results
-    pub fn get_dispute_votes(&self, dispute_id: u64) -> (u8, u8) {
-        let dispute = self.disputes.get(U64::from(dispute_id));
-        (
-            u8::from_le_bytes(dispute.votes_for.get().to_le_bytes()),
-            u8::from_le_bytes(dispute.votes_against.get().to_le_bytes()),
-        )
+    /// Allow or forbid registering judges that are contracts, e.g. a Safe (only owner).
+    /// Judges already registered are unaffected.
+    pub fn set_allow_contract_judges(&mut self, allowed: bool) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        self.allow_contract_judges.set(allowed);
+        
+        log(&self.__stylus_host, ContractJudgesAllowed { allowed });
+        
+        Ok(())
     }
-}
\ No newline at end of file
+    
+    /// Set the guardian (only owner; zero removes it). The guardian can pause the protocol, ban
+    /// judges, replace idle judges, void a dispute in an emergency and cancel a queued
+    /// withdrawal, but can't withdraw funds or change fees and parameters.
+    pub fn set_guardian(&mut self, new_guardian: Address) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        let old_guardian = self.guardian.get();
+        self.guardian.set(new_guardian);
+        
+        log(&self.__stylus_host, GuardianUpdated { old_guardian, new_guardian });
+        
+        Ok(())
+    }
+    
+    /// Stop new disputes, judge registrations and panel seats (owner or guardian). Disputes
+    /// already under way can still be voted on, closed, settled and appealed.
+    pub fn pause(&mut self) -> Result<(), ProtocolError> {
+        self.only_owner_or_guardian()?;
+        
+        self.paused.set(true);
+        log(&self.__stylus_host, PauseChanged { by: self.__stylus_host.msg_sender(), paused: true });
+        
+        Ok(())
+    }
+    
+    /// Lift a pause (only owner)
+    pub fn unpause(&mut self) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        self.paused.set(false);
+        log(&self.__stylus_host, PauseChanged { by: self.__stylus_host.msg_sender(), paused: false });
+        
+        Ok(())
+    }
+    
+    /// Ban a judge from new panels (owner or guardian). Seats already held are unaffected
+    /// and can be reassigned with replace_judge.
+    pub fn ban_judge(&mut self, judge_address: Address) -> Result<(), ProtocolError> {
+        self.only_owner_or_guardian()?;
+        
+        let mut judge = self.judges.setter(judge_address);
+        if judge.judge_address.get() == Address::ZERO {
+            return Err(ProtocolError::NotAJudge(NotAJudge {}));
+        }
+        judge.banned.set(true);
+        judge.active.set(false);
+        
+        log(&self.__stylus_host, JudgeBanned { judge: judge_address, banned: true });
+        
+        Ok(())
+    }
+    
+    /// Lift a judge's ban (only owner); the judge then sets themselves available again
+    pub fn unban_judge(&mut self, judge_address: Address) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        let mut judge = self.judges.setter(judge_address);
+        if judge.judge_address.get() == Address::ZERO {
+            return Err(ProtocolError::NotAJudge(NotAJudge {}));
+        }
+        judge.banned.set(false);
+        
+        log(&self.__stylus_host, JudgeBanned { judge: judge_address, banned: false });
+        
+        Ok(())
+    }
+    
+    /// Update the minimum reputation required to join a panel (may be negative)
+    pub fn update_min_reputation(&mut self, new_min: i32) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        self.min_reputation.set(I32::from_le_bytes(new_min.to_le_bytes()));
+        
+        log(&self.__stylus_host, MinReputationUpdated { min_reputation: new_min });
+        
+        Ok(())
+    }
+    
+    /// Read judge reputation from an external registry instead of the internal counter (only owner).
+    /// Zero switches back; internal reputation keeps being updated either way.
+    pub fn set_reputation_oracle(&mut self, oracle: Address) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        let old_oracle = self.reputation_oracle.get();
+        self.reputation_oracle.set(oracle);
+        
+        log(&self.__stylus_host, ReputationOracleUpdated { old_oracle, new_oracle: oracle });
+        
+        Ok(())
+    }
+    
+    /// Update how long a dispute may wait for a full panel before it can be expired
+    pub fn update_panel_timeout(&mut self, new_timeout: u64) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        if new_timeout == 0 {
+            return Err(ProtocolError::MustBeGreaterThanZero(MustBeGreaterThanZero {}));
+        }
+        
+        self.panel_timeout.set(U64::from(new_timeout));
+        Ok(())
+    }
+    
+    /// Update the cooldown between panel assignments (only owner); zero disables it
+    pub fn update_judge_cooldown(&mut self, new_cooldown: u64) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        self.judge_cooldown.set(U64::from(new_cooldown));
+        Ok(())
+    }
+    
+    /// Update how long requesters have to confirm disputes from marketplaces that require it
+    pub fn update_ack_window(&mut self, new_window: u64) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        if new_window == 0 {
+            return Err(ProtocolError::MustBeGreaterThanZero(MustBeGreaterThanZero {}));
+        }
+        
+        self.ack_window.set(U64::from(new_window));
+        Ok(())
+    }
+    
+    /// Update how long a ruling's judge rewards and reputation changes stay locked (0 = settleable as soon as the ruling lands)
+    pub fn update_appeal_window(&mut self, new_window: u64) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        self.appeal_window.set(U64::from(new_window));
+        Ok(())
+    }
+    
+    /// Update how long judges have to commit once a panel is complete
+    pub fn update_commit_period(&mut self, new_period: u64) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        if new_period == 0 {
+            return Err(ProtocolError::MustBeGreaterThanZero(MustBeGreaterThanZero {}));
+        }
+        
+        self.commit_period.set(U64::from(new_period));
+        Ok(())
+    }
+    
+    /// Update how long judges have to reveal once the commit deadline passed
+    pub fn update_reveal_period(&mut self, new_period: u64) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        if new_period == 0 {
+            return Err(ProtocolError::MustBeGreaterThanZero(MustBeGreaterThanZero {}));
+        }
+        
+        self.reveal_period.set(U64::from(new_period));
+        Ok(())
+    }
+    
+    /// Update the share of each dispute fee paid to judges, in basis points (rest goes to the treasury)
+    pub fn update_judge_reward_bps(&mut self, new_bps: u16) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        if new_bps > BPS_DENOMINATOR {
+            return Err(ProtocolError::InvalidBps(InvalidBps {}));
+        }
+        
+        self.judge_reward_bps.set(U16::from(new_bps));
+        Ok(())
+    }
+    
+    /// Configure the bond judges lock when joining a panel, and the share of it a judge on the
+    /// losing side forfeits to the winners (disputes created from now on). Judges who never
+    /// commit forfeit the whole bond, also when too few commits expire the dispute; everyone
+    /// else gets it back, including a judge whose reveal was cut off by an early majority.
+    pub fn set_judge_bond(&mut self, amount: U256, slash_bps: u16) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        if slash_bps > BPS_DENOMINATOR {
+            return Err(ProtocolError::InvalidBps(InvalidBps {}));
+        }
+        
+        self.judge_bond.set(amount);
+        self.judge_bond_slash_bps.set(U16::from(slash_bps));
+        
+        log(&self.__stylus_host, JudgeBondUpdated { amount, slash_bps });
+        
+        Ok(())
+    }
+    
+    /// Toggle stake-weighted reward distribution for disputes created from now on
+    pub fn set_stake_weighted_rewards(&mut self, enabled: bool) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        self.stake_weighted_rewards.set(enabled);
+        Ok(())
+    }
+    
+    /// Configure the beneficiary defense bond for disputes created from now on
+    pub fn set_defense_bond(&mut self, required: bool, amount: U256, window: u64) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        if required && (amount == U256::ZERO || window == 0) {
+            return Err(ProtocolError::MustBeGreaterThanZero(MustBeGreaterThanZero {}));
+        }
+        
+        self.defense_bond_required.set(required);
+        self.defense_bond_amount.set(amount);
+        self.defense_bond_window.set(U64::from(window));
+        Ok(())
+    }
+    
+    /// Create or update a dispute category (id 0 is reserved for the global defaults)
+    pub fn set_category(&mut self, category_id: u64, price: U256, panel_size: u8) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        if category_id == 0 {
+            return Err(ProtocolError::InvalidCategory(InvalidCategory {}));
+        }
+        
+        if price == U256::ZERO || panel_size == 0 {
+            return Err(ProtocolError::MustBeGreaterThanZero(MustBeGreaterThanZero {}));
+        }
+        
+        self.validate_panel_size(panel_size)?;
+        
+        let id = U64::from(category_id);
+        if !self.categories.get(id).exists.get() {
+            let count = self.category_count.get();
+            self.category_list.setter(count).set(id);
+            self.category_count.set(count + U256::from(1u64));
+        }
+        
+        let mut category = self.categories.setter(id);
+        category.price.set(price);
+        category.panel_size.set(U8::from(panel_size));
+        category.exists.set(true);
+        
+        log(&self.__stylus_host, CategoryUpdated { category_id, price, panel_size });
+        
+        Ok(())
+    }
+    
+    /// Replace the reward tier table (only owner): judges at or above a tier's reputation
+    /// weigh in at its multiplier when sharing the judge pool. Empty arrays disable tiers.
+    pub fn set_reward_tiers(&mut self, min_reputations: Vec<i32>, multipliers_bps: Vec<u16>) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        if min_reputations.len() != multipliers_bps.len() {
+            return Err(ProtocolError::LengthMismatch(LengthMismatch {}));
+        }
+        
+        // Tiers must be ascending and capped so the lookup stays cheap
+        let ascending = min_reputations.windows(2).all(|pair| pair[0] < pair[1]);
+        if min_reputations.len() > MAX_REWARD_TIERS || !ascending || multipliers_bps.contains(&0) {
+            return Err(ProtocolError::InvalidRewardTiers(InvalidRewardTiers {}));
+        }
+        
+        for (i, (min_reputation, multiplier_bps)) in min_reputations.iter().zip(&multipliers_bps).enumerate() {
+            let mut tier = self.reward_tiers.setter(U256::from(i));
+            tier.min_reputation.set(I32::from_le_bytes(min_reputation.to_le_bytes()));
+            tier.multiplier_bps.set(U16::from(*multiplier_bps));
+        }
+        self.reward_tier_count.set(U8::from(min_reputations.len()));
+        
+        Ok(())
+    }
+}
+
+// ====================================
+//        OPERATIONS FACET          
+// ====================================
+
+#[cfg_attr(feature = "operations", public)]
+impl ProtocolContract {
+    /// Import judges and their reputation from a previous deployment (only owner, before the migration is locked)
+    pub fn import_judges(&mut self, addresses: Vec<Address>, reputations: Vec<i32>) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        if self.migration_locked.get() {
+            return Err(ProtocolError::MigrationIsLocked(MigrationIsLocked {}));
+        }
+        
+        if addresses.len() != reputations.len() {
+            return Err(ProtocolError::LengthMismatch(LengthMismatch {}));
+        }
+        
+        if addresses.len() > MAX_BATCH_SIZE {
+            return Err(ProtocolError::TooManyIds(TooManyIds {}));
+        }
+        
+        for (judge_address, reputation) in addresses.into_iter().zip(reputations) {
+            if judge_address == Address::ZERO {
+                return Err(ProtocolError::InvalidAddress(InvalidAddress {}));
+            }
+            
+            if self.judges.get(judge_address).judge_address.get() != Address::ZERO {
+                return Err(ProtocolError::AlreadyRegistered(AlreadyRegistered {}));
+            }
+            
+            let Ok(stored_reputation) = i8::try_from(reputation) else {
+                return Err(ProtocolError::ReputationOutOfRange(ReputationOutOfRange {}));
+            };
+            
+            let mut new_judge = self.judges.setter(judge_address);
+            new_judge.judge_address.set(judge_address);
+            new_judge.balance.set(U256::ZERO);
+            new_judge.reputation.set(I8::from_le_bytes(stored_reputation.to_le_bytes()));
+            new_judge.active.set(true);
+            self.list_judge(judge_address);
+            
+            log(&self.__stylus_host, JudgeImported { judge: judge_address, reputation });
+        }
+        
+        Ok(())
+    }
+    
+    /// Permanently close judge imports (only owner)
+    pub fn lock_migration(&mut self) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        self.migration_locked.set(true);
+        Ok(())
+    }
+    
+    /// Run one page of the next storage upgrade (only owner): backfill disputes `start` to
+    /// `start + count - 1`. Pages can't leave a gap, disputes already backfilled by this step
+    /// are skipped, and the version is bumped once a page reaches the last dispute. A no-op once
+    /// storage is current. Deployments that predate versioning read as version 0 and can't be
+    /// upgraded in place (the original layout's slots were reordered): they need a fresh
+    /// deployment, with judges carried over through import_judges.
+    pub fn migrate(&mut self, start: u64, count: u64) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        let from = u32::from_le_bytes(self.storage_version.get().to_le_bytes());
+        if from == STORAGE_VERSION {
+            return Ok(());
+        }
+        if from == 0 || from > STORAGE_VERSION {
+            return Err(ProtocolError::UnsupportedStorageVersion(UnsupportedStorageVersion {}));
+        }
+        
+        // Dispute ids start at 1
+        let next = u64::from_le_bytes(self.migration_cursor.get().to_le_bytes()).max(1);
+        if start > next {
+            return Err(ProtocolError::MigrationGap(MigrationGap {}));
+        }
+        let dispute_count = u64::from_le_bytes(self.dispute_count.get().to_le_bytes());
+        let end = start.max(1).saturating_add(count).min(dispute_count);
+        
+        if self.ack_window.get() == U64::ZERO {
+            self.ack_window.set(U64::from(DEFAULT_ACK_WINDOW));
+        }
+        for dispute_id in next..end {
+            self.migrate_dispute_v1_to_v2(dispute_id);
+        }
+        
+        if end < dispute_count {
+            if end > next {
+                self.migration_cursor.set(U64::from(end));
+            }
+            return Ok(());
+        }
+        
+        let to = from + 1;
+        self.migration_cursor.set(U64::ZERO);
+        self.storage_version.set(U32::from(to));
+        
+        log(&self.__stylus_host, Migrated { from, to });
+        
+        Ok(())
+    }
+    
+    /// Change the fee token (only owner); blocked while fees or judge rewards are owed in the old one
+    pub fn set_fee_token(&mut self, new_token: Address) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        if new_token == Address::ZERO {
+            return Err(ProtocolError::InvalidAddress(InvalidAddress {}));
+        }
+        
+        // Every ledger is denominated in the current token, so all of them must be empty before
+        // it changes: fees in escrow, unpaid judge rewards, stakes and the treasury (withdraw it first)
+        if self.total_escrowed.get() != U256::ZERO
+            || self.total_judge_rewards.get() != U256::ZERO
+            || self.total_staked.get() != U256::ZERO
+            || self.contract_balance.get() != U256::ZERO
+            || self.queued_withdrawal.get() != U256::ZERO
+        {
+            return Err(ProtocolError::OutstandingClaims(OutstandingClaims {}));
+        }
+        
+        let old_token = self.usdc_token.get();
+        self.usdc_token.set(new_token);
+        
+        log(&self.__stylus_host, FeeTokenUpdated { old_token, new_token });
+        
+        Ok(())
+    }
+    
+    /// Recover tokens sent to the contract outside the dispute flow (only owner).
+    /// Judge rewards and stakes, escrowed dispute funds and the treasury can't be swept. They are all
+    /// held in the fee token: set_fee_token only switches tokens once every one of them is empty.
+    pub fn rescue_tokens(&mut self, token: Address, to: Address, amount: U256) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        if token == Address::ZERO || to == Address::ZERO {
+            return Err(ProtocolError::InvalidAddress(InvalidAddress {}));
+        }
+        
+        if amount == U256::ZERO {
+            return Err(ProtocolError::MustBeGreaterThanZero(MustBeGreaterThanZero {}));
+        }
+        
+        let erc20 = IERC20::new(token);
+        if token == self.usdc_token.get() {
+            let contract_addr = self.__stylus_host.contract_address();
+            let balance = erc20.balance_of(&*self, contract_addr)?;
+            // The treasury only leaves through the withdrawal timelock
+            let reserved = self.total_judge_rewards.get()
+                + self.total_escrowed.get()
+                + self.contract_balance.get()
+                + self.total_staked.get();
+            if balance < reserved || amount > balance - reserved {
+                return Err(ProtocolError::RescueExceedsAvailable(RescueExceedsAvailable {}));
+            }
+        }
+        
+        if !erc20.transfer(&mut *self, to, amount)? {
+            return Err(ProtocolError::CallFailed(CallFailed {}));
+        }
+        
+        log(&self.__stylus_host, TokensRescued { token, to, amount });
+        
+        Ok(())
+    }
+    
+    /// Queue a treasury withdrawal of the current surplus (only owner); it unlocks after the withdraw delay
+    pub fn queue_withdrawal(&mut self) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        if self.queued_withdrawal.get() != U256::ZERO {
+            return Err(ProtocolError::WithdrawalAlreadyQueued(WithdrawalAlreadyQueued {}));
+        }
+        
+        // Judge rewards and fees held for open disputes are never withdrawable; tokens sent
+        // in outside the dispute flow aren't treasury either and leave through rescue_tokens
+        let amount = self.contract_balance.get();
+        
+        if amount == U256::ZERO {
+            return Err(ProtocolError::NoUSDCToWithdraw(NoUSDCToWithdraw {}));
+        }
+        
+        let unlock_time = U64::from(self.__stylus_host.block_timestamp()) + self.withdraw_delay.get().max(U64::from(MIN_WITHDRAW_DELAY));
+        self.queued_withdrawal.set(amount);
+        self.withdrawal_unlock_time.set(unlock_time);
+        
+        log(&self.__stylus_host, WithdrawalQueued {
+            amount,
+            unlock_time: u64::from_le_bytes(unlock_time.to_le_bytes()),
+        });
+        
+        Ok(())
+    }
+    
+    /// Send a queued treasury withdrawal to the owner once its delay has passed (only owner)
+    pub fn execute_withdrawal(&mut self) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        let sender = self.__stylus_host.msg_sender();
+        
+        let amount = self.queued_withdrawal.get();
+        if amount == U256::ZERO {
+            return Err(ProtocolError::NoWithdrawalQueued(NoWithdrawalQueued {}));
+        }
+        
+        if U64::from(self.__stylus_host.block_timestamp()) < self.withdrawal_unlock_time.get() {
+            return Err(ProtocolError::WithdrawalLocked(WithdrawalLocked {}));
+        }
+        
+        let contract_balance = self.contract_balance.get();
+        if amount > contract_balance {
+            return Err(ProtocolError::NoUSDCToWithdraw(NoUSDCToWithdraw {}));
+        }
+        
+        self.contract_balance.set(contract_balance - amount);
+        self.queued_withdrawal.set(U256::ZERO);
+        self.withdrawal_unlock_time.set(U64::ZERO);
+        
+        self.push_tokens(sender, amount)?;
+        
+        log(&self.__stylus_host, OwnerWithdrawn { to: sender, amount });
+        
+        Ok(())
+    }
+    
+    /// Cancel a queued treasury withdrawal (owner or guardian)
+    pub fn cancel_withdrawal(&mut self) -> Result<(), ProtocolError> {
+        self.only_owner_or_guardian()?;
+        
+        let amount = self.queued_withdrawal.get();
+        if amount == U256::ZERO {
+            return Err(ProtocolError::NoWithdrawalQueued(NoWithdrawalQueued {}));
+        }
+        
+        self.queued_withdrawal.set(U256::ZERO);
+        self.withdrawal_unlock_time.set(U64::ZERO);
+        
+        log(&self.__stylus_host, WithdrawalCancelled { amount });
+        
+        Ok(())
+    }
+    
+    /// Update the treasury withdrawal delay (only owner); never below 24 hours
+    pub fn update_withdraw_delay(&mut self, new_delay: u64) -> Result<(), ProtocolError> {
+        self.only_owner()?;
+        
+        if new_delay < MIN_WITHDRAW_DELAY {
+            return Err(ProtocolError::DelayTooShort(DelayTooShort {}));
+        }
+        
+        self.withdraw_delay.set(U64::from(new_delay));
+        Ok(())
+    }
+}
+
+// ====================================
+//        PANEL VIEWS FACET          
+// ====================================
+
+#[cfg_attr(feature = "panel-views", public)]
+impl ProtocolContract {
+    /// Get the number of disputes currently recruiting a panel
+    pub fn open_for_registration_count(&self) -> U256 {
+        self.open_for_registration_count.get()
+    }
+    
+    /// Get ids of disputes currently recruiting a panel, up to 100 per call. The index is
+    /// unordered: removals move the last entry into the freed position.
+    pub fn get_open_for_registration(&self, offset: U256, limit: U256) -> Vec<u64> {
+        let count = self.open_for_registration_count.get();
+        let limit = limit.min(U256::from(MAX_BATCH_SIZE));
+        let end = offset.saturating_add(limit).min(count);
+        
+        let mut ids = Vec::new();
+        let mut i = offset;
+        while i < end {
+            ids.push(u64::from_le_bytes(self.open_for_registration.get(i).to_le_bytes()));
+            i += U256::from(1u64);
+        }
+        ids
+    }
+    
+    /// Compute the commit hash `reveal_votes` verifies; `judge` is the panel seat the vote is cast for.
+    /// Secrets must be at least 16 random bytes; shorter ones revert with WeakSecret here and at reveal.
+    pub fn compute_commit_hash(&self, dispute_id: u64, judge: Address, vote: u8, secret: Vec<u8>) -> Result<FixedBytes<32>, ProtocolError> {
+        if secret.len() < MIN_SECRET_LENGTH {
+            return Err(ProtocolError::WeakSecret(WeakSecret {}));
+        }
+        Ok(Self::commit_hash(dispute_id, judge, vote, &secret))
+    }
+    
+    /// Get a judge's next nonce for signed commits
+    pub fn nonces(&self, judge: Address) -> U256 {
+        self.nonces.get(judge)
+    }
+    
+    /// Get the EIP-712 domain separator used for signed commits
+    pub fn domain_separator(&self) -> FixedBytes<32> {
+        self.domain_separator_hash()
+    }
+    
+    /// Get dispute vote results as (for, against, abstain)
+    pub fn get_dispute_votes(&self, dispute_id: u64) -> (u8, u8, u8) {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        (
+            u8::from_le_bytes(dispute.votes_for.get().to_le_bytes()),
+            u8::from_le_bytes(dispute.votes_against.get().to_le_bytes()),
+            u8::from_le_bytes(dispute.votes_abstain.get().to_le_bytes()),
+        )
+    }
+    
+    /// Get dispute progress as (judges assigned, commits, reveals, required votes)
+    /// Returns zeros for nonexistent disputes
+    pub fn get_dispute_progress(&self, dispute_id: u64) -> (U256, U256, U256, u8) {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        (
+            dispute.able_to_vote_count.get(),
+            dispute.commits_count.get(),
+            dispute.reveals_count.get(),
+            u8::from_le_bytes(dispute.required_votes.get().to_le_bytes()),
+        )
+    }
+    
+    /// Get dispute deadlines as (created_at, panel deadline, commit deadline, reveal deadline)
+    /// The commit and reveal deadlines are 0 until the panel fills; returns zeros for nonexistent disputes
+    pub fn get_dispute_deadlines(&self, dispute_id: u64) -> (u64, u64, u64, u64) {
+        if self.dispute_phase(dispute_id) == PHASE_NOT_FOUND {
+            return (0, 0, 0, 0);
+        }
+        
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        let created_at = u64::from_le_bytes(dispute.created_at.get().to_le_bytes());
+        let round_started_at = u64::from_le_bytes(dispute.round_started_at.get().to_le_bytes());
+        let panel_timeout = u64::from_le_bytes(self.panel_timeout.get().to_le_bytes());
+        (
+            created_at,
+            round_started_at + panel_timeout,
+            u64::from_le_bytes(dispute.commit_deadline.get().to_le_bytes()),
+            if dispute.commit_deadline.get() == U64::ZERO {
+                0
+            } else {
+                u64::from_le_bytes(self.reveal_deadline_of(dispute_id).to_le_bytes())
+            },
+        )
+    }
+    
+    /// Get dispute panel as (judge, reputation at assignment)
+    pub fn get_dispute_panel(&self, dispute_id: u64) -> Vec<(Address, i32)> {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        let base = Self::round_base(&dispute);
+        let able_count = dispute.able_to_vote_count.get();
+        let mut panel = Vec::new();
+        for i in 0..able_count.as_limbs()[0] {
+            let idx = base + U256::from(i);
+            panel.push((
+                dispute.able_to_vote.get(idx),
+                i32::from_le_bytes(dispute.reputation_at_assignment.get(idx).to_le_bytes()),
+            ));
+        }
+        panel
+    }
+    
+    /// Get individual votes of a resolved dispute as (judge, did_reveal, vote), one entry per commit
+    /// vote is 1 For, 2 Against, 3 Abstain (0 if not revealed)
+    /// Reverts before resolution so votes can't be observed during the reveal phase
+    pub fn get_revealed_votes(&self, dispute_id: u64) -> Result<Vec<(Address, bool, u8)>, ProtocolError> {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        
+        if !dispute.resolved.get() {
+            return Err(ProtocolError::DisputeNotResolvedYet(DisputeNotResolvedYet {}));
+        }
+        
+        let base = Self::round_base(&dispute);
+        let commit_count = dispute.commits_count.get();
+        let mut votes = Vec::new();
+        for i in 0..commit_count.as_limbs()[0] {
+            let idx = base + U256::from(i);
+            votes.push((
+                dispute.voters.get(idx),
+                dispute.vote_plain.get(idx) != U8::ZERO,
+                u8::from_le_bytes(dispute.vote_plain.get(idx).to_le_bytes()),
+            ));
+        }
+        
+        Ok(votes)
+    }
+}
+
+// ====================================
+//        DISPUTE VIEWS FACET          
+// ====================================
+
+#[cfg_attr(feature = "dispute-views", public)]
+impl ProtocolContract {
+    /// Get dispute winner (called by Marketplace to execute result)
+    /// Returns true if requester (payer) wins, false if beneficiary (seller) wins;
+    /// disputes closed without a ruling revert with the matching error
+    pub fn get_dispute_winner(&self, dispute_id: u64) -> Result<bool, ProtocolError> {
+        match self.dispute_outcome(dispute_id) {
+            OUTCOME_REQUESTER_WON => Ok(true),
+            OUTCOME_BENEFICIARY_WON => Ok(false),
+            OUTCOME_NO_RULING => Err(ProtocolError::NoRuling(NoRuling {})),
+            OUTCOME_TIED => Err(ProtocolError::DisputeTied(DisputeTied {})),
+            OUTCOME_EXPIRED => Err(ProtocolError::DisputeIsExpired(DisputeIsExpired {})),
+            _ => Err(ProtocolError::DisputeNotResolvedYet(DisputeNotResolvedYet {})),
+        }
+    }
+    
+    /// Get how a dispute ended: 0 pending, 1 requester won, 2 beneficiary won,
+    /// 3 no ruling (all abstained), 4 tied, 5 expired
+    pub fn get_dispute_outcome(&self, dispute_id: u64) -> u8 {
+        self.dispute_outcome(dispute_id)
+    }
+    
+    /// Get dispute count
+    pub fn dispute_count(&self) -> u64 {
+        u64::from_le_bytes(self.dispute_count.get().to_le_bytes())
+    }
+    
+    /// Get the dispute opened for a deal, 0 if none. `creator` is the marketplace for
+    /// create_dispute, the sponsor for create_dispute_for, or the requester for direct disputes.
+    pub fn get_dispute_id_for_deal(&self, creator: Address, deal_id: u64) -> u64 {
+        let dispute_id = self.deal_disputes.getter(creator).get(U64::from(deal_id));
+        u64::from_le_bytes(dispute_id.to_le_bytes())
+    }
+    
+    /// Get who paid a dispute's fee and receives its refunds (zero for marketplace disputes)
+    pub fn get_fee_payer(&self, dispute_id: u64) -> Address {
+        self.disputes.get(U64::from(dispute_id)).fee_payer.get()
+    }
+    
+    /// Check whether a marketplace may sponsor disputes without the requester's signature
+    pub fn is_sponsor(&self, sponsor: Address) -> bool {
+        self.sponsors.get(sponsor)
+    }
+    
+    /// Get a ruling's pending panel settlement: whether it is still pending, the judge rewards
+    /// held, and when settle_original_panel can release them
+    pub fn get_pending_settlement(&self, dispute_id: u64) -> (bool, U256, u64) {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        (
+            dispute.panel_pending.get(),
+            dispute.pending_rewards.get(),
+            u64::from_le_bytes(dispute.panel_unlock_at.get().to_le_bytes()),
+        )
+    }
+    
+    /// Get a dispute's appeal links: the dispute it appeals, the appeal filed against it
+    /// (0 if none), and whether its ruling was overturned
+    pub fn get_appeal(&self, dispute_id: u64) -> (u64, u64, bool) {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        (
+            u64::from_le_bytes(dispute.appeal_of.get().to_le_bytes()),
+            u64::from_le_bytes(dispute.appealed_by.get().to_le_bytes()),
+            dispute.overturned.get(),
+        )
+    }
+    
+    /// Get the latest proof submitted by or relayed for a party (empty if none)
+    pub fn get_proof(&self, dispute_id: u64, party: Address) -> String {
+        self.disputes.get(U64::from(dispute_id)).proofs.getter(party).get_string()
+    }
+    
+    /// Check whether a marketplace's disputes need the requester's confirmation
+    pub fn requires_requester_ack(&self, marketplace: Address) -> bool {
+        self.require_requester_ack.get(marketplace)
+    }
+    
+    /// Get whether a dispute still awaits the requester's confirmation, and the deadline
+    pub fn get_ack_status(&self, dispute_id: u64) -> (bool, u64) {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        (
+            dispute.pending_ack.get(),
+            u64::from_le_bytes(dispute.ack_deadline.get().to_le_bytes()),
+        )
+    }
+    
+    /// Get the category a dispute was created under
+    pub fn get_dispute_category(&self, dispute_id: u64) -> u64 {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        u64::from_le_bytes(dispute.category_id.get().to_le_bytes())
+    }
+    
+    /// Get the fee, bonds and judge bonds still held for a dispute (zero once it is closed and
+    /// its panel settled)
+    pub fn get_dispute_escrow(&self, dispute_id: u64) -> U256 {
+        self.disputes.get(U64::from(dispute_id)).escrow_amount.get()
+    }
+    
+    /// Get the fee terms and remaining escrow of a dispute: nominal price, amount received,
+    /// fee payer, protocol-fee and judge-reward bps snapshotted at creation, escrow left
+    pub fn get_dispute_financials(&self, dispute_id: u64) -> Result<DisputeFinancials, ProtocolError> {
+        self.dispute_financials(dispute_id)
+    }
+    
+    /// Get settlement state: pending proposer (zero if none), proposed or agreed outcome, settled flag
+    pub fn get_settlement(&self, dispute_id: u64) -> (Address, bool, bool) {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        (
+            dispute.settlement_proposer.get(),
+            dispute.settlement_requester_wins.get(),
+            dispute.settled.get(),
+        )
+    }
+    
+    /// Get the dispute's re-vote round (0 for the first panel)
+    pub fn get_dispute_round(&self, dispute_id: u64) -> u8 {
+        u8::from_le_bytes(self.disputes.get(U64::from(dispute_id)).round.get().to_le_bytes())
+    }
+    
+    /// Get dispute phase: 0 NotFound, 1 WaitingForJudges, 2 Commit, 3 Reveal, 4 Resolved,
+    /// 5 Cancelled (reserved), 6 Expired (or voided), 7 PendingAck
+    pub fn get_dispute_phase(&self, dispute_id: u64) -> u8 {
+        self.dispute_phase(dispute_id)
+    }
+    
+    /// Get the delegate voting for a judge's seat on a dispute (zero if not delegated)
+    pub fn get_vote_delegate(&self, dispute_id: u64, judge: Address) -> Address {
+        self.disputes.get(U64::from(dispute_id)).delegate_of.get(judge)
+    }
+    
+    /// Get defense bond state as (amount, deadline, posted); amount is 0 when no bond is required
+    pub fn get_defense_bond(&self, dispute_id: u64) -> (U256, u64, bool) {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        (
+            dispute.bond_amount.get(),
+            u64::from_le_bytes(dispute.bond_deadline.get().to_le_bytes()),
+            dispute.bond_posted.get(),
+        )
+    }
+    
+    /// Check if dispute expired without filling its panel
+    pub fn check_if_dispute_is_expired(&self, dispute_id: u64) -> bool {
+        self.disputes.get(U64::from(dispute_id)).expired.get()
+    }
+    
+    /// Check if dispute is resolved
+    pub fn check_if_dispute_is_resolved(&self, dispute_id: u64) -> bool {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        dispute.resolved.get()
+    }
+    
+    /// Get full dispute info as a named struct
+    pub fn get_dispute_info(&self, dispute_id: u64) -> DisputeInfo {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        DisputeInfo {
+            dispute_id,
+            deal_id: self.deal_id_of(dispute_id),
+            category_id: u64::from_le_bytes(dispute.category_id.get().to_le_bytes()),
+            contract_address: dispute.contract_address.get(),
+            requester: dispute.requester.get(),
+            beneficiary: dispute.beneficiary.get(),
+            price: dispute.price.get(),
+            required_votes: u8::from_le_bytes(dispute.required_votes.get().to_le_bytes()),
+            created_at: u64::from_le_bytes(dispute.created_at.get().to_le_bytes()),
+            commit_deadline: u64::from_le_bytes(dispute.commit_deadline.get().to_le_bytes()),
+            phase: self.dispute_phase(dispute_id),
+            waiting_for_judges: dispute.waiting_for_judges.get(),
+            is_open: dispute.is_open.get(),
+            resolved: dispute.resolved.get(),
+            expired: dispute.expired.get(),
+            no_ruling: dispute.no_ruling.get(),
+            votes_for: u8::from_le_bytes(dispute.votes_for.get().to_le_bytes()),
+            votes_against: u8::from_le_bytes(dispute.votes_against.get().to_le_bytes()),
+            votes_abstain: u8::from_le_bytes(dispute.votes_abstain.get().to_le_bytes()),
+            resolved_at: u64::from_le_bytes(dispute.resolved_at.get().to_le_bytes()),
+            resolution_kind: u8::from_le_bytes(dispute.resolution_kind.get().to_le_bytes()),
+        }
+    }
+    
+    /// Get dispute basic info. The deal id is the legacy u32 one, zero for ids that don't fit;
+    /// get_dispute_info has the full value
+    pub fn get_dispute(&self, dispute_id: u64) -> (u32, Address, Address, Address, bool, bool, bool) {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        (
+            u32::from_le_bytes(dispute.dispute_id.get().to_le_bytes()),
+            dispute.contract_address.get(),
+            dispute.requester.get(),
+            dispute.beneficiary.get(),
+            dispute.waiting_for_judges.get(),
+            dispute.is_open.get(),
+            dispute.resolved.get(),
+        )
+    }
+}
+
+// ====================================
+//        PROTOCOL VIEWS FACET          
+// ====================================
+
+#[cfg_attr(feature = "judge-views", public)]
+impl ProtocolContract {
+    /// Check whether the contract implements an interface: ERC-165 itself or the
+    /// dispute interface marketplaces integrate against
+    pub fn supports_interface(&self, interface_id: FixedBytes<4>) -> bool {
+        interface_id == FixedBytes(ERC165_INTERFACE_ID) || interface_id == FixedBytes(DISPUTE_INTERFACE_ID)
+    }
+    
+    /// Get the id of the dispute interface (XOR of its function selectors)
+    pub fn dispute_interface_id(&self) -> FixedBytes<4> {
+        FixedBytes(DISPUTE_INTERFACE_ID)
+    }
+    
+    /// Get the storage layout version
+    pub fn storage_version(&self) -> u32 {
+        u32::from_le_bytes(self.storage_version.get().to_le_bytes())
+    }
+    
+    /// Get the next dispute the running migration step will backfill (0 before its first page)
+    pub fn migration_cursor(&self) -> u64 {
+        u64::from_le_bytes(self.migration_cursor.get().to_le_bytes())
+    }
+    
+    /// Get the guardian address (zero if none)
+    pub fn guardian(&self) -> Address {
+        self.guardian.get()
+    }
+    
+    /// Whether the protocol is paused
+    pub fn is_paused(&self) -> bool {
+        self.paused.get()
+    }
+    
+    /// Get number of votes required
+    pub fn number_of_votes(&self) -> u8 {
+        u8::from_le_bytes(self.number_of_votes.get().to_le_bytes())
+    }
+    
+    /// Get dispute price
+    pub fn dispute_price(&self) -> U256 {
+        self.dispute_price.get()
+    }
+    
+    /// Get all configured categories as (category_id, price, panel_size)
+    pub fn get_categories(&self) -> Vec<(u64, U256, u8)> {
+        let count = self.category_count.get();
+        let mut categories = Vec::new();
+        for i in 0..count.as_limbs()[0] {
+            let id = self.category_list.get(U256::from(i));
+            let category = self.categories.get(id);
+            categories.push((
+                u64::from_le_bytes(id.to_le_bytes()),
+                category.price.get(),
+                u8::from_le_bytes(category.panel_size.get().to_le_bytes()),
+            ));
+        }
+        categories
+    }
+    
+    /// Get the reward tier table as (min reputation, multiplier in bps)
+    pub fn get_reward_tiers(&self) -> Vec<(i32, u16)> {
+        let mut tiers = Vec::new();
+        for i in 0..u8::from_le_bytes(self.reward_tier_count.get().to_le_bytes()) {
+            let tier = self.reward_tiers.get(U256::from(i));
+            tiers.push((
+                i32::from_le_bytes(tier.min_reputation.get().to_le_bytes()),
+                u16::from_le_bytes(tier.multiplier_bps.get().to_le_bytes()),
+            ));
+        }
+        tiers
+    }
+    
+    /// Get the number of registered judges
+    pub fn judge_count(&self) -> U256 {
+        self.judge_count.get()
+    }
+    
+    /// Get registered judges in registration order, up to 100 per call
+    pub fn get_judges(&self, offset: U256, limit: U256) -> Vec<Address> {
+        let count = self.judge_count.get();
+        let limit = limit.min(U256::from(MAX_BATCH_SIZE));
+        let end = offset.saturating_add(limit).min(count);
+        
+        let mut judges = Vec::new();
+        let mut i = offset;
+        while i < end {
+            judges.push(self.judge_list.get(i));
+            i += U256::from(1u64);
+        }
+        judges
+    }
+    
+    /// Get minimum reputation required to join a panel
+    pub fn min_reputation(&self) -> i32 {
+        i32::from_le_bytes(self.min_reputation.get().to_le_bytes())
+    }
+    
+    /// Get total rewards owed to judges
+    pub fn total_judge_rewards(&self) -> U256 {
+        self.total_judge_rewards.get()
+    }
+    
+    /// Get total fees and bonds held for disputes not yet settled
+    pub fn total_escrowed(&self) -> U256 {
+        self.total_escrowed.get()
+    }
+    
+    /// Sum of all judge stakes held by the contract
+    pub fn total_staked(&self) -> U256 {
+        self.total_staked.get()
+    }
+    
+    /// Check if contracts may register as judges
+    pub fn allow_contract_judges(&self) -> bool {
+        self.allow_contract_judges.get()
+    }
+    
+    /// Check if panel sizes must be odd
+    pub fn strict_majority(&self) -> bool {
+        self.strict_majority.get()
+    }
+    
+    /// Check if judge imports have been locked
+    pub fn migration_locked(&self) -> bool {
+        self.migration_locked.get()
+    }
+    
+    /// Get the queued treasury withdrawal as (amount, unlock time), zero if none
+    pub fn get_queued_withdrawal(&self) -> (U256, u64) {
+        (
+            self.queued_withdrawal.get(),
+            u64::from_le_bytes(self.withdrawal_unlock_time.get().to_le_bytes()),
+        )
+    }
+    
+    /// Get the protocol treasury balance (fees not owed to judges or parties)
+    pub fn contract_balance(&self) -> U256 {
+        self.contract_balance.get()
+    }
+    
+    /// Get the treasury withdrawal delay in seconds
+    pub fn withdraw_delay(&self) -> u64 {
+        u64::from_le_bytes(self.withdraw_delay.get().to_le_bytes()).max(MIN_WITHDRAW_DELAY)
+    }
+    
+    /// Get the token disputes are paid in
+    pub fn fee_token(&self) -> Address {
+        self.usdc_token.get()
+    }
+    
+    /// Get the cooldown between panel assignments in seconds (0 = disabled)
+    pub fn judge_cooldown(&self) -> u64 {
+        u64::from_le_bytes(self.judge_cooldown.get().to_le_bytes())
+    }
+    
+    /// Get panel timeout in seconds
+    pub fn panel_timeout(&self) -> u64 {
+        u64::from_le_bytes(self.panel_timeout.get().to_le_bytes())
+    }
+    
+    /// Get the judge reward share in basis points applied to new disputes
+    pub fn judge_reward_bps(&self) -> u16 {
+        u16::from_le_bytes(self.judge_reward_bps.get().to_le_bytes())
+    }
+    
+    /// Check if stake-weighted reward distribution is enabled for new disputes
+    pub fn stake_weighted_rewards(&self) -> bool {
+        self.stake_weighted_rewards.get()
+    }
+    
+    /// Get judge info: address, balance and reputation (zeroed for unknown addresses)
+    pub fn get_judge(&self, judge_address: Address) -> (Address, U256, i8) {
+        let judge = self.judges.get(judge_address);
+        (
+            judge.judge_address.get(),
+            judge.balance.get(),
+            i8::from_le_bytes(judge.reputation.get().to_le_bytes()),
+        )
+    }
+    
+    /// Check if an address registered as a judge
+    pub fn is_judge(&self, judge_address: Address) -> bool {
+        self.judges.get(judge_address).judge_address.get() != Address::ZERO
+    }
+    
+    /// Get a judge's reputation (0 for unknown addresses)
+    pub fn judge_reputation(&self, judge_address: Address) -> i32 {
+        i32::from(i8::from_le_bytes(self.judges.get(judge_address).reputation.get().to_le_bytes()))
+    }
+    
+    /// Get the number of settled panel seats recorded in a judge's history
+    pub fn get_judge_history_length(&self, judge_address: Address) -> U256 {
+        self.judges.get(judge_address).history_count.get()
+    }
+    
+    /// Get a judge's settled panel seats in settlement order, up to 100 per call: the dispute,
+    /// whether the vote was revealed and with the majority, the reward and the reputation change
+    pub fn get_judge_history(&self, judge_address: Address, offset: U256, limit: U256) -> Vec<JudgeHistoryInfo> {
+        let judge = self.judges.get(judge_address);
+        let count = judge.history_count.get();
+        let limit = limit.min(U256::from(MAX_BATCH_SIZE));
+        let end = offset.saturating_add(limit).min(count);
+        
+        let mut history = Vec::new();
+        let mut i = offset;
+        while i < end {
+            let entry = judge.history.get(i);
+            history.push(JudgeHistoryInfo {
+                dispute_id: u64::from_le_bytes(entry.dispute_id.get().to_le_bytes()),
+                vote_revealed: entry.vote_revealed.get(),
+                was_majority: entry.was_majority.get(),
+                reward_amount: entry.reward_amount.get(),
+                reputation_delta: i8::from_le_bytes(entry.reputation_delta.get().to_le_bytes()),
+            });
+            i += U256::from(1u64);
+        }
+        history
+    }
+    
+    /// Get the judge bond amount and the slash share (bps) for new disputes
+    pub fn judge_bond(&self) -> (U256, u16) {
+        (self.judge_bond.get(), u16::from_le_bytes(self.judge_bond_slash_bps.get().to_le_bytes()))
+    }
+    
+    /// Get the bond a judge still has locked on a dispute (zero if not seated or already settled)
+    pub fn get_locked_judge_bond(&self, dispute_id: u64, judge: Address) -> U256 {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        let panel_index = dispute.panel_index.get(judge);
+        if panel_index == U256::ZERO {
+            return U256::ZERO;
+        }
+        dispute.bond_at_assignment.get(panel_index - U256::from(1u64))
+    }
+    
+    /// Get the external reputation registry (zero if internal reputation is used)
+    pub fn reputation_oracle(&self) -> Address {
+        self.reputation_oracle.get()
+    }
+    
+    /// Get the reputation used for panel eligibility and reward tiers: the oracle's
+    /// value when one is set and answers, the internal counter otherwise
+    pub fn effective_reputation(&self, judge_address: Address) -> i32 {
+        self.judge_standing(judge_address)
+    }
+    
+    /// Get a judge's withdrawable reward balance (0 for unknown addresses)
+    pub fn judge_balance(&self, judge_address: Address) -> U256 {
+        self.judges.get(judge_address).balance.get()
+    }
+    
+    /// Check if a judge is currently accepting panel assignments
+    pub fn is_judge_active(&self, judge_address: Address) -> bool {
+        self.judges.get(judge_address).active.get()
+    }
+    
+    /// Get judge info as a named struct
+    pub fn get_judge_info(&self, judge_address: Address) -> JudgeInfo {
+        let judge = self.judges.get(judge_address);
+        JudgeInfo {
+            judge_address: judge.judge_address.get(),
+            balance: judge.balance.get(),
+            reputation: i32::from(i8::from_le_bytes(judge.reputation.get().to_le_bytes())),
+            active: judge.active.get(),
+            stake: judge.stake.get(),
+            last_assignment_time: u64::from_le_bytes(judge.last_assignment_time.get().to_le_bytes()),
+            is_contract: judge.is_contract.get(),
+        }
+    }
+    
+    /// Get headline protocol numbers. Open counts every dispute not yet in a terminal state
+    /// (pending confirmation, recruiting, voting); the treasury surplus is the owner's balance.
+    /// Judge rewards paid include slashed and forfeited bonds redistributed to winners, but not
+    /// bonds returned to the judges who posted them.
+    pub fn get_protocol_metrics(&self) -> ProtocolMetrics {
+        // Ids start at 1, and dispute_count is still 0 before init
+        let created = u64::from_le_bytes(self.dispute_count.get().to_le_bytes()).saturating_sub(1);
+        let resolved = u64::from_le_bytes(self.disputes_resolved.get().to_le_bytes());
+        let expired = u64::from_le_bytes(self.disputes_expired.get().to_le_bytes());
+        ProtocolMetrics {
+            disputes_created: created,
+            disputes_open: created.saturating_sub(resolved).saturating_sub(expired),
+            disputes_resolved: resolved,
+            disputes_expired: expired,
+            total_fees_collected: self.total_fees_collected.get(),
+            total_judge_rewards_paid: self.total_judge_rewards_paid.get(),
+            treasury_surplus: self.contract_balance.get(),
+        }
+    }
+}
+
+// ====================================
+//        INTERNAL FUNCTIONS          
+// ====================================
+
+impl ProtocolContract {
+    /// Revert unless the caller is the owner
+    fn only_owner(&self) -> Result<(), ProtocolError> {
+        if self.__stylus_host.msg_sender() != self.owner.get() {
+            return Err(ProtocolError::NotOwner(NotOwner {}));
+        }
+        Ok(())
+    }
+    
+    /// Revert unless running as a step handed over by call_facet
+    fn only_facet_call(&self) -> Result<(), ProtocolError> {
+        if !self.facets.in_facet_call.get() {
+            return Err(ProtocolError::NotAFacetCall(NotAFacetCall {}));
+        }
+        Ok(())
+    }
+    
+    /// Run an internal step in the facet that implements it, passing its revert through.
+    /// Storage handles held across this call are stale, so callers only use it last.
+    fn call_facet(&mut self, calldata: Vec<u8>) -> Result<(), ProtocolError> {
+        let selector = FixedBytes::<4>::from_slice(&calldata[..4]);
+        let facet = self.facets.routes.get(selector);
+        if facet == Address::ZERO {
+            return Err(ProtocolError::UnknownSelector(UnknownSelector {}));
+        }
+        
+        self.facets.in_facet_call.set(true);
+        let result = unsafe { RawCall::new_delegate().clear_storage_cache().call(facet, &calldata) };
+        self.facets.in_facet_call.set(false);
+        
+        result.map(|_| ()).map_err(|data| {
+            ProtocolError::from_revert(&data).unwrap_or(ProtocolError::CallFailed(CallFailed {}))
+        })
+    }
+    
+    /// Revert while the protocol is paused
+    fn when_not_paused(&self) -> Result<(), ProtocolError> {
+        if self.paused.get() {
+            return Err(ProtocolError::ProtocolPaused(ProtocolPaused {}));
+        }
+        Ok(())
+    }
+    
+    /// Revert unless the caller is the owner or the guardian
+    fn only_owner_or_guardian(&self) -> Result<(), ProtocolError> {
+        let sender = self.__stylus_host.msg_sender();
+        let guardian = self.guardian.get();
+        if sender != self.owner.get() && (guardian == Address::ZERO || sender != guardian) {
+            return Err(ProtocolError::NotOwnerOrGuardian(NotOwnerOrGuardian {}));
+        }
+        Ok(())
+    }
+    
+    /// Resolve the price and panel size for a category (0 = global defaults)
+    fn category_terms(&self, category_id: u64) -> Result<(U256, U8), ProtocolError> {
+        if category_id == 0 {
+            return Ok((self.dispute_price.get(), self.number_of_votes.get()));
+        }
+        
+        let category = self.categories.get(U64::from(category_id));
+        if !category.exists.get() {
+            return Err(ProtocolError::CategoryDoesNotExist(CategoryDoesNotExist {}));
+        }
+        
+        Ok((category.price.get(), category.panel_size.get()))
+    }
+    
+    /// Reward multiplier for a judge's reputation: the highest tier reached, or 100%
+    fn reward_multiplier(&self, reputation: i32) -> u16 {
+        let mut multiplier = BPS_DENOMINATOR;
+        for i in 0..u8::from_le_bytes(self.reward_tier_count.get().to_le_bytes()) {
+            let tier = self.reward_tiers.get(U256::from(i));
+            if reputation >= i32::from_le_bytes(tier.min_reputation.get().to_le_bytes()) {
+                multiplier = u16::from_le_bytes(tier.multiplier_bps.get().to_le_bytes());
+            }
+        }
+        multiplier
+    }
+    
+    /// Deal id a dispute was opened for; disputes older than the u64 field only have the u32 one
+    fn deal_id_of(&self, dispute_id: u64) -> u64 {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        let deal_id = u64::from_le_bytes(dispute.deal_id.get().to_le_bytes());
+        if deal_id != 0 {
+            deal_id
+        } else {
+            u64::from(u32::from_le_bytes(dispute.dispute_id.get().to_le_bytes()))
+        }
+    }
+    
+    /// Map a creator's deal to its dispute; each deal can only be disputed once per creator
+    fn record_deal(&mut self, creator: Address, deal_id: u64, dispute_id: U64) -> Result<(), ProtocolError> {
+        let mut deals = self.deal_disputes.setter(creator);
+        let mut entry = deals.setter(U64::from(deal_id));
+        if entry.get() != U64::ZERO {
+            return Err(ProtocolError::DisputeAlreadyExistsForDeal(DisputeAlreadyExistsForDeal {}));
+        }
+        entry.set(dispute_id);
+        Ok(())
+    }
+    
+    /// Append a newly registered judge to the enumerable pool
+    fn list_judge(&mut self, judge_address: Address) {
+        let count = self.judge_count.get();
+        self.judge_list.setter(count).set(judge_address);
+        self.judge_count.set(count + U256::from(1u64));
+    }
+    
+    /// Check a panel size against the cap and, in strict-majority mode, require it to be odd
+    fn validate_panel_size(&self, panel_size: u8) -> Result<(), ProtocolError> {
+        if panel_size > MAX_PANEL_SIZE || (self.strict_majority.get() && panel_size % 2 == 0) {
+            return Err(ProtocolError::InvalidPanelSize(InvalidPanelSize {}));
+        }
+        Ok(())
+    }
+    
+    /// Resolve the panel seat a caller votes for: the seat delegated to them, or their own
+    fn voting_seat(&self, dispute_id: u64, caller: Address) -> Result<Address, ProtocolError> {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        
+        // A delegation from a tied round no longer holds a seat
+        let delegator = dispute.delegated_by.get(caller);
+        if delegator != Address::ZERO && Self::panel_slot(&dispute, delegator).is_some() {
+            return Ok(delegator);
+        }
+        
+        // Judges who delegated their seat can no longer vote with it
+        if Self::panel_slot(&dispute, caller).is_none() || dispute.delegate_of.get(caller) != Address::ZERO {
+            return Err(ProtocolError::JudgeNotAllowedToVote(JudgeNotAllowedToVote {}));
+        }
+        
+        Ok(caller)
+    }
+    
+    /// Check a judge can take a panel seat: active, reputable enough and off cooldown
+    fn check_panel_eligibility(&self, judge_address: Address) -> Result<(), ProtocolError> {
+        let judge = self.judges.get(judge_address);
+        if !judge.active.get() {
+            return Err(ProtocolError::JudgeNotActive(JudgeNotActive {}));
+        }
+        
+        let min_reputation = i32::from_le_bytes(self.min_reputation.get().to_le_bytes());
+        if self.judge_standing(judge_address) < min_reputation {
+            return Err(ProtocolError::NotEnoughReputation(NotEnoughReputation {}));
+        }
+        
+        let now = U64::from(self.__stylus_host.block_timestamp());
+        let cooldown = self.judge_cooldown.get();
+        let last_assignment_time = judge.last_assignment_time.get();
+        if cooldown != U64::ZERO && last_assignment_time != U64::ZERO && now < last_assignment_time + cooldown {
+            return Err(ProtocolError::JudgeOnCooldown(JudgeOnCooldown {}));
+        }
+        
+        Ok(())
+    }
+    
+    /// Reputation from the oracle when one is set, falling back to the internal
+    /// counter if it's unset or the call fails
+    fn judge_standing(&self, judge_address: Address) -> i32 {
+        let internal = i32::from(i8::from_le_bytes(self.judges.get(judge_address).reputation.get().to_le_bytes()));
+        let oracle = self.reputation_oracle.get();
+        if oracle == Address::ZERO {
+            return internal;
+        }
+        
+        match IReputationOracle::new(oracle).get_reputation(self, judge_address) {
+            Ok(reputation) => i32::try_from(reputation).unwrap_or(if reputation.is_negative() { i32::MIN } else { i32::MAX }),
+            Err(_) => internal,
+        }
+    }
+    
+    /// Put a judge in panel slot `index`, snapshotting reputation, stake and reward
+    /// multiplier so later changes don't affect this dispute
+    fn seat_judge(&mut self, dispute_id: u64, index: U256, judge_address: Address) -> Result<(), ProtocolError> {
+        let bond = self.judge_bond.get();
+        if bond != U256::ZERO {
+            let received = self.pull_tokens(judge_address, bond)?;
+            let mut dispute = self.disputes.setter(U64::from(dispute_id));
+            dispute.bond_at_assignment.setter(index).set(received);
+            let bonds_locked = dispute.bonds_locked.get();
+            dispute.bonds_locked.set(bonds_locked + received);
+            self.add_escrow(dispute_id, received);
+        }
+        
+        let standing = self.judge_standing(judge_address);
+        let reputation = I32::from_le_bytes(standing.to_le_bytes());
+        let stake = self.judges.get(judge_address).stake.get();
+        let multiplier = self.reward_multiplier(standing);
+        let now = U64::from(self.__stylus_host.block_timestamp());
+        let mut judge = self.judges.setter(judge_address);
+        judge.last_assignment_time.set(now);
+        let open_seats = judge.open_seats.get();
+        judge.open_seats.set(open_seats + U64::from(1u64));
+        
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+        dispute.able_to_vote.setter(index).set(judge_address);
+        dispute.reputation_at_assignment.setter(index).set(reputation);
+        dispute.stake_at_assignment.setter(index).set(stake);
+        dispute.multiplier_at_assignment.setter(index).set(U16::from(multiplier));
+        dispute.panel_index.setter(judge_address).set(index + U256::from(1u64));
+        
+        Ok(())
+    }
+    
+    /// Settle the judge bond on panel slot `index`: `forfeit_bps` of it stays in escrow for
+    /// the caller to pay out and is returned, the rest is credited back to the judge
+    fn settle_seat_bond(&mut self, dispute_id: u64, index: U256, forfeit_bps: u16) -> U256 {
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+        let bond = dispute.bond_at_assignment.get(index);
+        if bond == U256::ZERO {
+            return U256::ZERO;
+        }
+        dispute.bond_at_assignment.setter(index).set(U256::ZERO);
+        if index < Self::round_base(&dispute) {
+            let carried_bonds = dispute.carried_bonds.get();
+            dispute.carried_bonds.set(carried_bonds - bond);
+        } else {
+            let bonds_locked = dispute.bonds_locked.get();
+            dispute.bonds_locked.set(bonds_locked - bond);
+        }
+        let judge_address = dispute.able_to_vote.get(index);
+        
+        let forfeited = bond * U256::from(forfeit_bps) / U256::from(BPS_DENOMINATOR);
+        let returned = bond - forfeited;
+        if returned != U256::ZERO {
+            let mut judge = self.judges.setter(judge_address);
+            let current_balance = judge.balance.get();
+            judge.balance.set(current_balance + returned);
+            let total_judge_rewards = self.total_judge_rewards.get();
+            self.total_judge_rewards.set(total_judge_rewards + returned);
+            self.release_escrow(dispute_id, returned);
+        }
+        
+        if forfeited != U256::ZERO {
+            log(&self.__stylus_host, JudgeBondForfeited {
+                dispute_id: U256::from(dispute_id),
+                judge: judge_address,
+                amount: forfeited,
+            });
+        }
+        
+        forfeited
+    }
+    
+    /// Return every judge bond still locked on a dispute and free its seats, tied rounds'
+    /// included (settlement, expiry, default judgment)
+    fn return_judge_bonds(&mut self, dispute_id: u64) {
+        let end = Self::panel_end(&self.disputes.get(U64::from(dispute_id)));
+        for i in 0..end.as_limbs()[0] {
+            let seat = self.disputes.get(U64::from(dispute_id)).able_to_vote.get(U256::from(i));
+            if seat == Address::ZERO {
+                continue;
+            }
+            self.settle_seat_bond(dispute_id, U256::from(i), 0);
+            self.release_seat(seat);
+        }
+    }
+    
+    /// Send the bonds of current-round seats that never committed to the treasury, before an
+    /// inquorate commit phase expires the dispute or a vote closes without a ruling (the seats
+    /// are freed as the other bonds are returned)
+    fn forfeit_silent_bonds(&mut self, dispute_id: u64) {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        let base = Self::round_base(&dispute);
+        let seats = dispute.able_to_vote_count.get();
+        for i in 0..seats.as_limbs()[0] {
+            let idx = base + U256::from(i);
+            let dispute = self.disputes.get(U64::from(dispute_id));
+            if Self::commit_slot(&dispute, dispute.able_to_vote.get(idx)).is_some() {
+                continue;
+            }
+            let forfeited = self.settle_seat_bond(dispute_id, idx, BPS_DENOMINATOR);
+            let current_contract_balance = self.contract_balance.get();
+            self.contract_balance.set(current_contract_balance + forfeited);
+            self.release_escrow(dispute_id, forfeited);
+        }
+    }
+    
+    /// Send the bonds of current-round seats that committed but never revealed to the treasury,
+    /// and take 1 reputation from each. Their bonds leave the committed total too, so the
+    /// reward pool only counts the silent seats' bonds.
+    fn forfeit_unrevealed_bonds(&mut self, dispute_id: u64) {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        let base = Self::round_base(&dispute);
+        let commits = dispute.commits_count.get();
+        for i in 0..commits.as_limbs()[0] {
+            let slot = base + U256::from(i);
+            let dispute = self.disputes.get(U64::from(dispute_id));
+            if dispute.vote_plain.get(slot) != U8::ZERO {
+                continue;
+            }
+            let seat = dispute.voters.get(slot);
+            let index = dispute.panel_index.get(seat) - U256::from(1u64);
+            let bond = dispute.bond_at_assignment.get(index);
+            let mut dispute = self.disputes.setter(U64::from(dispute_id));
+            let committed_bonds = dispute.committed_bonds.get();
+            dispute.committed_bonds.set(committed_bonds - bond);
+            
+            let forfeited = self.settle_seat_bond(dispute_id, index, BPS_DENOMINATOR);
+            let current_contract_balance = self.contract_balance.get();
+            self.contract_balance.set(current_contract_balance + forfeited);
+            self.release_escrow(dispute_id, forfeited);
+            
+            let mut judge = self.judges.setter(seat);
+            let current_rep = judge.reputation.get();
+            judge.reputation.set(current_rep.saturating_sub(I8::ONE));
+        }
+    }
+    
+    /// The reveal period in force; contracts upgraded from before it existed read 0
+    fn reveal_period_or_default(&self) -> U64 {
+        let reveal_period = self.reveal_period.get();
+        if reveal_period == U64::ZERO {
+            U64::from(DEFAULT_REVEAL_PERIOD)
+        } else {
+            reveal_period
+        }
+    }
+    
+    /// When a dispute's reveal phase ends. Panels filled before reveal deadlines existed
+    /// get the default reveal period after their commit deadline.
+    fn reveal_deadline_of(&self, dispute_id: u64) -> U64 {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        let reveal_deadline = dispute.reveal_deadline.get();
+        if reveal_deadline == U64::ZERO {
+            dispute.commit_deadline.get() + U64::from(DEFAULT_REVEAL_PERIOD)
+        } else {
+            reveal_deadline
+        }
+    }
+    
+    /// A judge's panel seat is done with, so it no longer locks their stake. Seats taken
+    /// before the count existed were never added, hence the saturating subtraction.
+    fn release_seat(&mut self, judge_address: Address) {
+        let mut judge = self.judges.setter(judge_address);
+        let open_seats = judge.open_seats.get();
+        judge.open_seats.set(open_seats.saturating_sub(U64::from(1u64)));
+    }
+    
+    /// Record a judge's commit, shared by direct and signed commits
+    fn record_commit(&mut self, dispute_id: u64, judge: Address, commit_hash: FixedBytes<32>) -> Result<(), ProtocolError> {
+        if self.judges.get(judge).judge_address.get() == Address::ZERO {
+            return Err(ProtocolError::NotAJudge(NotAJudge {}));
+        }
+        
+        let seat = self.voting_seat(dispute_id, judge)?;
+        
+        // Commits are only accepted during the commit phase; it ends once every seat
+        // committed, and no commit is accepted after the deadline
+        let phase = self.dispute_phase(dispute_id);
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+        let commits = dispute.commits_count.get();
+        let now = U64::from(self.__stylus_host.block_timestamp());
+        match phase {
+            PHASE_COMMIT if now >= dispute.commit_deadline.get() => {
+                return Err(ProtocolError::CommitPhaseEnded(CommitPhaseEnded {}));
+            }
+            PHASE_COMMIT => {}
+            PHASE_RESOLVED => return Err(ProtocolError::DisputeAlreadyResolved(DisputeAlreadyResolved {})),
+            PHASE_REVEAL if commits >= U256::from(dispute.required_votes.get()) => {
+                return Err(ProtocolError::AllVotesCommitted(AllVotesCommitted {}));
+            }
+            PHASE_REVEAL => return Err(ProtocolError::CommitPhaseEnded(CommitPhaseEnded {})),
+            _ => return Err(ProtocolError::DisputeNotOpen(DisputeNotOpen {})),
+        }
+        
+        if Self::commit_slot(&dispute, seat).is_some() {
+            return Err(ProtocolError::JudgeAlreadyVoted(JudgeAlreadyVoted {}));
+        }
+        
+        // A commitment revealed in an earlier round can't be replayed
+        if dispute.revealed_commits.get(commit_hash) {
+            return Err(ProtocolError::CommitmentAlreadyRevealed(CommitmentAlreadyRevealed {}));
+        }
+        
+        // Store commit under the panel seat
+        let new_commits = Self::checked_increment(commits)?;
+        let slot = Self::round_base(&dispute) + commits;
+        dispute.commit_index.setter(seat).set(slot + U256::from(1u64));
+        dispute.voters.setter(slot).set(seat);
+        dispute.vote_commits.setter(slot).set(commit_hash);
+        dispute.commits_count.set(new_commits);
+        
+        // A seat that commits keeps its bond safe from the non-reveal forfeit if an early
+        // majority closes the vote before it can reveal
+        let panel_seat = dispute.panel_index.get(seat) - U256::from(1u64);
+        let bond = dispute.bond_at_assignment.get(panel_seat);
+        let committed_bonds = dispute.committed_bonds.get();
+        dispute.committed_bonds.set(committed_bonds + bond);
+
+        Ok(())
+    }
+    
+    /// Add one to a panel counter, reverting instead of wrapping. Panels are capped at
+    /// MAX_PANEL_SIZE so this never trips in practice; it guards against a bug elsewhere.
+    fn checked_increment<const BITS: usize, const LIMBS: usize>(
+        value: Uint<BITS, LIMBS>,
+    ) -> Result<Uint<BITS, LIMBS>, ProtocolError> {
+        value
+            .checked_add(Uint::from(1u8))
+            .ok_or(ProtocolError::CounterOverflow(CounterOverflow {}))
+    }
+    
+    /// Commit preimage, packed like abi.encodePacked(uint64 dispute_id, address judge, uint8 vote, bytes secret)
+    fn commit_hash(dispute_id: u64, judge: Address, vote: u8, secret: &[u8]) -> FixedBytes<32> {
+        let mut preimage = Vec::with_capacity(8 + 20 + 1 + secret.len());
+        preimage.extend_from_slice(&dispute_id.to_be_bytes());
+        preimage.extend_from_slice(judge.as_slice());
+        preimage.push(vote);
+        preimage.extend_from_slice(secret);
+        keccak(preimage)
+    }
+    
+    /// EIP-712 domain separator, bound to this contract and chain
+    fn domain_separator_hash(&self) -> FixedBytes<32> {
+        let mut encoded = Vec::with_capacity(5 * 32);
+        encoded.extend_from_slice(keccak(EIP712_DOMAIN_TYPE).as_slice());
+        encoded.extend_from_slice(keccak(EIP712_NAME).as_slice());
+        encoded.extend_from_slice(keccak(EIP712_VERSION).as_slice());
+        encoded.extend_from_slice(&U256::from(self.__stylus_host.chain_id()).to_be_bytes::<32>());
+        encoded.extend_from_slice(self.__stylus_host.contract_address().into_word().as_slice());
+        keccak(encoded)
+    }
+    
+    /// EIP-712 digest a judge signs to authorize a relayed commit
+    fn commit_digest(&self, dispute_id: u64, commit_hash: FixedBytes<32>, nonce: U256, deadline: U256) -> FixedBytes<32> {
+        let mut encoded = Vec::with_capacity(5 * 32);
+        encoded.extend_from_slice(keccak(COMMIT_TYPE).as_slice());
+        encoded.extend_from_slice(&U256::from(dispute_id).to_be_bytes::<32>());
+        encoded.extend_from_slice(commit_hash.as_slice());
+        encoded.extend_from_slice(&nonce.to_be_bytes::<32>());
+        encoded.extend_from_slice(&deadline.to_be_bytes::<32>());
+        self.typed_data_digest(keccak(encoded))
+    }
+    
+    /// EIP-712 digest of a requester's consent to `sponsor` opening a dispute for them
+    fn sponsor_digest(&self, deal_id: u64, beneficiary: Address, category_id: u64, sponsor: Address, nonce: U256, deadline: U256) -> FixedBytes<32> {
+        let mut encoded = Vec::with_capacity(7 * 32);
+        encoded.extend_from_slice(keccak(SPONSOR_TYPE).as_slice());
+        encoded.extend_from_slice(&U256::from(deal_id).to_be_bytes::<32>());
+        encoded.extend_from_slice(beneficiary.into_word().as_slice());
+        encoded.extend_from_slice(&U256::from(category_id).to_be_bytes::<32>());
+        encoded.extend_from_slice(sponsor.into_word().as_slice());
+        encoded.extend_from_slice(&nonce.to_be_bytes::<32>());
+        encoded.extend_from_slice(&deadline.to_be_bytes::<32>());
+        self.typed_data_digest(keccak(encoded))
+    }
+    
+    /// Final EIP-712 digest of a struct hash under this contract's domain
+    fn typed_data_digest(&self, struct_hash: FixedBytes<32>) -> FixedBytes<32> {
+        let mut message = Vec::with_capacity(2 + 2 * 32);
+        message.extend_from_slice(&[0x19, 0x01]);
+        message.extend_from_slice(self.domain_separator_hash().as_slice());
+        message.extend_from_slice(struct_hash.as_slice());
+        keccak(message)
+    }
+    
+    /// Recover the signer of a 65-byte (r, s, v) signature through the ecrecover precompile
+    fn recover_signer(&self, digest: FixedBytes<32>, signature: &[u8]) -> Result<Address, ProtocolError> {
+        if signature.len() != 65 {
+            return Err(ProtocolError::InvalidSignature(InvalidSignature {}));
+        }
+        let v = match signature[64] {
+            0 | 1 => signature[64] + 27,
+            v => v,
+        };
+        
+        let mut input = Vec::with_capacity(4 * 32);
+        input.extend_from_slice(digest.as_slice());
+        input.extend_from_slice(&U256::from(v).to_be_bytes::<32>());
+        input.extend_from_slice(&signature[..64]);
+        
+        // The precompile returns nothing for signatures it can't recover
+        let output = self.__stylus_host.static_call(&self, ECRECOVER, &input)?;
+        if output.len() != 32 {
+            return Err(ProtocolError::InvalidSignature(InvalidSignature {}));
+        }
+        Ok(Address::from_slice(&output[12..]))
+    }
+    
+    /// Open a dispute outside a marketplace, pulling the fee from `payer`; shared by
+    /// direct and sponsored disputes. Returns the new dispute id.
+    fn open_direct_dispute(
+        &mut self,
+        payer: Address,
+        requester: Address,
+        deal_id: u64,
+        beneficiary: Address,
+        category_id: u64,
+    ) -> Result<u64, ProtocolError> {
+        self.when_not_paused()?;
+        self.record_deal(payer, deal_id, self.dispute_count.get())?;
+        self.open_dispute_record(payer, requester, deal_id, beneficiary, category_id)
+    }
+    
+    /// Open a dispute paid for by `payer` without claiming the deal id, which an appeal
+    /// shares with the dispute it appeals
+    fn open_dispute_record(
+        &mut self,
+        payer: Address,
+        requester: Address,
+        deal_id: u64,
+        beneficiary: Address,
+        category_id: u64,
+    ) -> Result<u64, ProtocolError> {
+        let (price, required_votes) = self.category_terms(category_id)?;
+        let now = self.__stylus_host.block_timestamp();
+        let stake_weighted = self.stake_weighted_rewards.get();
+        let judge_reward_bps = self.judge_reward_bps.get();
+        let judge_bond_slash_bps = self.judge_bond_slash_bps.get();
+        let (bond_amount, bond_deadline) = self.defense_bond_terms(now);
+        
+        let contract_addr = self.__stylus_host.contract_address();
+        
+        // Fee-on-transfer tokens deliver less than the category price; every later
+        // split and refund works from the amount actually received
+        let nominal_price = price;
+        let price = self.pull_tokens(payer, price)?;
+        
+        // Create dispute
+        let dispute_id = self.dispute_count.get();
+        let dispute_id_u64 = u64::from_le_bytes(dispute_id.to_le_bytes());
+        
+        // Dispute ids are never reused, so fields left out here are already zero
+        let mut dispute = self.disputes.setter(dispute_id);
+        dispute.deal_id.set(U64::from(deal_id));
+        if let Ok(legacy_deal_id) = u32::try_from(deal_id) {
+            dispute.dispute_id.set(U32::from(legacy_deal_id));
+        }
+        dispute.category_id.set(U64::from(category_id));
+        dispute.nominal_price.set(nominal_price);
+        dispute.price.set(price);
+        dispute.required_votes.set(required_votes);
+        dispute.created_at.set(U64::from(now));
+        dispute.round_started_at.set(U64::from(now));
+        dispute.stake_weighted.set(stake_weighted);
+        dispute.judge_reward_bps.set(judge_reward_bps);
+        dispute.judge_bond_slash_bps.set(judge_bond_slash_bps);
+        dispute.bond_amount.set(bond_amount);
+        dispute.bond_deadline.set(bond_deadline);
+        dispute.requester.set(requester);
+        dispute.beneficiary.set(beneficiary);
+        dispute.fee_payer.set(payer);
+        dispute.contract_address.set(contract_addr);
+        self.set_waiting_for_judges(dispute_id_u64, true);
+        
+        log(&self.__stylus_host, DisputeCreated {
+            dispute_id: U256::from(dispute_id_u64),
+            deal_id,
+            requester,
+            beneficiary,
+            contract_address: contract_addr,
+        });
+        
+        // Increment counter
+        let current_counter = self.dispute_count.get();
+        self.dispute_count.set(current_counter + U64::from(1));
+        self.add_escrow(dispute_id_u64, price);
+        let fees_collected = self.total_fees_collected.get();
+        self.total_fees_collected.set(fees_collected + price);
+        
+        Ok(dispute_id_u64)
+    }
+    
+    /// Refund the dispute fee to whoever paid it: the sponsor or requester for direct
+    /// disputes, or the originating marketplace
+    fn refund_fee(&mut self, dispute_id: u64) -> Result<(Address, U256), ProtocolError> {
+        let amount = self.disputes.get(U64::from(dispute_id)).price.get();
+        self.refund_fee_amount(dispute_id, amount)
+    }
+    
+    /// Refund part of the dispute fee to whoever paid it
+    fn refund_fee_amount(&mut self, dispute_id: u64, amount: U256) -> Result<(Address, U256), ProtocolError> {
+        let refund_to = self.fee_payer_of(dispute_id);
+        self.release_escrow(dispute_id, amount);
+        self.push_tokens(refund_to, amount)?;
+        
+        Ok((refund_to, amount))
+    }
+    
+    /// Who paid a dispute's fee: the sponsor, the requester of a direct dispute, or the marketplace
+    fn fee_payer_of(&self, dispute_id: u64) -> Address {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        let contract_address = dispute.contract_address.get();
+        let fee_payer = dispute.fee_payer.get();
+        if fee_payer != Address::ZERO {
+            fee_payer
+        } else if contract_address == self.__stylus_host.contract_address() {
+            dispute.requester.get()
+        } else {
+            contract_address
+        }
+    }
+    
+    /// Fee and escrow snapshot for an existing dispute
+    fn dispute_financials(&self, dispute_id: u64) -> Result<DisputeFinancials, ProtocolError> {
+        if dispute_id == 0 || U64::from(dispute_id) >= self.dispute_count.get() {
+            return Err(ProtocolError::DisputeDoesNotExist(DisputeDoesNotExist {}));
+        }
+        
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        let judge_reward_bps = u16::from_le_bytes(dispute.judge_reward_bps.get().to_le_bytes());
+        Ok(DisputeFinancials {
+            nominal_price: dispute.nominal_price.get(),
+            amount_received: dispute.price.get(),
+            fee_payer: self.fee_payer_of(dispute_id),
+            protocol_fee_bps: BPS_DENOMINATOR - judge_reward_bps,
+            judge_reward_bps,
+            escrow_remaining: dispute.escrow_amount.get(),
+        })
+    }
+    
+    /// Start another round on a fresh panel. The new round's seats and commits live in their
+    /// own block of slots, so nothing of the tied round is cleared: its judges stay barred
+    /// through their panel index, and their bonds are carried until the final panel settles.
+    /// Only the per-round counters and tallies are reset, so a tie costs the same for any panel size.
+    fn start_revote(&mut self, dispute_id: u64) {
+        let now = U64::from(self.__stylus_host.block_timestamp());
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+        
+        let carried_bonds = dispute.carried_bonds.get();
+        let bonds_locked = dispute.bonds_locked.get();
+        dispute.carried_bonds.set(carried_bonds + bonds_locked);
+        dispute.bonds_locked.set(U256::ZERO);
+        
+        for side in [U8::from(VOTE_FOR), U8::from(VOTE_AGAINST)] {
+            dispute.side_weight.setter(side).set(U256::ZERO);
+            dispute.side_stake.setter(side).set(U256::ZERO);
+            dispute.side_stake_weight.setter(side).set(U256::ZERO);
+            dispute.side_slash.setter(side).set(U256::ZERO);
+        }
+        
+        dispute.able_to_vote_count.set(U256::ZERO);
+        dispute.commits_count.set(U256::ZERO);
+        dispute.reveals_count.set(U256::ZERO);
+        dispute.committed_bonds.set(U256::ZERO);
+        dispute.votes_for.set(U8::ZERO);
+        dispute.votes_against.set(U8::ZERO);
+        dispute.votes_abstain.set(U8::ZERO);
+        dispute.commit_deadline.set(U64::ZERO);
+        dispute.reveal_deadline.set(U64::ZERO);
+        dispute.commit_phase_closed.set(false);
+        dispute.round_started_at.set(now);
+        dispute.is_open.set(false);
+        
+        let round = dispute.round.get() + U8::from(1u8);
+        dispute.round.set(round);
+        self.set_waiting_for_judges(dispute_id, true);
+        
+        log(&self.__stylus_host, DisputeRevote {
+            dispute_id: U256::from(dispute_id),
+            round: u8::from_le_bytes(round.to_le_bytes()),
+        });
+    }
+    
+    /// First panel and commit slot of the dispute's current round. Each round owns
+    /// MAX_PANEL_SIZE slots, so a re-vote never reuses the tied round's storage.
+    fn round_base(dispute: &Dispute) -> U256 {
+        U256::from(dispute.round.get()) * U256::from(MAX_PANEL_SIZE)
+    }
+    
+    /// One past the last seat slot used by any round so far
+    fn panel_end(dispute: &Dispute) -> U256 {
+        Self::round_base(dispute) + dispute.able_to_vote_count.get()
+    }
+    
+    /// A judge's panel slot in the current round, if seated in it
+    fn panel_slot(dispute: &Dispute, judge: Address) -> Option<U256> {
+        let panel_index = dispute.panel_index.get(judge);
+        (panel_index > Self::round_base(dispute)).then(|| panel_index - U256::from(1u64))
+    }
+    
+    /// A seat's commit slot in the current round, if it committed in it
+    fn commit_slot(dispute: &Dispute, seat: Address) -> Option<U256> {
+        let commit_index = dispute.commit_index.get(seat);
+        (commit_index > Self::round_base(dispute)).then(|| commit_index - U256::from(1u64))
+    }
+    
+    /// Only the marketplace contract that opened a dispute may relay proofs for its parties;
+    /// disputes opened on the protocol itself (direct or sponsored) have no relayer
+    fn check_relayer(&self, dispute_id: u64) -> Result<(), ProtocolError> {
+        let origin = self.disputes.get(U64::from(dispute_id)).contract_address.get();
+        if origin == self.__stylus_host.contract_address() || origin != self.__stylus_host.msg_sender() {
+            return Err(ProtocolError::NotOriginatingContract(NotOriginatingContract {}));
+        }
+        Ok(())
+    }
+    
+    /// Store a party's proof and log who submitted it (the party or its marketplace)
+    fn record_proof(&mut self, dispute_id: u64, party: Address, proof: String) -> Result<(), ProtocolError> {
+        if proof.is_empty() {
+            return Err(ProtocolError::ProofCannotBeEmpty(ProofCannotBeEmpty {}));
+        }
+        
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+        if dispute.resolved.get() || dispute.expired.get() {
+            return Err(ProtocolError::DisputeAlreadyResolved(DisputeAlreadyResolved {}));
+        }
+        dispute.proofs.setter(party).set_str(&proof);
+        
+        log(&self.__stylus_host, ProofSubmitted {
+            dispute_id: U256::from(dispute_id),
+            party,
+            submitted_by: self.__stylus_host.msg_sender(),
+            proof,
+        });
+        
+        Ok(())
+    }
+    
+    /// Move a dispute into its terminal state. Every resolution, expiry and void goes through
+    /// here so the status flags and the protocol metrics can't drift apart.
+    fn close_dispute(&mut self, dispute_id: u64, kind: u8) {
+        let now = U64::from(self.__stylus_host.block_timestamp());
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+        dispute.is_open.set(false);
+        dispute.pending_ack.set(false);
+        dispute.resolved_at.set(now);
+        dispute.resolution_kind.set(U8::from(kind));
+        
+        if kind == RESOLUTION_EXPIRED || kind == RESOLUTION_VOIDED {
+            dispute.expired.set(true);
+            let expired = self.disputes_expired.get();
+            self.disputes_expired.set(expired + U64::from(1));
+        } else {
+            dispute.resolved.set(true);
+            let resolved = self.disputes_resolved.get();
+            self.disputes_resolved.set(resolved + U64::from(1));
+        }
+        
+        self.set_waiting_for_judges(dispute_id, false);
+        
+        // An appeal closed without a ruling leaves the original one standing; a ruling is
+        // concluded once its rewards are set aside
+        if kind != RESOLUTION_RULING {
+            self.conclude_appeal(dispute_id);
+        }
+    }
+    
+    /// Close out an appeal, unlocking the original panel's settlement. If the appeal reversed
+    /// the ruling, the original losers' slash is undone and the rest of the original pool
+    /// joins the appeal panel's pool; settle_seats then inverts the original reputation changes.
+    /// The appeal itself is final, so its panel settles at once.
+    fn conclude_appeal(&mut self, appeal_id: u64) {
+        let appeal = self.disputes.get(U64::from(appeal_id));
+        let original_id = u64::from_le_bytes(appeal.appeal_of.get().to_le_bytes());
+        if original_id == 0 {
+            return;
+        }
+        let ruled = appeal.resolution_kind.get() == U8::from(RESOLUTION_RULING);
+        let appeal_requester_won = appeal.votes_for.get() > appeal.votes_against.get();
+        
+        let original = self.disputes.get(U64::from(original_id));
+        let requester_won = original.votes_for.get() > original.votes_against.get();
+        let overturned = ruled && appeal_requester_won != requester_won;
+        if overturned {
+            let losing_vote = if requester_won { U8::from(VOTE_AGAINST) } else { U8::from(VOTE_FOR) };
+            let redirected = original.reward_pool.get() - original.side_slash.get(losing_vote);
+            let mut original = self.disputes.setter(U64::from(original_id));
+            original.overturned.set(true);
+            original.reward_pool.set(U256::ZERO);
+            original.pending_rewards.set(U256::ZERO);
+            self.release_escrow(original_id, redirected);
+            self.add_escrow(appeal_id, redirected);
+            
+            let mut appeal = self.disputes.setter(U64::from(appeal_id));
+            let reward_pool = appeal.reward_pool.get();
+            appeal.reward_pool.set(reward_pool + redirected);
+            let pending_rewards = appeal.pending_rewards.get();
+            appeal.pending_rewards.set(pending_rewards + redirected);
+        }
+        
+        let now = U64::from(self.__stylus_host.block_timestamp());
+        self.disputes.setter(U64::from(original_id)).panel_unlock_at.set(now);
+        self.disputes.setter(U64::from(appeal_id)).panel_unlock_at.set(now);
+        
+        log(&self.__stylus_host, AppealConcluded {
+            dispute_id: U256::from(original_id),
+            appeal_id: U256::from(appeal_id),
+            overturned,
+        });
+    }
+    
+    /// Whether a judge sat on any round of a dispute, directly or as a delegate
+    fn sat_on_dispute(&self, dispute_id: u64, judge: Address) -> bool {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        dispute.served.get(judge)
+            || dispute.panel_index.get(judge) != U256::ZERO
+            || dispute.delegated_by.get(judge) != Address::ZERO
+    }
+    
+    /// Tally the revealed votes and close the dispute. This is the only place a vote outcome
+    /// is applied: the resolved flag is checked and set before any refund, payout or reputation
+    /// change, so a second call reverts instead of paying the panel twice. A tie before the
+    /// round cap starts a revote instead of closing.
+    fn resolve(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+        // The tally is too large to build into the reveal and settlement facets as well
+        if cfg!(any(feature = "reveal", feature = "settlement")) {
+            use alloy_sol_types::SolCall;
+            return self.call_facet(steps::resolveRoundCall { dispute_id }.abi_encode());
+        }
+        self.tally(dispute_id)
+    }
+    
+    /// Apply the outcome of a round's revealed votes (see resolve)
+    fn tally(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        if dispute.resolved.get() || dispute.expired.get() {
+            return Err(ProtocolError::DisputeAlreadyResolved(DisputeAlreadyResolved {}));
+        }
+        let votes_for = dispute.votes_for.get();
+        let votes_against = dispute.votes_against.get();
+        let requester = dispute.requester.get();
+        let beneficiary = dispute.beneficiary.get();
+        
+        // Without a ruling nobody is slashed for the side they took, but seats that never
+        // revealed still lose their bond (finalize_dispute already took the committed ones')
+        let tied = votes_for == votes_against && votes_for != U8::ZERO;
+        if tied || (votes_for == U8::ZERO && votes_against == U8::ZERO) {
+            self.forfeit_silent_bonds(dispute_id);
+        }
+        
+        // A tie goes to a fresh panel until the round cap is reached
+        let round = self.disputes.get(U64::from(dispute_id)).round.get();
+        if tied && round + U8::from(1u8) < U8::from(MAX_VOTE_ROUNDS) {
+            self.start_revote(dispute_id);
+            return Ok(());
+        }
+        
+        if tied || (votes_for == U8::ZERO && votes_against == U8::ZERO) {
+            // Everyone abstained or the last round tied - no ruling, refund the fee (and any defense bond)
+            // Judge bonds come back as the seats are settled, with no reward or reputation change
+            let now = U64::from(self.__stylus_host.block_timestamp());
+            let mut dispute = self.disputes.setter(U64::from(dispute_id));
+            dispute.no_ruling.set(true);
+            dispute.panel_unlock_at.set(now);
+            dispute.panel_pending.set(true);
+            self.close_dispute(dispute_id, RESOLUTION_NO_RULING);
+            let (refund_to, amount) = self.refund_fee(dispute_id)?;
+            self.refund_bond(dispute_id)?;
+            log(&self.__stylus_host, DisputeRefunded {
+                dispute_id: U256::from(dispute_id),
+                refunded_to: refund_to,
+                amount,
+            });
+            return Ok(());
+        }
+        
+        self.close_dispute(dispute_id, RESOLUTION_RULING);
+        let requester_won = votes_for > votes_against;
+        log(&self.__stylus_host, DisputeResolved {
+            dispute_id: U256::from(dispute_id),
+            winner: if requester_won { requester } else { beneficiary },
+            requester_won,
+            votes_for: u8::from_le_bytes(votes_for.to_le_bytes()),
+            votes_against: u8::from_le_bytes(votes_against.to_le_bytes()),
+        });
+        
+        self.distribute_rewards(dispute_id)?;
+        self.conclude_appeal(dispute_id);
+        Ok(())
+    }
+    
+    /// Whether a strict majority of the panel committed, enough to reveal without the rest
+    fn commit_set_quorate(&self, dispute_id: u64) -> bool {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        dispute.commits_count.get() * U256::from(2u64) > U256::from(dispute.required_votes.get())
+    }
+    
+    /// End a commit phase whose deadline passed: every seat that never committed loses 1
+    /// reputation, and a quorate commit set is frozen so reveals open. Returns whether it was
+    /// quorate; the caller expires the dispute if not.
+    fn freeze_commit_set(&mut self, dispute_id: u64) -> bool {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        let commits = dispute.commits_count.get();
+        let base = Self::round_base(&dispute);
+        let seats = dispute.able_to_vote_count.get();
+        let mut absent = Vec::new();
+        for i in 0..seats.as_limbs()[0] {
+            let seat = dispute.able_to_vote.get(base + U256::from(i));
+            if Self::commit_slot(&dispute, seat).is_none() {
+                absent.push(seat);
+            }
+        }
+        for seat in absent {
+            let mut judge = self.judges.setter(seat);
+            let current_rep = judge.reputation.get();
+            judge.reputation.set(current_rep.saturating_sub(I8::ONE));
+        }
+        
+        let quorate = self.commit_set_quorate(dispute_id);
+        log(&self.__stylus_host, CommitPhaseClosed {
+            dispute_id: U256::from(dispute_id),
+            commits,
+            quorate,
+        });
+        
+        if quorate {
+            self.disputes.setter(U64::from(dispute_id)).commit_phase_closed.set(true);
+        }
+        quorate
+    }
+    
+    /// Close a dispute without a ruling: refund the fee, the defense bond and judge bonds
+    fn expire(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+        self.close_dispute(dispute_id, RESOLUTION_EXPIRED);
+        
+        let (refund_to, amount) = self.refund_fee(dispute_id)?;
+        self.refund_bond(dispute_id)?;
+        self.return_judge_bonds(dispute_id);
+        
+        log(&self.__stylus_host, DisputeExpired {
+            dispute_id: U256::from(dispute_id),
+            refunded_to: refund_to,
+            amount,
+        });
+        
+        Ok(())
+    }
+    
+    /// Move a dispute in or out of the waiting-for-judges state. Every transition goes through
+    /// here so the open-for-registration index always matches the flag (swap-and-pop removal).
+    fn set_waiting_for_judges(&mut self, dispute_id: u64, waiting: bool) {
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+        dispute.waiting_for_judges.set(waiting);
+        let slot = dispute.open_for_registration_slot.get();
+        let count = self.open_for_registration_count.get();
+        
+        if waiting {
+            if slot == U256::ZERO {
+                self.open_for_registration.setter(count).set(U64::from(dispute_id));
+                self.open_for_registration_count.set(count + U256::from(1u64));
+                dispute.open_for_registration_slot.set(count + U256::from(1u64));
+            }
+            return;
+        }
+        
+        if slot == U256::ZERO {
+            return;
+        }
+        dispute.open_for_registration_slot.set(U256::ZERO);
+        
+        let last = count - U256::from(1u64);
+        let removed = slot - U256::from(1u64);
+        if removed != last {
+            let moved = self.open_for_registration.get(last);
+            self.open_for_registration.setter(removed).set(moved);
+            self.disputes.setter(moved).open_for_registration_slot.set(slot);
+        }
+        self.open_for_registration.setter(last).set(U64::ZERO);
+        self.open_for_registration_count.set(last);
+    }
+    
+    /// Split a settled dispute's fee: partial refund, compensation for committed judges, rest to the treasury
+    fn settle_fee(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        let price = dispute.price.get();
+        let refund = price * U256::from(SETTLEMENT_REFUND_BPS) / U256::from(BPS_DENOMINATOR);
+        let pool = price * U256::from(SETTLEMENT_COMPENSATION_BPS) / U256::from(BPS_DENOMINATOR);
+        
+        let base = Self::round_base(&dispute);
+        let mut payees: Vec<Address> = Vec::new();
+        for i in 0..dispute.commits_count.get().as_limbs()[0] {
+            let voter = dispute.voters.get(base + U256::from(i));
+            let delegate = dispute.delegate_of.get(voter);
+            payees.push(if delegate == Address::ZERO { voter } else { delegate });
+        }
+        
+        let mut paid = U256::ZERO;
+        if !payees.is_empty() {
+            let compensation = pool / U256::from(payees.len() as u64);
+            for payee in payees {
+                let mut payee_judge = self.judges.setter(payee);
+                let current_balance = payee_judge.balance.get();
+                payee_judge.balance.set(current_balance + compensation);
+                paid += compensation;
+            }
+            let total_judge_rewards = self.total_judge_rewards.get();
+            self.total_judge_rewards.set(total_judge_rewards + paid);
+            let rewards_paid = self.total_judge_rewards_paid.get();
+            self.total_judge_rewards_paid.set(rewards_paid + paid);
+        }
+        
+        let treasury = price - refund - paid;
+        let current_contract_balance = self.contract_balance.get();
+        self.contract_balance.set(current_contract_balance + treasury);
+        self.release_escrow(dispute_id, treasury + paid);
+        
+        self.refund_fee_amount(dispute_id, refund)?;
+        Ok(())
+    }
+    
+    /// Debit `amount` from a judge's balance and send it to them
+    fn withdraw_judge_balance(&mut self, judge: Address, amount: U256) -> Result<(), ProtocolError> {
+        let balance = self.judges.get(judge).balance.get();
+        if amount > balance {
+            return Err(ProtocolError::InsufficientBalance(InsufficientBalance {}));
+        }
+        
+        // The ledger is debited by the amount sent out, so a transfer fee only
+        // shrinks what the judge receives, never what the contract still owes
+        self.judges.setter(judge).balance.set(balance - amount);
+        let total_judge_rewards = self.total_judge_rewards.get();
+        self.total_judge_rewards.set(total_judge_rewards - amount);
+        
+        self.push_tokens(judge, amount)
+    }
+    
+    /// Backfill what v2 added for a dispute created under v1: the nominal price, the int32
+    /// reputation snapshots, the waiting-for-judges index, and the per-side reveal totals of a
+    /// vote still in progress
+    fn migrate_dispute_v1_to_v2(&mut self, dispute_id: u64) {
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+        if dispute.nominal_price.get() == U256::ZERO {
+            let price = dispute.price.get();
+            dispute.nominal_price.set(price);
+        }
+        
+        let seats = dispute.able_to_vote_count.get().as_limbs()[0];
+        for s in 0..seats {
+            let idx = U256::from(s);
+            let legacy = dispute.reputation_at_assignment_v1.get(idx);
+            if dispute.reputation_at_assignment.get(idx) == I32::ZERO && legacy != I8::ZERO {
+                let widened = i32::from(i8::from_le_bytes(legacy.to_le_bytes()));
+                dispute.reputation_at_assignment.setter(idx).set(I32::from_le_bytes(widened.to_le_bytes()));
+            }
+        }
+        
+        let open = !dispute.resolved.get() && !dispute.expired.get();
+        let waiting = dispute.waiting_for_judges.get();
+        let totals_missing = dispute.side_weight.get(U8::from(VOTE_FOR)) == U256::ZERO
+            && dispute.side_weight.get(U8::from(VOTE_AGAINST)) == U256::ZERO;
+        if open && totals_missing {
+            for c in 0..dispute.commits_count.get().as_limbs()[0] {
+                let idx = U256::from(c);
+                let vote = dispute.vote_plain.get(idx);
+                if vote != U8::from(VOTE_FOR) && vote != U8::from(VOTE_AGAINST) {
+                    continue;
+                }
+                let seat = dispute.voters.get(idx);
+                let panel_seat = dispute.panel_index.get(seat) - U256::from(1u64);
+                let stake = dispute.stake_at_assignment.get(panel_seat);
+                let multiplier = U256::from(Self::seat_multiplier(dispute.multiplier_at_assignment.get(panel_seat)));
+                let side_weight = dispute.side_weight.get(vote);
+                dispute.side_weight.setter(vote).set(side_weight + multiplier);
+                let side_stake = dispute.side_stake.get(vote);
+                dispute.side_stake.setter(vote).set(side_stake + stake);
+                let side_stake_weight = dispute.side_stake_weight.get(vote);
+                dispute.side_stake_weight.setter(vote).set(side_stake_weight + stake * multiplier);
+            }
+        }
+        
+        if open && waiting {
+            self.set_waiting_for_judges(dispute_id, true);
+        }
+    }
+    
+    /// Work out the OUTCOME_* of a dispute
+    fn dispute_outcome(&self, dispute_id: u64) -> u8 {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        
+        if dispute.expired.get() {
+            return OUTCOME_EXPIRED;
+        }
+        
+        if !dispute.resolved.get() {
+            return OUTCOME_PENDING;
+        }
+        
+        // votes_for means vote for requester/payer
+        // votes_against means vote for beneficiary/seller
+        let votes_for = dispute.votes_for.get();
+        let votes_against = dispute.votes_against.get();
+        
+        if dispute.no_ruling.get() {
+            return if votes_for == votes_against && votes_for != U8::ZERO {
+                OUTCOME_TIED
+            } else {
+                OUTCOME_NO_RULING
+            };
+        }
+        
+        let requester_wins = if dispute.defaulted.get() {
+            true
+        } else if dispute.settled.get() {
+            dispute.settlement_requester_wins.get()
+        } else {
+            (votes_for > votes_against) != dispute.overturned.get()
+        };
+        
+        if requester_wins {
+            OUTCOME_REQUESTER_WON
+        } else {
+            OUTCOME_BENEFICIARY_WON
+        }
+    }
+    
+    /// Pull fee tokens from `from` and return the amount that actually arrived
+    fn pull_tokens(&mut self, from: Address, amount: U256) -> Result<U256, ProtocolError> {
+        let contract_addr = self.__stylus_host.contract_address();
+        let token = IERC20::new(self.usdc_token.get());
+        let balance_before = token.balance_of(&*self, contract_addr)?;
+        if !token.transfer_from(&mut *self, from, contract_addr, amount)? {
+            return Err(ProtocolError::CallFailed(CallFailed {}));
+        }
+        let balance_after = token.balance_of(&*self, contract_addr)?;
+        
+        Ok(balance_after.saturating_sub(balance_before))
+    }
+    
+    /// Send fee tokens out of the contract
+    fn push_tokens(&mut self, to: Address, amount: U256) -> Result<(), ProtocolError> {
+        if amount == U256::ZERO {
+            return Ok(());
+        }
+        
+        let token = IERC20::new(self.usdc_token.get());
+        if !token.transfer(&mut *self, to, amount)? {
+            return Err(ProtocolError::CallFailed(CallFailed {}));
+        }
+        
+        Ok(())
+    }
+    
+    /// Track funds taken in for a dispute
+    fn add_escrow(&mut self, dispute_id: u64, amount: U256) {
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+        let escrow_amount = dispute.escrow_amount.get();
+        dispute.escrow_amount.set(escrow_amount + amount);
+        let total_escrowed = self.total_escrowed.get();
+        self.total_escrowed.set(total_escrowed + amount);
+    }
+    
+    /// Track funds leaving a dispute's escrow (paid out, refunded or moved to the treasury)
+    fn release_escrow(&mut self, dispute_id: u64, amount: U256) {
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+        let escrow_amount = dispute.escrow_amount.get();
+        dispute.escrow_amount.set(escrow_amount - amount);
+        let total_escrowed = self.total_escrowed.get();
+        self.total_escrowed.set(total_escrowed - amount);
+    }
+    
+    /// Return a posted defense bond to the beneficiary
+    fn refund_bond(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        if !dispute.bond_posted.get() {
+            return Ok(());
+        }
+        let amount = dispute.bond_amount.get();
+        let beneficiary = dispute.beneficiary.get();
+        self.release_escrow(dispute_id, amount);
+        self.push_tokens(beneficiary, amount)
+    }
+    
+    /// Snapshot the defense bond amount and deadline for a dispute created at `now`
+    fn defense_bond_terms(&self, now: u64) -> (U256, U64) {
+        if !self.defense_bond_required.get() {
+            return (U256::ZERO, U64::ZERO);
+        }
+        (
+            self.defense_bond_amount.get(),
+            U64::from(now) + self.defense_bond_window.get(),
+        )
+    }
+    
+    /// Pay the winning judges and update reputation once a dispute resolves
+    /// The judge pool is price * judge_reward_bps / 10000 regardless of the vote split, plus any
+    /// forfeited judge bonds; winners share it equally, or pro-rata to their stake at assignment
+    /// in stake-weighted mode.
+    /// The treasury keeps the rest plus rounding dust. With an appeal window set, rewards and
+    /// reputation changes are held until settle_original_panel releases them.
+    fn distribute_rewards(&mut self, dispute_id: u64) -> Result<(), ProtocolError> {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        let requester_wins = dispute.votes_for.get() > dispute.votes_against.get();
+        let (winning_vote, losing_vote) = if requester_wins {
+            (U8::from(VOTE_FOR), U8::from(VOTE_AGAINST))
+        } else {
+            (U8::from(VOTE_AGAINST), U8::from(VOTE_FOR))
+        };
+        
+        // Bonds forfeited by losing judges and those who never committed join the winners'
+        // pool; they stay in escrow until each seat is settled
+        let silent_bonds = dispute.bonds_locked.get() - dispute.committed_bonds.get();
+        let forfeited = silent_bonds + dispute.side_slash.get(losing_vote);
+        
+        // With a defense bond posted, the loser's payment funds the rewards
+        let bond_posted = dispute.bond_posted.get();
+        let price = if bond_posted && requester_wins {
+            dispute.bond_amount.get()
+        } else {
+            dispute.price.get()
+        };
+        let judges_share = price * U256::from(dispute.judge_reward_bps.get()) / U256::from(BPS_DENOMINATOR);
+        let pool = judges_share + forfeited;
+        
+        // Shares are proportional to tier multipliers (times stake in stake-weighted mode),
+        // so the total paid never exceeds the pool
+        let by_stake = dispute.stake_weighted.get() && dispute.side_stake.get(winning_vote) > U256::ZERO;
+        let total_weight = if by_stake {
+            dispute.side_stake_weight.get(winning_vote)
+        } else {
+            dispute.side_weight.get(winning_vote)
+        };
+        
+        // Rewards stay in escrow, and reputation unchanged, until the appeal window lapses
+        // and the seats are settled
+        let appeal_window = self.appeal_window.get();
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+        dispute.reward_pool.set(pool);
+        dispute.reward_weight.set(total_weight);
+        dispute.reward_by_stake.set(by_stake);
+        dispute.pending_rewards.set(pool);
+        dispute.panel_unlock_at.set(U64::from(self.__stylus_host.block_timestamp()) + appeal_window);
+        dispute.panel_pending.set(true);
+        
+        let current_contract_balance = self.contract_balance.get();
+        self.contract_balance.set(current_contract_balance + (price - judges_share));
+        self.release_escrow(dispute_id, price - judges_share);
+        
+        // ...and the winner's payment is refunded
+        if bond_posted {
+            if requester_wins {
+                self.refund_fee(dispute_id)?;
+            } else {
+                self.refund_bond(dispute_id)?;
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Seats assigned before reward tiers existed count at 100%
+    fn seat_multiplier(multiplier: U16) -> u16 {
+        if multiplier == U16::ZERO {
+            BPS_DENOMINATOR
+        } else {
+            u16::from_le_bytes(multiplier.to_le_bytes())
+        }
+    }
+    
+    /// Settle panel seats `start..start + count` of a closed vote. Winners, abstainers and
+    /// committed seats left unrevealed by an early majority get their bond back, losers forfeit
+    /// the slash share and judges who never committed all of it. Each winning
+    /// seat is credited its weighted share of the pool (to its delegate if delegated), and
+    /// reputation moves +1 for the majority, -1 for the minority. A no-ruling vote only
+    /// returns bonds. The seat that completes the panel sends the rounding remainder to the
+    /// treasury.
+    fn settle_seats(&mut self, dispute_id: u64, start: u64, count: u64) -> Result<(), ProtocolError> {
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        if !dispute.panel_pending.get() {
+            return Err(ProtocolError::NoPendingSettlement(NoPendingSettlement {}));
+        }
+        
+        if U64::from(self.__stylus_host.block_timestamp()) < dispute.panel_unlock_at.get() {
+            return Err(ProtocolError::SettlementLocked(SettlementLocked {}));
+        }
+        
+        // Tied rounds filled their panel before the tie, so every round but the last has a full one
+        let base = Self::round_base(&dispute);
+        let seats = U256::from(dispute.round.get()) * U256::from(dispute.required_votes.get()) + dispute.able_to_vote_count.get();
+        let end = start.saturating_add(count).min(Self::panel_end(&dispute).as_limbs()[0]);
+        let no_ruling = dispute.no_ruling.get();
+        let winning_vote = if dispute.votes_for.get() > dispute.votes_against.get() {
+            U8::from(VOTE_FOR)
+        } else {
+            U8::from(VOTE_AGAINST)
+        };
+        let slash_bps = u16::from_le_bytes(dispute.judge_bond_slash_bps.get().to_le_bytes());
+        let overturned = dispute.overturned.get();
+        let pool = dispute.reward_pool.get();
+        let total_weight = dispute.reward_weight.get();
+        let by_stake = dispute.reward_by_stake.get();
+        
+        let mut paid = U256::ZERO;
+        for i in start..end {
+            let idx = U256::from(i);
+            let mut dispute = self.disputes.setter(U64::from(dispute_id));
+            // Slots past a tied round's panel were never used
+            let seat = dispute.able_to_vote.get(idx);
+            if seat == Address::ZERO || dispute.seat_settled.get(idx) {
+                continue;
+            }
+            dispute.seat_settled.setter(idx).set(true);
+            let seats_settled = dispute.seats_settled.get();
+            dispute.seats_settled.set(seats_settled + U256::from(1u64));
+            
+            let commit_index = dispute.commit_index.get(seat);
+            let vote = if commit_index == U256::ZERO {
+                U8::ZERO
+            } else {
+                dispute.vote_plain.get(commit_index - U256::from(1u64))
+            };
+            let stake = dispute.stake_at_assignment.get(idx);
+            let multiplier = U256::from(Self::seat_multiplier(dispute.multiplier_at_assignment.get(idx)));
+            let delegate = dispute.delegate_of.get(seat);
+            let payee = if delegate == Address::ZERO { seat } else { delegate };
+            
+            // Unrevealed (0) and abstaining judges get neither reward nor reputation change.
+            // Only a seat that never committed loses its bond for silence: one that committed
+            // can only be unrevealed because an early majority closed the vote. Seats of a
+            // tied round just get their bond back.
+            let (forfeit_bps, won) = if idx < base || no_ruling || vote == U8::from(VOTE_ABSTAIN) {
+                (0, None)
+            } else if vote == U8::ZERO && commit_index == U256::ZERO {
+                (BPS_DENOMINATOR, None)
+            } else if vote == U8::ZERO {
+                (0, None)
+            } else if overturned {
+                // Reversed on appeal: nobody is slashed and the reputation changes flip
+                (0, Some(vote != winning_vote))
+            } else if vote == winning_vote {
+                (0, Some(true))
+            } else {
+                (slash_bps, Some(false))
+            };
+            
+            // A forfeited bond stays in escrow; the ruling already counted it into the pool
+            self.settle_seat_bond(dispute_id, idx, forfeit_bps);
+            
+            let mut reward = U256::ZERO;
+            if won == Some(true) && total_weight != U256::ZERO {
+                let weight = if by_stake { stake * multiplier } else { multiplier };
+                reward = pool * weight / total_weight;
+                if reward != U256::ZERO {
+                    let mut payee_judge = self.judges.setter(payee);
+                    let current_balance = payee_judge.balance.get();
+                    payee_judge.balance.set(current_balance + reward);
+                    paid += reward;
+                }
+            }
+            
+            self.release_seat(seat);
+            let mut judge = self.judges.setter(seat);
+            let current_rep = judge.reputation.get();
+            let new_rep = match won {
+                Some(true) => current_rep.saturating_add(I8::ONE),
+                Some(false) => current_rep.saturating_sub(I8::ONE),
+                None => current_rep,
+            };
+            judge.reputation.set(new_rep);
+            
+            // Each seat settles once, so each judge gets one entry per dispute
+            let entry_index = judge.history_count.get();
+            let mut entry = judge.history.setter(entry_index);
+            entry.dispute_id.set(U64::from(dispute_id));
+            entry.vote_revealed.set(vote != U8::ZERO);
+            entry.was_majority.set(won == Some(true));
+            entry.reputation_delta.set(new_rep - current_rep);
+            entry.reward_amount.set(reward);
+            judge.history_count.set(entry_index + U256::from(1u64));
+        }
+        
+        let total_judge_rewards = self.total_judge_rewards.get();
+        self.total_judge_rewards.set(total_judge_rewards + paid);
+        let rewards_paid = self.total_judge_rewards_paid.get();
+        self.total_judge_rewards_paid.set(rewards_paid + paid);
+        self.release_escrow(dispute_id, paid);
+        
+        let mut dispute = self.disputes.setter(U64::from(dispute_id));
+        let remaining = dispute.pending_rewards.get() - paid;
+        dispute.pending_rewards.set(remaining);
+        let seats_settled = dispute.seats_settled.get();
+        if seats_settled >= seats {
+            // Whatever the floor division left over goes to the treasury
+            dispute.pending_rewards.set(U256::ZERO);
+            dispute.panel_pending.set(false);
+            let current_contract_balance = self.contract_balance.get();
+            self.contract_balance.set(current_contract_balance + remaining);
+            self.release_escrow(dispute_id, remaining);
+        }
+        
+        log(&self.__stylus_host, JudgesSettled {
+            dispute_id: U256::from(dispute_id),
+            seats_settled,
+            paid,
+        });
+        
+        Ok(())
+    }
+    
+    /// Derive the current phase of a dispute from its flags, counters and deadlines
+    fn dispute_phase(&self, dispute_id: u64) -> u8 {
+        if dispute_id == 0 || U64::from(dispute_id) >= self.dispute_count.get() {
+            return PHASE_NOT_FOUND;
+        }
+        
+        let dispute = self.disputes.get(U64::from(dispute_id));
+        if dispute.resolved.get() {
+            return PHASE_RESOLVED;
+        }
+        if dispute.expired.get() {
+            return PHASE_EXPIRED;
+        }
+        if dispute.pending_ack.get() {
+            return PHASE_PENDING_ACK;
+        }
+        if dispute.waiting_for_judges.get() {
+            return PHASE_WAITING_FOR_JUDGES;
+        }
+        if !dispute.is_open.get() {
+            return PHASE_NOT_FOUND;
+        }
+        
+        // A commit phase past its deadline with seats missing stays in Commit (accepting no
+        // more commits) until the first reveal or close_commit_phase freezes it, or the
+        // dispute expires
+        let all_committed = dispute.commits_count.get() >= U256::from(dispute.required_votes.get());
+        if all_committed || dispute.commit_phase_closed.get() {
+            PHASE_REVEAL
+        } else {
+            PHASE_COMMIT
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
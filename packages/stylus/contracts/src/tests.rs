@@ -0,0 +1,2095 @@
+//! Unit tests, run natively. The Stylus hostio imports are provided below and backed by a
+//! thread-local chain: contract storage, a clock, the caller, an ERC20 fee token and a
+//! reputation oracle. A call that returns an error rolls storage and balances back, the way
+//! a revert would on chain.
+
+use super::*;
+use alloy_sol_types::{SolCall, SolError, SolValue};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use stylus_sdk::alloy_primitives::{B256, I256};
+
+const OWNER: Address = Address::repeat_byte(0x0A);
+const CONTRACT: Address = Address::repeat_byte(0xC0);
+const TOKEN: Address = Address::repeat_byte(0x70);
+const OTHER_TOKEN: Address = Address::repeat_byte(0x71);
+const ORACLE: Address = Address::repeat_byte(0x0E);
+const REQUESTER: Address = Address::repeat_byte(0x51);
+const BENEFICIARY: Address = Address::repeat_byte(0x52);
+const MARKETPLACE: Address = Address::repeat_byte(0x53);
+const STRANGER: Address = Address::repeat_byte(0x54);
+const START: u64 = 1_700_000_000;
+const PRICE: u64 = 50_000_000;
+const SECRET: &[u8] = b"sixteen byte secret";
+
+sol! {
+    interface MockToken {
+        function transferFrom(address from, address to, uint256 amount) external returns (bool);
+        function transfer(address to, uint256 amount) external returns (bool);
+        function balanceOf(address account) external view returns (uint256);
+        function allowance(address owner, address spender) external view returns (uint256);
+        function permit(address owner, address spender, uint256 value, uint256 deadline, uint8 v, bytes32 r, bytes32 s) external;
+    }
+
+    interface MockOracle {
+        function getReputation(address judge) external view returns (int256);
+    }
+}
+
+/// One ERC20 ledger; `fee_bps` of every transfer is burned, like a fee-on-transfer token
+#[derive(Clone, Default)]
+struct Ledger {
+    balances: HashMap<Address, U256>,
+    allowances: HashMap<(Address, Address), U256>,
+    fee_bps: u64,
+    permit_reverts: bool,
+}
+
+impl Ledger {
+    fn move_funds(&mut self, from: Address, to: Address, amount: U256) -> bool {
+        let balance = self.balances.get(&from).copied().unwrap_or_default();
+        if balance < amount {
+            return false;
+        }
+        let fee = amount * U256::from(self.fee_bps) / U256::from(10_000u64);
+        self.balances.insert(from, balance - amount);
+        *self.balances.entry(to).or_default() += amount - fee;
+        true
+    }
+}
+
+#[derive(Clone, Default)]
+struct Chain {
+    storage: HashMap<B256, B256>,
+    tokens: HashMap<Address, Ledger>,
+    reputations: HashMap<Address, I256>,
+    contracts: HashSet<Address>,
+    sender: Address,
+    now: u64,
+    return_data: Vec<u8>,
+    logs: Vec<B256>,
+}
+
+thread_local! {
+    static CHAIN: RefCell<Chain> = RefCell::new(Chain::default());
+}
+
+fn with_chain<T>(f: impl FnOnce(&mut Chain) -> T) -> T {
+    CHAIN.with(|chain| f(&mut chain.borrow_mut()))
+}
+
+fn read_address(ptr: *const u8) -> Address {
+    Address::from_slice(unsafe { std::slice::from_raw_parts(ptr, 20) })
+}
+
+fn write_bytes(ptr: *mut u8, bytes: &[u8]) {
+    unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len()) }
+}
+
+/// Run a call against a mocked contract; Err carries the revert data
+fn dispatch(caller: Address, to: Address, data: &[u8], chain: &mut Chain) -> Result<Vec<u8>, Vec<u8>> {
+    if to == ECRECOVER {
+        return Ok(ecrecover(data));
+    }
+    if to == ORACLE {
+        let call = MockOracle::getReputationCall::abi_decode(data, true).map_err(|_| Vec::new())?;
+        return match chain.reputations.get(&call.judge) {
+            Some(reputation) => Ok(reputation.abi_encode()),
+            None => Err(Vec::new()),
+        };
+    }
+
+    let ledger = chain.tokens.get_mut(&to).ok_or_else(Vec::new)?;
+    let selector: [u8; 4] = data.get(..4).ok_or_else(Vec::new)?.try_into().unwrap();
+    match selector {
+        MockToken::transferFromCall::SELECTOR => {
+            let call = MockToken::transferFromCall::abi_decode(data, true).map_err(|_| Vec::new())?;
+            let allowance = ledger.allowances.get(&(call.from, caller)).copied().unwrap_or_default();
+            if allowance < call.amount || !ledger.move_funds(call.from, call.to, call.amount) {
+                return Err(Vec::new());
+            }
+            ledger.allowances.insert((call.from, caller), allowance - call.amount);
+            Ok(true.abi_encode())
+        }
+        MockToken::transferCall::SELECTOR => {
+            let call = MockToken::transferCall::abi_decode(data, true).map_err(|_| Vec::new())?;
+            if !ledger.move_funds(caller, call.to, call.amount) {
+                return Err(Vec::new());
+            }
+            Ok(true.abi_encode())
+        }
+        MockToken::balanceOfCall::SELECTOR => {
+            let call = MockToken::balanceOfCall::abi_decode(data, true).map_err(|_| Vec::new())?;
+            Ok(ledger.balances.get(&call.account).copied().unwrap_or_default().abi_encode())
+        }
+        MockToken::allowanceCall::SELECTOR => {
+            let call = MockToken::allowanceCall::abi_decode(data, true).map_err(|_| Vec::new())?;
+            Ok(ledger.allowances.get(&(call.owner, call.spender)).copied().unwrap_or_default().abi_encode())
+        }
+        MockToken::permitCall::SELECTOR => {
+            let call = MockToken::permitCall::abi_decode(data, true).map_err(|_| Vec::new())?;
+            if ledger.permit_reverts {
+                return Err(Vec::new());
+            }
+            ledger.allowances.insert((call.owner, call.spender), call.value);
+            Ok(Vec::new())
+        }
+        _ => Err(Vec::new()),
+    }
+}
+
+/// The ecrecover precompile: (hash, v, r, s) in, the left-padded signer out, nothing if invalid
+fn ecrecover(input: &[u8]) -> Vec<u8> {
+    use ethers::types::{RecoveryMessage, Signature, H256, U256 as EthU256};
+    if input.len() != 128 {
+        return Vec::new();
+    }
+    let signature = Signature {
+        r: EthU256::from_big_endian(&input[64..96]),
+        s: EthU256::from_big_endian(&input[96..128]),
+        v: EthU256::from_big_endian(&input[32..64]).low_u64(),
+    };
+    match signature.recover(RecoveryMessage::Hash(H256::from_slice(&input[..32]))) {
+        Ok(signer) => Address::from(signer.0).into_word().to_vec(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn external_call(to: *const u8, data: *const u8, len: usize, return_data_len: *mut usize) -> u8 {
+    let to = read_address(to);
+    let data = unsafe { std::slice::from_raw_parts(data, len) }.to_vec();
+    with_chain(|chain| {
+        let (status, output) = match dispatch(CONTRACT, to, &data, chain) {
+            Ok(output) => (0, output),
+            Err(output) => (1, output),
+        };
+        unsafe { *return_data_len = output.len() };
+        chain.return_data = output;
+        status
+    })
+}
+
+// The hostio imports the SDK links against, in place of the Stylus VM
+
+#[no_mangle]
+pub unsafe extern "C" fn storage_load_bytes32(key: *const u8, dest: *mut u8) {
+    let key = B256::from_slice(std::slice::from_raw_parts(key, 32));
+    let value = with_chain(|chain| chain.storage.get(&key).copied().unwrap_or_default());
+    write_bytes(dest, value.as_slice());
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn storage_cache_bytes32(key: *const u8, value: *const u8) {
+    let key = B256::from_slice(std::slice::from_raw_parts(key, 32));
+    let value = B256::from_slice(std::slice::from_raw_parts(value, 32));
+    with_chain(|chain| chain.storage.insert(key, value));
+}
+
+#[no_mangle]
+pub extern "C" fn storage_flush_cache(_clear: bool) {}
+
+#[no_mangle]
+pub extern "C" fn block_timestamp() -> u64 {
+    with_chain(|chain| chain.now)
+}
+
+#[no_mangle]
+pub extern "C" fn block_number() -> u64 {
+    with_chain(|chain| chain.now / 12)
+}
+
+#[no_mangle]
+pub extern "C" fn chainid() -> u64 {
+    421_614
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn msg_sender(sender: *mut u8) {
+    write_bytes(sender, with_chain(|chain| chain.sender).as_slice());
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn tx_origin(origin: *mut u8) {
+    write_bytes(origin, with_chain(|chain| chain.sender).as_slice());
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn msg_value(value: *mut u8) {
+    write_bytes(value, &[0; 32]);
+}
+
+#[no_mangle]
+pub extern "C" fn msg_reentrant() -> bool {
+    false
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn contract_address(address: *mut u8) {
+    write_bytes(address, CONTRACT.as_slice());
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn account_code_size(address: *const u8) -> usize {
+    let address = read_address(address);
+    with_chain(|chain| if chain.contracts.contains(&address) { 100 } else { 0 })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn emit_log(data: *const u8, len: usize, topics: usize) {
+    if topics > 0 && len >= 32 {
+        let topic = B256::from_slice(std::slice::from_raw_parts(data, 32));
+        with_chain(|chain| chain.logs.push(topic));
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn call_contract(
+    contract: *const u8,
+    calldata: *const u8,
+    calldata_len: usize,
+    _value: *const u8,
+    _gas: u64,
+    return_data_len: *mut usize,
+) -> u8 {
+    external_call(contract, calldata, calldata_len, return_data_len)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn static_call_contract(
+    contract: *const u8,
+    calldata: *const u8,
+    calldata_len: usize,
+    _gas: u64,
+    return_data_len: *mut usize,
+) -> u8 {
+    external_call(contract, calldata, calldata_len, return_data_len)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn delegate_call_contract(
+    _contract: *const u8,
+    _calldata: *const u8,
+    _calldata_len: usize,
+    _gas: u64,
+    return_data_len: *mut usize,
+) -> u8 {
+    *return_data_len = 0;
+    1
+}
+
+#[no_mangle]
+pub extern "C" fn return_data_size() -> usize {
+    with_chain(|chain| chain.return_data.len())
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn read_return_data(dest: *mut u8, offset: usize, size: usize) -> usize {
+    with_chain(|chain| {
+        let start = offset.min(chain.return_data.len());
+        let end = offset.saturating_add(size).min(chain.return_data.len());
+        write_bytes(dest, &chain.return_data[start..end]);
+        end - start
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn native_keccak256(bytes: *const u8, len: usize, output: *mut u8) {
+    let hash = alloy_primitives::keccak256(std::slice::from_raw_parts(bytes, len));
+    write_bytes(output, hash.as_slice());
+}
+
+/// Hostios the contract never reaches; they only have to link
+macro_rules! unreachable_hostios {
+    ($(fn $name:ident($($arg:ident: $ty:ty),*) $(-> $ret:ty)?;)*) => {
+        $(
+            #[no_mangle]
+            pub extern "C" fn $name($(_: $ty),*) $(-> $ret)? {
+                unreachable!(concat!(stringify!($name), " is not mocked"))
+            }
+        )*
+    };
+}
+
+unreachable_hostios! {
+    fn account_balance(address: *const u8, dest: *mut u8);
+    fn account_code(address: *const u8, offset: usize, size: usize, dest: *mut u8) -> usize;
+    fn account_codehash(address: *const u8, dest: *mut u8);
+    fn block_basefee(basefee: *mut u8);
+    fn block_coinbase(coinbase: *mut u8);
+    fn block_gas_limit() -> u64;
+    fn create1(code: *const u8, code_len: usize, endowment: *const u8, contract: *mut u8, revert_data_len: *mut usize);
+    fn create2(code: *const u8, code_len: usize, endowment: *const u8, salt: *const u8, contract: *mut u8, revert_data_len: *mut usize);
+    fn evm_gas_left() -> u64;
+    fn evm_ink_left() -> u64;
+    fn pay_for_memory_grow(pages: u16);
+    fn read_args(dest: *mut u8);
+    fn tx_gas_price(gas_price: *mut u8);
+    fn tx_ink_price() -> u32;
+    fn write_result(data: *const u8, len: usize);
+}
+
+// ====================================
+//              HARNESS
+// ====================================
+
+fn judge(n: u8) -> Address {
+    Address::with_last_byte(n)
+}
+
+fn usdc(amount: u64) -> U256 {
+    U256::from(amount)
+}
+
+/// The deployed contract plus helpers that act as a given caller
+struct Protocol {
+    contract: ProtocolContract,
+}
+
+impl Protocol {
+    /// A fresh chain with the contract initialized by OWNER, TOKEN as the fee token and
+    /// the parties funded and approving the contract
+    fn new() -> Self {
+        CHAIN.with(|chain| {
+            *chain.borrow_mut() = Chain {
+                now: START,
+                ..Chain::default()
+            }
+        });
+        for token in [TOKEN, OTHER_TOKEN] {
+            with_chain(|chain| chain.tokens.insert(token, Ledger::default()));
+        }
+        let contract = unsafe {
+            <ProtocolContract as StorageType>::new(U256::ZERO, 0, stylus_sdk::host::VM(stylus_sdk::host::WasmVM {}))
+        };
+        let mut protocol = Protocol { contract };
+        protocol.ok(OWNER, |c| c.init(OWNER, TOKEN));
+        for party in [REQUESTER, BENEFICIARY, MARKETPLACE, STRANGER] {
+            protocol.fund(party, usdc(10_000 * PRICE));
+        }
+        protocol
+    }
+
+    /// Call as `sender`, rolling storage and balances back if the call fails
+    fn call<T>(
+        &mut self,
+        sender: Address,
+        f: impl FnOnce(&mut ProtocolContract) -> Result<T, ProtocolError>,
+    ) -> Result<T, ProtocolError> {
+        let snapshot = with_chain(|chain| {
+            chain.sender = sender;
+            (chain.storage.clone(), chain.tokens.clone())
+        });
+        let result = f(&mut self.contract);
+        if result.is_err() {
+            with_chain(|chain| (chain.storage, chain.tokens) = snapshot);
+        }
+        result
+    }
+
+    /// Call as `sender` and fail the test if the call reverts
+    fn ok<T>(&mut self, sender: Address, f: impl FnOnce(&mut ProtocolContract) -> Result<T, ProtocolError>) -> T {
+        match self.call(sender, f) {
+            Ok(value) => value,
+            Err(error) => panic!("call reverted: 0x{}", hex::encode(Vec::<u8>::from(error))),
+        }
+    }
+
+    fn view<T>(&self, f: impl FnOnce(&ProtocolContract) -> T) -> T {
+        f(&self.contract)
+    }
+
+    fn warp(&mut self, seconds: u64) {
+        with_chain(|chain| chain.now += seconds);
+    }
+
+    fn fund(&mut self, account: Address, amount: U256) {
+        self.fund_token(TOKEN, account, amount);
+    }
+
+    /// Mint `amount` of `token` to `account` and approve the contract to spend all of it
+    fn fund_token(&mut self, token: Address, account: Address, amount: U256) {
+        with_chain(|chain| {
+            let ledger = chain.tokens.get_mut(&token).unwrap();
+            *ledger.balances.entry(account).or_default() += amount;
+            ledger.allowances.insert((account, CONTRACT), U256::MAX);
+        });
+    }
+
+    fn approve(&mut self, owner: Address, amount: U256) {
+        with_chain(|chain| chain.tokens.get_mut(&TOKEN).unwrap().allowances.insert((owner, CONTRACT), amount));
+    }
+
+    fn balance(&self, account: Address) -> U256 {
+        self.token_balance(TOKEN, account)
+    }
+
+    fn token_balance(&self, token: Address, account: Address) -> U256 {
+        with_chain(|chain| chain.tokens[&token].balances.get(&account).copied().unwrap_or_default())
+    }
+
+    fn set_transfer_fee(&mut self, fee_bps: u64) {
+        with_chain(|chain| chain.tokens.get_mut(&TOKEN).unwrap().fee_bps = fee_bps);
+    }
+
+    fn mark_contract(&mut self, account: Address) {
+        with_chain(|chain| chain.contracts.insert(account));
+    }
+
+    fn logged<E: alloy_sol_types::SolEvent>(&self) -> usize {
+        with_chain(|chain| chain.logs.iter().filter(|topic| **topic == E::SIGNATURE_HASH).count())
+    }
+
+    /// Register judges 1..=n, funded and approving the contract for bonds and stakes
+    fn register_judges(&mut self, n: u8) {
+        for i in 1..=n {
+            self.fund(judge(i), usdc(100 * PRICE));
+            self.ok(judge(i), |c| c.register_as_judge());
+        }
+    }
+
+    /// Open a direct dispute from REQUESTER against BENEFICIARY and return its id
+    fn open_dispute(&mut self, deal_id: u64) -> u64 {
+        self.ok(REQUESTER, |c| c.create_dispute_direct(deal_id, BENEFICIARY, "proof".into(), 0));
+        self.view(|c| c.dispute_count()) - 1
+    }
+
+    /// Seat `judges` on the dispute's panel, in order
+    fn seat(&mut self, dispute_id: u64, judges: &[u8]) {
+        for &i in judges {
+            self.ok(judge(i), |c| c.register_to_vote(dispute_id));
+        }
+    }
+
+    fn commit(&mut self, dispute_id: u64, i: u8, vote: u8) {
+        let hash = ProtocolContract::commit_hash(dispute_id, judge(i), vote, SECRET);
+        self.ok(judge(i), |c| c.commit_vote(dispute_id, hash));
+    }
+
+    fn try_reveal(&mut self, dispute_id: u64, i: u8, vote: u8) -> Result<(), ProtocolError> {
+        self.call(judge(i), |c| c.reveal_votes(dispute_id, vote, SECRET.to_vec()))
+    }
+
+    fn reveal(&mut self, dispute_id: u64, i: u8, vote: u8) {
+        self.ok(judge(i), |c| c.reveal_votes(dispute_id, vote, SECRET.to_vec()))
+    }
+
+    /// Seat judges 1..=votes.len(), commit every vote and reveal them in order
+    fn vote(&mut self, dispute_id: u64, first: u8, votes: &[u8]) {
+        let judges: Vec<u8> = (first..first + votes.len() as u8).collect();
+        self.seat(dispute_id, &judges);
+        for (&i, &vote) in judges.iter().zip(votes) {
+            self.commit(dispute_id, i, vote);
+        }
+        for (&i, &vote) in judges.iter().zip(votes) {
+            if self.view(|c| c.get_dispute_phase(dispute_id)) != PHASE_REVEAL {
+                break;
+            }
+            self.reveal(dispute_id, i, vote);
+        }
+    }
+
+    fn phase(&self, dispute_id: u64) -> u8 {
+        self.view(|c| c.get_dispute_phase(dispute_id))
+    }
+
+    fn outcome(&self, dispute_id: u64) -> u8 {
+        self.view(|c| c.get_dispute_outcome(dispute_id))
+    }
+
+    /// Tokens the contract holds beyond what its ledgers owe
+    fn surplus(&self) -> I256 {
+        let held = self.balance(CONTRACT);
+        let owed = self.view(|c| {
+            c.contract_balance() + c.total_judge_rewards() + c.total_escrowed() + c.total_staked()
+        });
+        I256::from_raw(held) - I256::from_raw(owed)
+    }
+}
+
+macro_rules! assert_reverts {
+    ($result:expr, $error:ident) => {
+        match $result {
+            Err(ProtocolError::$error(_)) => {}
+            Err(error) => panic!(
+                "expected {}, reverted with 0x{}",
+                stringify!($error),
+                hex::encode(Vec::<u8>::from(error))
+            ),
+            Ok(_) => panic!("expected {}, call succeeded", stringify!($error)),
+        }
+    };
+}
+
+
+/// An EOA with a known key, for signed commits and sponsorship consents
+fn wallet(seed: u8) -> (ethers::signers::LocalWallet, Address) {
+    use ethers::signers::Signer;
+    let wallet = ethers::signers::LocalWallet::from_bytes(&[seed; 32]).unwrap();
+    let address = Address::from(wallet.address().0);
+    (wallet, address)
+}
+
+fn sign(wallet: &ethers::signers::LocalWallet, digest: FixedBytes<32>) -> Bytes {
+    let signature = wallet.sign_hash(ethers::types::H256(digest.0)).unwrap();
+    Bytes::from(signature.to_vec())
+}
+
+// ====================================
+//              TESTS
+// ====================================
+
+#[test]
+fn init_sets_defaults() {
+    let protocol = Protocol::new();
+    assert_eq!(protocol.view(|c| c.owner()), OWNER);
+    assert_eq!(protocol.view(|c| c.fee_token()), TOKEN);
+    assert_eq!(protocol.view(|c| c.dispute_price()), usdc(PRICE));
+    assert_eq!(protocol.view(|c| c.number_of_votes()), 5);
+    assert_eq!(protocol.view(|c| c.storage_version()), STORAGE_VERSION);
+}
+
+#[test]
+fn init_only_runs_once() {
+    let mut protocol = Protocol::new();
+    assert_reverts!(protocol.call(STRANGER, |c| c.init(STRANGER, OTHER_TOKEN)), AlreadyInitialized);
+    assert_eq!(protocol.view(|c| c.owner()), OWNER);
+    assert_eq!(protocol.view(|c| c.fee_token()), TOKEN);
+}
+
+#[test]
+fn init_rejects_a_zero_owner() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| {
+        c.owner.set(Address::ZERO);
+        c.storage_version.set(U32::ZERO);
+        Ok(())
+    });
+    assert_reverts!(protocol.call(STRANGER, |c| c.init(Address::ZERO, TOKEN)), InvalidAddress);
+}
+
+#[test]
+fn owner_functions_reject_other_callers() {
+    let mut protocol = Protocol::new();
+    assert_reverts!(protocol.call(STRANGER, |c| c.update_number_of_votes(3)), NotOwner);
+    assert_reverts!(protocol.call(STRANGER, |c| c.set_category(1, usdc(PRICE), 3)), NotOwner);
+    assert_reverts!(protocol.call(STRANGER, |c| c.queue_withdrawal()), NotOwner);
+    assert_reverts!(protocol.call(STRANGER, |c| c.migrate(1, 10)), NotOwner);
+}
+
+#[test]
+fn a_full_vote_pays_the_majority_and_keeps_the_ledgers_whole() {
+    let mut protocol = Protocol::new();
+    protocol.register_judges(5);
+    let id = protocol.open_dispute(1);
+    assert_eq!(protocol.balance(CONTRACT), usdc(PRICE));
+
+    protocol.vote(id, 1, &[VOTE_FOR, VOTE_FOR, VOTE_AGAINST, VOTE_AGAINST, VOTE_FOR]);
+    assert_eq!(protocol.phase(id), PHASE_RESOLVED);
+    assert_eq!(protocol.outcome(id), OUTCOME_REQUESTER_WON);
+    assert_eq!(protocol.view(|c| c.get_dispute_winner(id)).ok(), Some(true));
+
+    protocol.ok(STRANGER, |c| c.settle_original_panel(id));
+    // 80% of the fee split over three winners, the rounding dust to the treasury
+    for i in [1, 2, 5] {
+        assert_eq!(protocol.view(|c| c.judge_balance(judge(i))), usdc(13_333_333));
+        assert_eq!(protocol.view(|c| c.judge_reputation(judge(i))), 1);
+    }
+    for i in [3, 4] {
+        assert_eq!(protocol.view(|c| c.judge_balance(judge(i))), U256::ZERO);
+        assert_eq!(protocol.view(|c| c.judge_reputation(judge(i))), -1);
+    }
+    assert_eq!(protocol.view(|c| c.contract_balance()), usdc(10_000_001));
+    assert_eq!(protocol.view(|c| c.total_escrowed()), U256::ZERO);
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+// 1558
+#[test]
+fn revealed_votes_are_hidden_until_resolution() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.register_judges(3);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2, 3]);
+    for i in 1..=3 {
+        protocol.commit(id, i, if i == 3 { VOTE_AGAINST } else { VOTE_FOR });
+    }
+    protocol.reveal(id, 1, VOTE_FOR);
+    assert_reverts!(protocol.view(|c| c.get_revealed_votes(id)), DisputeNotResolvedYet);
+
+    protocol.reveal(id, 2, VOTE_FOR);
+    let votes = protocol.view(|c| c.get_revealed_votes(id)).unwrap_or_else(|_| panic!("view reverted"));
+    assert_eq!(votes, vec![
+        (judge(1), true, VOTE_FOR),
+        (judge(2), true, VOTE_FOR),
+        (judge(3), false, 0),
+    ]);
+}
+
+// 1560
+#[test]
+fn panel_keeps_the_reputation_judges_had_when_seated() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.import_judges(vec![judge(1)], vec![4]));
+    protocol.fund(judge(1), usdc(PRICE));
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1]);
+        assert_eq!(protocol.view(|c| c.get_dispute_panel(id)), vec![(judge(1), 4)]);
+}
+
+// 1561
+#[test]
+fn categories_set_the_price_and_panel_size() {
+    let mut protocol = Protocol::new();
+    assert_reverts!(protocol.call(OWNER, |c| c.set_category(0, usdc(PRICE), 3)), InvalidCategory);
+    protocol.ok(OWNER, |c| c.set_category(4, usdc(20_000_000), 3));
+    assert_eq!(protocol.view(|c| c.get_categories()), vec![(4, usdc(20_000_000), 3)]);
+
+    protocol.ok(REQUESTER, |c| c.create_dispute_direct(1, BENEFICIARY, "proof".into(), 4));
+    let info = protocol.view(|c| c.get_dispute_info(1));
+    assert_eq!((info.category_id, info.price, info.required_votes), (4, usdc(20_000_000), 3));
+    assert_reverts!(protocol.call(REQUESTER, |c| c.create_dispute_direct(2, BENEFICIARY, "proof".into(), 5)), CategoryDoesNotExist);
+}
+
+#[test]
+fn only_the_owner_routes_selectors_to_facets() {
+    let mut protocol = Protocol::new();
+    let facet = Address::repeat_byte(0xFA);
+    let selectors = vec![
+        FixedBytes(function_selector!("disputeCount")),
+        FixedBytes(function_selector!("getDispute", u64)),
+    ];
+    assert_reverts!(protocol.call(STRANGER, |c| c.set_facet(facet, selectors.clone())), NotOwner);
+
+    protocol.ok(OWNER, |c| c.set_facet(facet, selectors.clone()));
+    assert_eq!(protocol.view(|c| c.facet_address(selectors[1])), facet);
+    protocol.ok(OWNER, |c| c.set_facet(Address::ZERO, vec![selectors[0]]));
+    assert_eq!(protocol.view(|c| c.facet_address(selectors[0])), Address::ZERO);
+    assert_eq!(protocol.view(|c| c.facet_address(selectors[1])), facet);
+}
+
+#[test]
+fn unrouted_selectors_and_outside_step_calls_revert() {
+    let mut protocol = Protocol::new();
+    let calldata = function_selector!("disputeCount").to_vec();
+    let reverted = protocol.contract.facets.forward(&calldata).unwrap_err();
+    assert_eq!(reverted, UnknownSelector::SELECTOR.to_vec());
+
+    protocol.register_judges(1);
+    let id = protocol.open_dispute(1);
+    assert_reverts!(protocol.call(STRANGER, |c| c.resolve_round(id)), NotAFacetCall);
+}
+
+// 1562
+#[test]
+fn inactive_judges_cannot_join_panels() {
+    let mut protocol = Protocol::new();
+    protocol.register_judges(1);
+    let id = protocol.open_dispute(1);
+    protocol.ok(judge(1), |c| c.set_availability(false));
+    assert!(!protocol.view(|c| c.is_judge_active(judge(1))));
+    assert_reverts!(protocol.call(judge(1), |c| c.register_to_vote(id)), JudgeNotActive);
+    protocol.ok(judge(1), |c| c.set_availability(true));
+    protocol.seat(id, &[1]);
+}
+
+// 1563
+#[test]
+fn disputes_without_a_panel_expire_after_the_timeout() {
+    let mut protocol = Protocol::new();
+    let before = protocol.balance(REQUESTER);
+    let id = protocol.open_dispute(1);
+    assert_reverts!(protocol.call(STRANGER, |c| c.expire_dispute(id)), PanelTimeoutNotReached);
+
+    protocol.warp(DEFAULT_PANEL_TIMEOUT);
+    protocol.ok(STRANGER, |c| c.expire_dispute(id));
+    assert_eq!(protocol.phase(id), PHASE_EXPIRED);
+    assert!(protocol.view(|c| c.check_if_dispute_is_expired(id)));
+    assert_eq!(protocol.balance(REQUESTER), before);
+    assert_reverts!(protocol.call(STRANGER, |c| c.expire_dispute(id)), DisputeIsExpired);
+}
+
+// 1564
+#[test]
+fn commits_follow_the_dispute_lifecycle() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.register_judges(3);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1]);
+    let hash = ProtocolContract::commit_hash(id, judge(1), VOTE_FOR, SECRET);
+    assert_reverts!(protocol.call(judge(1), |c| c.commit_vote(id, hash)), DisputeNotOpen);
+
+    protocol.seat(id, &[2, 3]);
+    protocol.commit(id, 1, VOTE_FOR);
+    assert_reverts!(protocol.call(judge(1), |c| c.commit_vote(id, hash)), JudgeAlreadyVoted);
+    protocol.commit(id, 2, VOTE_FOR);
+    protocol.commit(id, 3, VOTE_FOR);
+    protocol.reveal(id, 1, VOTE_FOR);
+    protocol.reveal(id, 2, VOTE_FOR);
+    assert_reverts!(protocol.call(judge(3), |c| c.commit_vote(id, hash)), DisputeAlreadyResolved);
+}
+
+// 1565
+#[test]
+fn reveals_wait_for_the_commit_phase_to_end() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.register_judges(3);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2, 3]);
+    protocol.commit(id, 1, VOTE_FOR);
+    assert_eq!(protocol.phase(id), PHASE_COMMIT);
+    assert_reverts!(protocol.try_reveal(id, 1, VOTE_FOR), CommitPhaseNotEnded);
+    protocol.commit(id, 2, VOTE_FOR);
+    protocol.commit(id, 3, VOTE_FOR);
+    assert_eq!(protocol.phase(id), PHASE_REVEAL);
+    protocol.reveal(id, 1, VOTE_FOR);
+}
+
+// 1566
+#[test]
+fn a_vote_cannot_be_revealed_twice() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.register_judges(3);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2, 3]);
+    for i in 1..=3 {
+        protocol.commit(id, i, VOTE_FOR);
+    }
+    protocol.reveal(id, 1, VOTE_FOR);
+    assert_reverts!(protocol.try_reveal(id, 1, VOTE_FOR), JudgeAlreadyVoted);
+    assert_eq!(protocol.view(|c| c.get_dispute_votes(id)), (1, 0, 0));
+}
+
+// 1567
+#[test]
+fn stake_weighted_rewards_follow_the_stake_at_assignment() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.ok(OWNER, |c| c.set_stake_weighted_rewards(true));
+    protocol.register_judges(3);
+    protocol.ok(judge(1), |c| c.judge_stake(usdc(1_000_000)));
+    protocol.ok(judge(2), |c| c.judge_stake(usdc(3_000_000)));
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR, VOTE_FOR, VOTE_AGAINST]);
+
+    // Stakes are locked until the seat settles
+    assert_reverts!(protocol.call(judge(1), |c| c.judge_unstake(usdc(1_000_000))), StakeLocked);
+    protocol.ok(STRANGER, |c| c.settle_original_panel(id));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(1))), usdc(10_000_000));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(2))), usdc(30_000_000));
+    protocol.ok(judge(1), |c| c.judge_unstake(usdc(1_000_000)));
+    assert_eq!(protocol.view(|c| c.total_staked()), usdc(3_000_000));
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+// 1568
+#[test]
+fn an_all_abstain_panel_refunds_the_fee_without_a_ruling() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.register_judges(3);
+    let before = protocol.balance(REQUESTER);
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_ABSTAIN; 3]);
+    assert_eq!(protocol.outcome(id), OUTCOME_NO_RULING);
+    assert_eq!(protocol.view(|c| c.get_dispute_votes(id)), (0, 0, 3));
+    assert_eq!(protocol.balance(REQUESTER), before);
+    assert_reverts!(protocol.call(judge(1), |c| c.reveal_votes(id, 4, SECRET.to_vec())), InvalidVote);
+}
+
+// 1569
+#[test]
+fn dispute_info_is_returned_as_a_named_struct() {
+    let mut protocol = Protocol::new();
+    let id = protocol.open_dispute(42);
+    let info = protocol.view(|c| c.get_dispute_info(id));
+    assert_eq!(info.dispute_id, id);
+    assert_eq!(info.deal_id, 42);
+    assert_eq!(info.requester, REQUESTER);
+    assert_eq!(info.beneficiary, BENEFICIARY);
+    assert_eq!(info.contract_address, CONTRACT);
+    assert_eq!(info.phase, PHASE_WAITING_FOR_JUDGES);
+    assert_eq!(info.created_at, START);
+    assert!(info.waiting_for_judges);
+}
+
+// 1570
+#[test]
+fn progress_counters_track_seats_commits_and_reveals() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.register_judges(3);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2, 3]);
+    protocol.commit(id, 1, VOTE_FOR);
+    assert_eq!(protocol.view(|c| c.get_dispute_progress(id)), (U256::from(3), U256::from(1), U256::ZERO, 3));
+    assert_eq!(protocol.view(|c| c.get_dispute_progress(999)), (U256::ZERO, U256::ZERO, U256::ZERO, 0));
+}
+
+// 1571
+#[test]
+fn a_delegate_votes_the_seat_and_collects_its_reward() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.register_judges(4);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2, 3]);
+    protocol.ok(judge(1), |c| c.delegate_vote(id, judge(4)));
+    assert_eq!(protocol.view(|c| c.get_vote_delegate(id, judge(1))), judge(4));
+    assert_reverts!(protocol.call(judge(1), |c| c.commit_vote(id, FixedBytes::ZERO)), JudgeNotAllowedToVote);
+
+    // The delegate signs with the seat it votes for
+    let hash = ProtocolContract::commit_hash(id, judge(1), VOTE_FOR, SECRET);
+    protocol.ok(judge(4), |c| c.commit_vote(id, hash));
+    protocol.commit(id, 2, VOTE_FOR);
+    protocol.commit(id, 3, VOTE_AGAINST);
+    protocol.reveal(id, 4, VOTE_FOR);
+    protocol.reveal(id, 2, VOTE_FOR);
+    protocol.ok(STRANGER, |c| c.settle_original_panel(id));
+
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(4))), usdc(20_000_000));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(1))), U256::ZERO);
+    assert_eq!(protocol.view(|c| c.judge_reputation(judge(1))), 1);
+    assert_eq!(protocol.view(|c| c.judge_reputation(judge(4))), 0);
+}
+
+#[test]
+fn delegation_rejects_panel_members_and_self() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.register_judges(4);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2, 3]);
+    assert_reverts!(protocol.call(judge(1), |c| c.delegate_vote(id, judge(1))), InvalidDelegate);
+    assert_reverts!(protocol.call(judge(1), |c| c.delegate_vote(id, judge(2))), InvalidDelegate);
+    assert_reverts!(protocol.call(judge(1), |c| c.delegate_vote(id, STRANGER)), InvalidDelegate);
+    assert_reverts!(protocol.call(judge(4), |c| c.delegate_vote(id, judge(1))), JudgeNotAllowedToVote);
+    protocol.ok(judge(1), |c| c.delegate_vote(id, judge(4)));
+    assert_reverts!(protocol.call(judge(2), |c| c.delegate_vote(id, judge(4))), InvalidDelegate);
+    assert_reverts!(protocol.call(judge(1), |c| c.delegate_vote(id, judge(4))), SeatAlreadyDelegated);
+}
+
+// 1572
+#[test]
+fn a_required_defense_bond_gates_the_panel() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.set_defense_bond(true, usdc(PRICE), 86_400));
+    protocol.register_judges(1);
+    let id = protocol.open_dispute(1);
+    assert_reverts!(protocol.call(judge(1), |c| c.register_to_vote(id)), DefenseBondNotPosted);
+    assert_reverts!(protocol.call(STRANGER, |c| c.post_defense_bond(id)), NotTheBeneficiary);
+    protocol.ok(BENEFICIARY, |c| c.post_defense_bond(id));
+    assert_eq!(protocol.view(|c| c.get_defense_bond(id)), (usdc(PRICE), START + 86_400, true));
+    assert_eq!(protocol.view(|c| c.get_dispute_escrow(id)), usdc(2 * PRICE));
+    protocol.seat(id, &[1]);
+}
+
+#[test]
+fn an_unposted_defense_bond_ends_in_default_judgment() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.set_defense_bond(true, usdc(PRICE), 86_400));
+    let before = protocol.balance(REQUESTER);
+    let id = protocol.open_dispute(1);
+    assert_reverts!(protocol.call(STRANGER, |c| c.default_judgment(id)), BondWindowNotEnded);
+    protocol.warp(86_401);
+    assert_reverts!(protocol.call(BENEFICIARY, |c| c.post_defense_bond(id)), BondWindowClosed);
+    protocol.ok(STRANGER, |c| c.default_judgment(id));
+    assert_eq!(protocol.view(|c| c.get_dispute_winner(id)).ok(), Some(true));
+    assert_eq!(protocol.balance(REQUESTER), before);
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+// 1573
+#[test]
+fn judges_below_the_minimum_reputation_cannot_join() {
+    let mut protocol = Protocol::new();
+    protocol.register_judges(1);
+    protocol.ok(OWNER, |c| c.import_judges(vec![judge(2)], vec![2]));
+    protocol.ok(OWNER, |c| c.update_min_reputation(1));
+    assert_eq!(protocol.view(|c| c.min_reputation()), 1);
+    let id = protocol.open_dispute(1);
+    assert_reverts!(protocol.call(judge(1), |c| c.register_to_vote(id)), NotEnoughReputation);
+    protocol.seat(id, &[2]);
+}
+
+#[test]
+fn delegates_must_meet_the_panel_bar() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.import_judges(vec![judge(1), judge(2), judge(3)], vec![1, 1, 0]));
+    protocol.fund(judge(1), usdc(PRICE));
+    protocol.ok(OWNER, |c| c.update_min_reputation(1));
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1]);
+    assert_reverts!(protocol.call(judge(1), |c| c.delegate_vote(id, judge(3))), NotEnoughReputation);
+    protocol.ok(judge(2), |c| c.set_availability(false));
+    assert_reverts!(protocol.call(judge(1), |c| c.delegate_vote(id, judge(2))), JudgeNotActive);
+
+    protocol.ok(judge(2), |c| c.set_availability(true));
+    protocol.ok(OWNER, |c| c.update_judge_cooldown(3_600));
+    protocol.ok(judge(1), |c| c.delegate_vote(id, judge(2)));
+    let other = protocol.open_dispute(2);
+    assert_reverts!(protocol.call(judge(2), |c| c.register_to_vote(other)), JudgeOnCooldown);
+}
+
+// 1574
+#[test]
+fn the_judge_reward_share_is_snapshotted_per_dispute() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    assert_reverts!(protocol.call(OWNER, |c| c.update_judge_reward_bps(10_001)), InvalidBps);
+    protocol.ok(OWNER, |c| c.update_judge_reward_bps(5_000));
+    protocol.register_judges(3);
+    let id = protocol.open_dispute(1);
+    protocol.ok(OWNER, |c| c.update_judge_reward_bps(9_000));
+    protocol.vote(id, 1, &[VOTE_FOR, VOTE_FOR, VOTE_FOR]);
+    protocol.ok(STRANGER, |c| c.settle_original_panel(id));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(1))), usdc(12_500_000));
+    assert_eq!(protocol.view(|c| c.contract_balance()), usdc(25_000_000));
+}
+
+// 1575
+#[test]
+fn a_permit_pays_the_fee_in_the_same_call() {
+    let mut protocol = Protocol::new();
+    protocol.approve(REQUESTER, U256::ZERO);
+    protocol.ok(REQUESTER, |c| {
+        c.create_dispute_with_permit(1, BENEFICIARY, "proof".into(), 0, U256::MAX, 27, FixedBytes::ZERO, FixedBytes::ZERO)
+    });
+    assert_eq!(protocol.view(|c| c.get_dispute_escrow(1)), usdc(PRICE));
+}
+
+#[test]
+fn a_failed_permit_falls_back_to_the_existing_allowance() {
+    let mut protocol = Protocol::new();
+    with_chain(|chain| chain.tokens.get_mut(&TOKEN).unwrap().permit_reverts = true);
+    let permit = |c: &mut ProtocolContract, deal_id| {
+        c.create_dispute_with_permit(deal_id, BENEFICIARY, "proof".into(), 0, U256::MAX, 27, FixedBytes::ZERO, FixedBytes::ZERO)
+    };
+    // Someone already submitted the permit, so the allowance is in place
+    protocol.approve(REQUESTER, usdc(PRICE));
+    protocol.ok(REQUESTER, |c| permit(c, 1));
+    assert_eq!(protocol.view(|c| c.get_dispute_escrow(1)), usdc(PRICE));
+    assert_reverts!(protocol.call(REQUESTER, |c| permit(c, 2)), CallFailed);
+}
+
+// 1576
+#[test]
+fn judge_rewards_and_the_treasury_are_separate_ledgers() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.register_judges(3);
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR, VOTE_FOR, VOTE_FOR]);
+    protocol.ok(STRANGER, |c| c.settle_original_panel(id));
+    assert_eq!(protocol.view(|c| c.total_judge_rewards()), usdc(40_000_000));
+    assert_eq!(protocol.view(|c| c.contract_balance()), usdc(10_000_000));
+
+    // Only the treasury can be queued for the owner
+    protocol.ok(OWNER, |c| c.queue_withdrawal());
+    assert_eq!(protocol.view(|c| c.get_queued_withdrawal()).0, usdc(10_000_000));
+}
+
+// 1577
+#[test]
+fn a_relayer_submits_a_signed_commit() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(1));
+    let (signer, address) = wallet(7);
+    protocol.fund(address, usdc(PRICE));
+    protocol.ok(address, |c| c.register_as_judge());
+    let id = protocol.open_dispute(1);
+    protocol.ok(address, |c| c.register_to_vote(id));
+
+    let hash = ProtocolContract::commit_hash(id, address, VOTE_FOR, SECRET);
+    let deadline = U256::from(START + 60);
+    let digest = protocol.view(|c| c.commit_digest(id, hash, U256::ZERO, deadline));
+    let signature = sign(&signer, digest);
+    assert_reverts!(protocol.call(STRANGER, |c| c.commit_vote_by_sig(id, hash, judge(1), deadline, signature.clone())), InvalidSignature);
+    protocol.ok(STRANGER, |c| c.commit_vote_by_sig(id, hash, address, deadline, signature.clone()));
+    assert_eq!(protocol.view(|c| c.nonces(address)), U256::from(1));
+
+    // The nonce moved on, so the same signature can't be replayed
+    assert_reverts!(protocol.call(STRANGER, |c| c.commit_vote_by_sig(id, hash, address, deadline, signature.clone())), InvalidSignature);
+    protocol.warp(61);
+    assert_reverts!(protocol.call(STRANGER, |c| c.commit_vote_by_sig(id, hash, address, deadline, signature)), SignatureExpired);
+}
+
+// 1578
+#[test]
+fn only_registered_judges_can_join_a_panel() {
+    let mut protocol = Protocol::new();
+    let id = protocol.open_dispute(1);
+    assert_reverts!(protocol.call(STRANGER, |c| c.register_to_vote(id)), NotAJudge);
+}
+
+// 1579
+#[test]
+fn a_full_panel_takes_no_more_judges() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.register_judges(4);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2, 3]);
+    assert_reverts!(protocol.call(judge(4), |c| c.register_to_vote(id)), JudgesAlreadyAssigned);
+}
+
+// 1580
+#[test]
+fn the_cooldown_spaces_out_panel_assignments() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_judge_cooldown(3_600));
+    protocol.register_judges(1);
+    let first = protocol.open_dispute(1);
+    let second = protocol.open_dispute(2);
+    protocol.seat(first, &[1]);
+    assert_reverts!(protocol.call(judge(1), |c| c.register_to_vote(second)), JudgeOnCooldown);
+    protocol.warp(3_600);
+    protocol.seat(second, &[1]);
+}
+
+// 1581
+#[test]
+fn the_commit_hash_helper_matches_reveal() {
+    let protocol = Protocol::new();
+    let hash = protocol.view(|c| c.compute_commit_hash(3, judge(1), VOTE_AGAINST, SECRET.to_vec())).unwrap_or_else(|_| panic!("view reverted"));
+    let mut preimage = 3u64.to_be_bytes().to_vec();
+    preimage.extend_from_slice(judge(1).as_slice());
+    preimage.push(VOTE_AGAINST);
+    preimage.extend_from_slice(SECRET);
+    assert_eq!(hash, alloy_primitives::keccak256(preimage));
+    assert_reverts!(protocol.view(|c| c.compute_commit_hash(3, judge(1), VOTE_FOR, b"short".to_vec())), WeakSecret);
+}
+
+// 1582
+#[test]
+fn judge_introspection_views() {
+    let mut protocol = Protocol::new();
+    protocol.register_judges(1);
+    assert!(protocol.view(|c| c.is_judge(judge(1))));
+    assert!(!protocol.view(|c| c.is_judge(STRANGER)));
+    assert_reverts!(protocol.call(judge(1), |c| c.register_as_judge()), AlreadyRegistered);
+    let info = protocol.view(|c| c.get_judge_info(judge(1)));
+    assert_eq!(info.judge_address, judge(1));
+    assert!(info.active);
+    assert_eq!(protocol.view(|c| c.get_judge_info(STRANGER)).judge_address, Address::ZERO);
+    assert_eq!(protocol.view(|c| c.get_judge(judge(1))), (judge(1), U256::ZERO, 0));
+    assert_eq!(protocol.view(|c| c.get_judge(STRANGER)), (Address::ZERO, U256::ZERO, 0));
+}
+
+// 1583
+#[test]
+fn the_fee_token_only_changes_with_nothing_owed() {
+    let mut protocol = Protocol::new();
+    let id = protocol.open_dispute(1);
+    assert_reverts!(protocol.call(OWNER, |c| c.set_fee_token(OTHER_TOKEN)), OutstandingClaims);
+    assert_reverts!(protocol.call(OWNER, |c| c.set_fee_token(Address::ZERO)), InvalidAddress);
+    protocol.warp(DEFAULT_PANEL_TIMEOUT);
+    protocol.ok(STRANGER, |c| c.expire_dispute(id));
+    protocol.ok(OWNER, |c| c.set_fee_token(OTHER_TOKEN));
+    assert_eq!(protocol.view(|c| c.fee_token()), OTHER_TOKEN);
+}
+
+// 1584
+#[test]
+fn the_fee_token_stays_put_while_the_treasury_holds_funds() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(1));
+    protocol.register_judges(1);
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR]);
+    protocol.ok(STRANGER, |c| c.settle_original_panel(id));
+    protocol.ok(judge(1), |c| c.judge_withdraw());
+    assert_eq!(protocol.view(|c| c.contract_balance()), usdc(10_000_000));
+
+    // Switching now would leave the treasury in a token rescue_tokens no longer caps
+    assert_reverts!(protocol.call(OWNER, |c| c.set_fee_token(OTHER_TOKEN)), OutstandingClaims);
+    protocol.ok(OWNER, |c| c.queue_withdrawal());
+    assert_reverts!(protocol.call(OWNER, |c| c.set_fee_token(OTHER_TOKEN)), OutstandingClaims);
+    protocol.warp(MIN_WITHDRAW_DELAY);
+    protocol.ok(OWNER, |c| c.execute_withdrawal());
+    protocol.ok(OWNER, |c| c.set_fee_token(OTHER_TOKEN));
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+// 1584
+#[test]
+fn rescue_never_touches_what_the_contract_owes() {
+    let mut protocol = Protocol::new();
+    protocol.open_dispute(1);
+    protocol.fund_token(OTHER_TOKEN, CONTRACT, usdc(5));
+    protocol.fund(CONTRACT, usdc(3));
+    protocol.ok(OWNER, |c| c.rescue_tokens(OTHER_TOKEN, STRANGER, usdc(5)));
+    assert_eq!(protocol.token_balance(OTHER_TOKEN, STRANGER), usdc(5));
+    assert_reverts!(protocol.call(OWNER, |c| c.rescue_tokens(TOKEN, STRANGER, usdc(4))), RescueExceedsAvailable);
+    protocol.ok(OWNER, |c| c.rescue_tokens(TOKEN, STRANGER, usdc(3)));
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+// 1585
+#[test]
+fn parties_can_settle_before_any_reveal() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.register_judges(3);
+    let before = protocol.balance(REQUESTER);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2, 3]);
+    protocol.commit(id, 1, VOTE_FOR);
+    protocol.commit(id, 2, VOTE_FOR);
+
+    assert_reverts!(protocol.call(STRANGER, |c| c.settle_dispute(id, false)), NotAParty);
+    protocol.ok(REQUESTER, |c| c.settle_dispute(id, false));
+    assert_eq!(protocol.view(|c| c.get_settlement(id)), (REQUESTER, false, false));
+    protocol.ok(BENEFICIARY, |c| c.settle_dispute(id, false));
+    assert_eq!(protocol.outcome(id), OUTCOME_BENEFICIARY_WON);
+
+    // Half the fee back, 10% shared by the two judges who committed
+    assert_eq!(protocol.balance(REQUESTER), before - usdc(PRICE / 2));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(1))), usdc(2_500_000));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(3))), U256::ZERO);
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+#[test]
+fn settlement_is_blocked_once_a_vote_is_revealed() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.register_judges(3);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2, 3]);
+    for i in 1..=3 {
+        protocol.commit(id, i, VOTE_FOR);
+    }
+    protocol.reveal(id, 1, VOTE_FOR);
+    assert_reverts!(protocol.call(REQUESTER, |c| c.settle_dispute(id, true)), SettlementBlocked);
+}
+
+// 1586
+#[test]
+fn a_tie_goes_to_a_fresh_panel() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(4));
+    protocol.register_judges(8);
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR, VOTE_AGAINST, VOTE_FOR, VOTE_AGAINST]);
+    assert_eq!(protocol.view(|c| c.get_dispute_round(id)), 1);
+    assert_eq!(protocol.phase(id), PHASE_WAITING_FOR_JUDGES);
+    assert_eq!(protocol.view(|c| c.get_dispute_progress(id)), (U256::ZERO, U256::ZERO, U256::ZERO, 4));
+    assert_reverts!(protocol.call(judge(1), |c| c.register_to_vote(id)), JudgeAlreadyServed);
+
+    protocol.vote(id, 5, &[VOTE_AGAINST, VOTE_AGAINST, VOTE_AGAINST, VOTE_FOR]);
+    assert_eq!(protocol.outcome(id), OUTCOME_BENEFICIARY_WON);
+    assert_eq!(protocol.view(|c| c.get_judge_history_length(judge(1))), U256::ZERO);
+}
+
+#[test]
+fn a_tied_round_judge_cannot_return_as_a_delegate() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(2));
+    protocol.register_judges(4);
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR, VOTE_AGAINST]);
+    protocol.seat(id, &[3, 4]);
+    assert_reverts!(protocol.call(judge(3), |c| c.delegate_vote(id, judge(1))), JudgeAlreadyServed);
+}
+
+#[test]
+fn a_tie_in_the_last_round_ends_without_a_ruling() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(2));
+    protocol.register_judges(4);
+    let before = protocol.balance(REQUESTER);
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR, VOTE_AGAINST]);
+    protocol.vote(id, 3, &[VOTE_FOR, VOTE_AGAINST]);
+    assert_eq!(protocol.outcome(id), OUTCOME_TIED);
+    assert_reverts!(protocol.view(|c| c.get_dispute_winner(id)), DisputeTied);
+    assert_eq!(protocol.balance(REQUESTER), before);
+}
+
+// 1587
+#[test]
+fn judges_are_imported_until_the_migration_is_locked() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.import_judges(vec![judge(1), judge(2)], vec![5, -3]));
+    assert_eq!(protocol.view(|c| c.judge_reputation(judge(2))), -3);
+    assert_reverts!(protocol.call(OWNER, |c| c.import_judges(vec![judge(1)], vec![1])), AlreadyRegistered);
+    assert_reverts!(protocol.call(OWNER, |c| c.import_judges(vec![judge(3)], vec![1, 2])), LengthMismatch);
+    assert_reverts!(protocol.call(OWNER, |c| c.import_judges(vec![judge(3)], vec![200])), ReputationOutOfRange);
+    protocol.ok(OWNER, |c| c.lock_migration());
+    assert_reverts!(protocol.call(OWNER, |c| c.import_judges(vec![judge(3)], vec![1])), MigrationIsLocked);
+}
+
+// 1589
+#[test]
+fn open_disputes_keep_their_panel_size() {
+    let mut protocol = Protocol::new();
+    let id = protocol.open_dispute(1);
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    assert_eq!(protocol.view(|c| c.get_dispute_progress(id)).3, 5);
+    assert_reverts!(protocol.call(OWNER, |c| c.update_number_of_votes(0)), MustBeGreaterThanZero);
+}
+
+// 1590
+#[test]
+fn the_judge_pool_is_enumerable() {
+    let mut protocol = Protocol::new();
+    protocol.register_judges(3);
+    assert_eq!(protocol.view(|c| c.judge_count()), U256::from(3));
+    assert_eq!(protocol.view(|c| c.get_judges(U256::from(1), U256::from(5))), vec![judge(2), judge(3)]);
+}
+
+// 1591
+#[test]
+fn reward_tiers_weight_reputable_judges() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    assert_reverts!(protocol.call(OWNER, |c| c.set_reward_tiers(vec![2, 1], vec![1, 1])), InvalidRewardTiers);
+    protocol.ok(OWNER, |c| c.set_reward_tiers(vec![1], vec![20_000]));
+    protocol.ok(OWNER, |c| c.import_judges(vec![judge(1)], vec![1]));
+    protocol.fund(judge(1), usdc(PRICE));
+    for i in 2..=3 {
+        protocol.fund(judge(i), usdc(PRICE));
+        protocol.ok(judge(i), |c| c.register_as_judge());
+    }
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR, VOTE_FOR, VOTE_AGAINST]);
+    protocol.ok(STRANGER, |c| c.settle_original_panel(id));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(1))), usdc(26_666_666));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(2))), usdc(13_333_333));
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+// 1592
+#[test]
+fn deals_map_to_a_single_dispute() {
+    let mut protocol = Protocol::new();
+    let id = protocol.open_dispute(77);
+    assert_eq!(protocol.view(|c| c.get_dispute_id_for_deal(REQUESTER, 77)), id);
+    assert_eq!(protocol.view(|c| c.get_dispute_id_for_deal(STRANGER, 77)), 0);
+    assert_reverts!(protocol.call(REQUESTER, |c| c.create_dispute_direct(77, BENEFICIARY, "proof".into(), 0)), DisputeAlreadyExistsForDeal);
+    protocol.ok(STRANGER, |c| c.create_dispute_direct(77, BENEFICIARY, "proof".into(), 0));
+}
+
+// 1593
+#[test]
+fn resolution_time_and_kind_are_recorded() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(1));
+    protocol.register_judges(1);
+    let id = protocol.open_dispute(1);
+    protocol.warp(100);
+    protocol.vote(id, 1, &[VOTE_AGAINST]);
+    let info = protocol.view(|c| c.get_dispute_info(id));
+    assert_eq!(info.resolved_at, START + 100);
+    assert_eq!(info.resolution_kind, RESOLUTION_RULING);
+}
+
+// 1594
+#[test]
+fn treasury_withdrawals_wait_out_the_timelock() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(1));
+    protocol.register_judges(1);
+    assert_reverts!(protocol.call(OWNER, |c| c.queue_withdrawal()), NoUSDCToWithdraw);
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR]);
+    assert_reverts!(protocol.call(OWNER, |c| c.update_withdraw_delay(3_600)), DelayTooShort);
+
+    protocol.ok(OWNER, |c| c.queue_withdrawal());
+    assert_reverts!(protocol.call(OWNER, |c| c.queue_withdrawal()), WithdrawalAlreadyQueued);
+    assert_reverts!(protocol.call(OWNER, |c| c.execute_withdrawal()), WithdrawalLocked);
+    protocol.warp(MIN_WITHDRAW_DELAY);
+    protocol.ok(OWNER, |c| c.execute_withdrawal());
+    assert_eq!(protocol.balance(OWNER), usdc(10_000_000));
+    assert_eq!(protocol.view(|c| c.contract_balance()), U256::ZERO);
+}
+
+// 1595
+#[test]
+fn a_strict_majority_resolves_without_waiting_for_the_rest() {
+    let mut protocol = Protocol::new();
+    protocol.register_judges(5);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2, 3, 4, 5]);
+    for i in 1..=5 {
+        protocol.commit(id, i, VOTE_AGAINST);
+    }
+    protocol.reveal(id, 1, VOTE_AGAINST);
+    protocol.reveal(id, 2, VOTE_AGAINST);
+    assert_eq!(protocol.phase(id), PHASE_REVEAL);
+    protocol.reveal(id, 3, VOTE_AGAINST);
+    assert_eq!(protocol.outcome(id), OUTCOME_BENEFICIARY_WON);
+}
+
+#[test]
+fn a_stuck_reveal_phase_is_finalized_on_the_revealed_votes() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.ok(OWNER, |c| c.set_judge_bond(usdc(10_000_000), 5_000));
+    protocol.register_judges(3);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2, 3]);
+    protocol.commit(id, 1, VOTE_FOR);
+    protocol.commit(id, 2, VOTE_FOR);
+    protocol.commit(id, 3, VOTE_AGAINST);
+    protocol.reveal(id, 1, VOTE_FOR);
+    assert_reverts!(protocol.call(STRANGER, |c| c.finalize_dispute(id)), RevealPhaseNotEnded);
+
+    let reveal_deadline = protocol.view(|c| c.get_dispute_deadlines(id)).3;
+    protocol.warp(reveal_deadline - START);
+    protocol.ok(STRANGER, |c| c.finalize_dispute(id));
+    assert_eq!(protocol.outcome(id), OUTCOME_REQUESTER_WON);
+    assert_eq!(protocol.logged::<JudgeBondForfeited>(), 2);
+    assert_eq!(protocol.view(|c| c.get_judge(judge(2))).2, -1);
+    assert_reverts!(protocol.try_reveal(id, 2, VOTE_FOR), DisputeAlreadyResolved);
+
+    // The one judge who revealed takes the whole judge share; the silent bonds fund the treasury
+    protocol.ok(STRANGER, |c| c.settle_original_panel(id));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(1))), usdc(50_000_000));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(3))), U256::ZERO);
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+// 1596
+#[test]
+fn fee_on_transfer_tokens_escrow_what_arrived() {
+    let mut protocol = Protocol::new();
+    protocol.set_transfer_fee(100);
+    let id = protocol.open_dispute(1);
+    let financials = protocol.view(|c| c.get_dispute_financials(id)).unwrap_or_else(|_| panic!("view reverted"));
+    assert_eq!(financials.nominal_price, usdc(PRICE));
+    assert_eq!(financials.amount_received, usdc(49_500_000));
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+// 1597
+#[test]
+fn the_winner_view_distinguishes_closures_without_a_ruling() {
+    let mut protocol = Protocol::new();
+    let id = protocol.open_dispute(1);
+    assert_reverts!(protocol.view(|c| c.get_dispute_winner(id)), DisputeNotResolvedYet);
+    protocol.warp(DEFAULT_PANEL_TIMEOUT);
+    protocol.ok(STRANGER, |c| c.expire_dispute(id));
+    assert_eq!(protocol.outcome(id), OUTCOME_EXPIRED);
+    assert_reverts!(protocol.view(|c| c.get_dispute_winner(id)), DisputeIsExpired);
+}
+
+#[test]
+fn a_panel_that_never_reveals_expires_with_the_fee_refunded() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(2));
+    protocol.register_judges(2);
+    let before = protocol.balance(REQUESTER);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2]);
+    protocol.commit(id, 1, VOTE_FOR);
+    protocol.commit(id, 2, VOTE_AGAINST);
+    protocol.warp(protocol.view(|c| c.get_dispute_deadlines(id)).3 - START);
+    protocol.ok(STRANGER, |c| c.finalize_dispute(id));
+    assert_eq!(protocol.outcome(id), OUTCOME_EXPIRED);
+    assert_reverts!(protocol.view(|c| c.get_dispute_winner(id)), DisputeIsExpired);
+    assert_eq!(protocol.balance(REQUESTER), before);
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+// 1598
+#[test]
+fn lifecycle_events_are_emitted() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(1));
+    protocol.register_judges(1);
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR]);
+    assert_eq!(protocol.logged::<DisputeCreated>(), 1);
+    assert_eq!(protocol.logged::<DisputeOpened>(), 1);
+    assert_eq!(protocol.logged::<DisputeResolved>(), 1);
+}
+
+// 1599
+#[test]
+fn judges_withdraw_part_of_their_balance() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(1));
+    protocol.register_judges(1);
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR]);
+    protocol.ok(STRANGER, |c| c.settle_original_panel(id));
+    let before = protocol.balance(judge(1));
+
+    assert_reverts!(protocol.call(judge(1), |c| c.judge_withdraw_amount(usdc(40_000_001))), InsufficientBalance);
+    protocol.ok(judge(1), |c| c.judge_withdraw_amount(usdc(15_000_000)));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(1))), usdc(25_000_000));
+    protocol.ok(judge(1), |c| c.judge_withdraw());
+    assert_eq!(protocol.balance(judge(1)), before + usdc(40_000_000));
+    assert_reverts!(protocol.call(judge(1), |c| c.judge_withdraw()), NoBalanceToWithdraw);
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+// 1600
+#[test]
+fn each_dispute_tracks_its_escrow() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(1));
+    protocol.register_judges(1);
+    let id = protocol.open_dispute(1);
+    protocol.open_dispute(2);
+    assert_eq!(protocol.view(|c| c.get_dispute_escrow(id)), usdc(PRICE));
+    assert_eq!(protocol.view(|c| c.total_escrowed()), usdc(2 * PRICE));
+    protocol.vote(id, 1, &[VOTE_FOR]);
+    protocol.ok(STRANGER, |c| c.settle_original_panel(id));
+    assert_eq!(protocol.view(|c| c.get_dispute_escrow(id)), U256::ZERO);
+    assert_eq!(protocol.view(|c| c.total_escrowed()), usdc(PRICE));
+}
+
+// 1601
+#[test]
+fn migrate_steps_storage_up_one_version() {
+    let mut protocol = Protocol::new();
+    protocol.open_dispute(1);
+    protocol.open_dispute(2);
+    protocol.ok(OWNER, |c| c.migrate(1, 10));
+    assert_eq!(protocol.view(|c| c.storage_version()), STORAGE_VERSION);
+
+    protocol.ok(OWNER, |c| {
+        c.storage_version.set(U32::from(1));
+        c.ack_window.set(U64::ZERO);
+        c.disputes.setter(U64::from(2)).nominal_price.set(U256::ZERO);
+        Ok(())
+    });
+    assert_reverts!(protocol.call(OWNER, |c| c.migrate(3, 1)), MigrationGap);
+    protocol.ok(OWNER, |c| c.migrate(1, 1));
+    assert_eq!(protocol.view(|c| c.migration_cursor()), 2);
+    protocol.ok(OWNER, |c| c.migrate(2, 1));
+    assert_eq!(protocol.view(|c| c.storage_version()), 2);
+    assert_eq!(protocol.view(|c| c.get_dispute_financials(2)).unwrap_or_else(|_| panic!("view reverted")).nominal_price, usdc(PRICE));
+    assert_eq!(protocol.view(|c| c.ack_window.get()), U64::from(DEFAULT_ACK_WINDOW));
+}
+
+#[test]
+fn unversioned_storage_needs_a_fresh_deployment() {
+    let mut protocol = Protocol::new();
+    protocol.open_dispute(1);
+    protocol.ok(OWNER, |c| {
+        c.storage_version.set(U32::ZERO);
+        Ok(())
+    });
+    assert_reverts!(protocol.call(OWNER, |c| c.migrate(1, 10)), UnsupportedStorageVersion);
+    assert_eq!(protocol.view(|c| c.storage_version()), 0);
+}
+
+// 1602
+#[test]
+fn an_idle_judge_is_replaced_and_loses_reputation() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(2));
+    protocol.register_judges(3);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2]);
+    protocol.commit(id, 2, VOTE_FOR);
+    assert_reverts!(protocol.call(STRANGER, |c| c.replace_judge(id, judge(1), judge(3))), NotOwnerOrGuardian);
+    assert_reverts!(protocol.call(OWNER, |c| c.replace_judge(id, judge(2), judge(3))), JudgeAlreadyVoted);
+    protocol.ok(OWNER, |c| c.replace_judge(id, judge(1), judge(3)));
+    assert_eq!(protocol.view(|c| c.judge_reputation(judge(1))), -1);
+    assert_eq!(protocol.view(|c| c.get_dispute_panel(id))[0].0, judge(3));
+    assert_reverts!(protocol.call(judge(1), |c| c.commit_vote(id, FixedBytes::ZERO)), JudgeNotAllowedToVote);
+}
+
+// 1603
+#[test]
+fn an_external_oracle_supplies_reputation() {
+    let mut protocol = Protocol::new();
+    protocol.register_judges(2);
+    protocol.ok(OWNER, |c| c.set_reputation_oracle(ORACLE));
+    with_chain(|chain| chain.reputations.insert(judge(1), I256::try_from(9).unwrap()));
+    assert_eq!(protocol.view(|c| c.effective_reputation(judge(1))), 9);
+    // The oracle reverting for judge 2 falls back to the internal counter
+    assert_eq!(protocol.view(|c| c.effective_reputation(judge(2))), 0);
+
+    protocol.ok(OWNER, |c| c.update_min_reputation(5));
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1]);
+    assert_reverts!(protocol.call(judge(2), |c| c.register_to_vote(id)), NotEnoughReputation);
+    assert_eq!(protocol.view(|c| c.get_dispute_panel(id)), vec![(judge(1), 9)]);
+}
+
+// 1604
+#[test]
+fn deal_ids_beyond_u32_survive_the_packed_layout() {
+    let mut protocol = Protocol::new();
+    let deal_id = u64::from(u32::MAX) + 5;
+    let id = protocol.open_dispute(deal_id);
+    assert_eq!(protocol.view(|c| c.get_dispute_info(id)).deal_id, deal_id);
+    assert_eq!(protocol.view(|c| c.get_dispute(id)).0, 0);
+}
+
+// 1605
+#[test]
+fn interface_detection_and_version() {
+    let protocol = Protocol::new();
+    assert!(protocol.view(|c| c.supports_interface(FixedBytes(ERC165_INTERFACE_ID))));
+    let dispute_interface = protocol.view(|c| c.dispute_interface_id());
+    assert!(protocol.view(|c| c.supports_interface(dispute_interface)));
+    assert!(!protocol.view(|c| c.supports_interface(FixedBytes([0xff; 4]))));
+    assert_eq!(protocol.view(|c| c.version()), env!("CARGO_PKG_VERSION"));
+}
+
+// 1606
+#[test]
+fn a_whitelisted_sponsor_pays_and_is_refunded() {
+    let mut protocol = Protocol::new();
+    let requester_before = protocol.balance(REQUESTER);
+    let sponsor_before = protocol.balance(MARKETPLACE);
+    assert_reverts!(
+        protocol.call(MARKETPLACE, |c| c.create_dispute_for(1, REQUESTER, BENEFICIARY, String::new(), 0, U256::MAX, Bytes::from(vec![0; 65]))),
+        InvalidSignature
+    );
+    protocol.ok(OWNER, |c| c.set_sponsor(MARKETPLACE, true));
+    protocol.ok(MARKETPLACE, |c| c.create_dispute_for(1, REQUESTER, BENEFICIARY, String::new(), 0, U256::ZERO, Bytes::from(Vec::new())));
+    assert_eq!(protocol.view(|c| c.get_fee_payer(1)), MARKETPLACE);
+    assert_eq!(protocol.view(|c| c.get_dispute_info(1)).requester, REQUESTER);
+
+    protocol.warp(DEFAULT_PANEL_TIMEOUT);
+    protocol.ok(STRANGER, |c| c.expire_dispute(1));
+    assert_eq!(protocol.balance(MARKETPLACE), sponsor_before);
+    assert_eq!(protocol.balance(REQUESTER), requester_before);
+}
+
+#[test]
+fn a_requester_signature_lets_anyone_sponsor() {
+    let mut protocol = Protocol::new();
+    let (signer, requester) = wallet(9);
+    let deadline = U256::from(START + 60);
+    let digest = protocol.view(|c| c.sponsor_digest(5, BENEFICIARY, 0, STRANGER, U256::ZERO, deadline));
+    let signature = sign(&signer, digest);
+    protocol.ok(STRANGER, |c| c.create_dispute_for(5, requester, BENEFICIARY, String::new(), 0, deadline, signature.clone()));
+    assert_eq!(protocol.view(|c| c.nonces(requester)), U256::from(1));
+    assert_reverts!(
+        protocol.call(STRANGER, |c| c.create_dispute_for(6, requester, BENEFICIARY, String::new(), 0, deadline, signature)),
+        InvalidSignature
+    );
+}
+
+// 1607
+#[test]
+fn the_phase_view_follows_the_dispute() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(1));
+    protocol.register_judges(1);
+    assert_eq!(protocol.phase(1), PHASE_NOT_FOUND);
+    let id = protocol.open_dispute(1);
+    assert_eq!(protocol.phase(id), PHASE_WAITING_FOR_JUDGES);
+    protocol.seat(id, &[1]);
+    assert_eq!(protocol.phase(id), PHASE_COMMIT);
+    protocol.commit(id, 1, VOTE_FOR);
+    assert_eq!(protocol.phase(id), PHASE_REVEAL);
+    protocol.reveal(id, 1, VOTE_FOR);
+    assert_eq!(protocol.phase(id), PHASE_RESOLVED);
+}
+
+// 1608
+#[test]
+fn a_losing_judge_forfeits_the_slash_share_of_the_bond() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.ok(OWNER, |c| c.set_judge_bond(usdc(10_000_000), 5_000));
+    protocol.register_judges(3);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2, 3]);
+    assert_eq!(protocol.view(|c| c.get_locked_judge_bond(id, judge(1))), usdc(10_000_000));
+    protocol.commit(id, 1, VOTE_FOR);
+    protocol.commit(id, 2, VOTE_FOR);
+    protocol.commit(id, 3, VOTE_AGAINST);
+    protocol.reveal(id, 3, VOTE_AGAINST);
+    protocol.reveal(id, 1, VOTE_FOR);
+    protocol.reveal(id, 2, VOTE_FOR);
+    protocol.ok(STRANGER, |c| c.settle_original_panel(id));
+
+    // Winners split the judge share plus half the loser's bond, and get their own bond back
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(1))), usdc(32_500_000));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(3))), usdc(5_000_000));
+    assert_eq!(protocol.logged::<JudgeBondForfeited>(), 1);
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+#[test]
+fn a_judge_who_never_committed_forfeits_the_whole_bond() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.ok(OWNER, |c| c.set_judge_bond(usdc(10_000_000), 5_000));
+    protocol.register_judges(3);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2, 3]);
+    protocol.commit(id, 1, VOTE_FOR);
+    protocol.commit(id, 2, VOTE_FOR);
+    protocol.warp(DEFAULT_COMMIT_PERIOD);
+    protocol.reveal(id, 1, VOTE_FOR);
+    protocol.reveal(id, 2, VOTE_FOR);
+    protocol.ok(STRANGER, |c| c.settle_original_panel(id));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(1))), usdc(35_000_000));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(3))), U256::ZERO);
+    assert_eq!(protocol.view(|c| c.judge_reputation(judge(3))), -1);
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+#[test]
+fn a_tie_forfeits_the_bonds_of_seats_that_never_revealed() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.ok(OWNER, |c| c.set_judge_bond(usdc(10_000_000), 5_000));
+    protocol.register_judges(6);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2, 3]);
+    protocol.commit(id, 1, VOTE_FOR);
+    protocol.commit(id, 2, VOTE_AGAINST);
+    protocol.commit(id, 3, VOTE_FOR);
+    protocol.reveal(id, 1, VOTE_FOR);
+    protocol.reveal(id, 2, VOTE_AGAINST);
+    protocol.warp(protocol.view(|c| c.get_dispute_deadlines(id)).3 - START);
+    protocol.ok(STRANGER, |c| c.finalize_dispute(id));
+    assert_eq!(protocol.view(|c| c.get_dispute_round(id)), 1);
+    assert_eq!(protocol.logged::<JudgeBondForfeited>(), 1);
+    assert_eq!(protocol.view(|c| c.get_locked_judge_bond(id, judge(3))), U256::ZERO);
+
+    // The revealed seats of the tied round get their bonds back as the final panel settles
+    protocol.vote(id, 4, &[VOTE_FOR; 3]);
+    protocol.ok(STRANGER, |c| c.settle_original_panel(id));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(1))), usdc(10_000_000));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(3))), U256::ZERO);
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+#[test]
+fn a_tie_forfeits_the_bonds_of_seats_that_never_committed() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.ok(OWNER, |c| c.set_judge_bond(usdc(10_000_000), 5_000));
+    protocol.register_judges(3);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2, 3]);
+    protocol.commit(id, 1, VOTE_FOR);
+    protocol.commit(id, 2, VOTE_AGAINST);
+    protocol.warp(DEFAULT_COMMIT_PERIOD);
+    protocol.reveal(id, 1, VOTE_FOR);
+    protocol.reveal(id, 2, VOTE_AGAINST);
+    assert_eq!(protocol.view(|c| c.get_dispute_round(id)), 1);
+    assert_eq!(protocol.logged::<JudgeBondForfeited>(), 1);
+    assert_eq!(protocol.view(|c| c.get_locked_judge_bond(id, judge(3))), U256::ZERO);
+    assert_eq!(protocol.view(|c| c.get_locked_judge_bond(id, judge(1))), usdc(10_000_000));
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+// 1609
+#[test]
+fn disputes_awaiting_judges_are_indexed() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(1));
+    protocol.register_judges(1);
+    let first = protocol.open_dispute(1);
+    let second = protocol.open_dispute(2);
+    let third = protocol.open_dispute(3);
+    assert_eq!(protocol.view(|c| c.open_for_registration_count()), U256::from(3));
+    protocol.seat(first, &[1]);
+    assert_eq!(protocol.view(|c| c.get_open_for_registration(U256::ZERO, U256::from(10))), vec![third, second]);
+}
+
+// 1610
+#[test]
+fn panel_sizes_are_capped_and_can_be_forced_odd() {
+    let mut protocol = Protocol::new();
+    assert_reverts!(protocol.call(OWNER, |c| c.update_number_of_votes(MAX_PANEL_SIZE + 1)), InvalidPanelSize);
+    assert_reverts!(protocol.call(OWNER, |c| c.set_category(1, usdc(PRICE), MAX_PANEL_SIZE + 1)), InvalidPanelSize);
+    protocol.ok(OWNER, |c| c.set_strict_majority(true));
+    assert_reverts!(protocol.call(OWNER, |c| c.update_number_of_votes(4)), InvalidPanelSize);
+    protocol.ok(OWNER, |c| c.update_number_of_votes(MAX_PANEL_SIZE));
+    assert!(ProtocolContract::checked_increment(U8::MAX).is_err());
+}
+
+// 1611
+#[test]
+fn a_quorate_commit_set_is_frozen_at_the_deadline() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.register_judges(3);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2, 3]);
+    protocol.commit(id, 1, VOTE_FOR);
+    protocol.commit(id, 2, VOTE_AGAINST);
+    assert_reverts!(protocol.call(STRANGER, |c| c.close_commit_phase(id)), CommitPhaseNotEnded);
+    protocol.warp(DEFAULT_COMMIT_PERIOD);
+    protocol.ok(STRANGER, |c| c.close_commit_phase(id));
+    assert_eq!(protocol.phase(id), PHASE_REVEAL);
+    assert_eq!(protocol.view(|c| c.judge_reputation(judge(3))), -1);
+    assert_reverts!(protocol.call(STRANGER, |c| c.close_commit_phase(id)), CommitPhaseAlreadyClosed);
+    assert_reverts!(protocol.call(judge(3), |c| c.commit_vote(id, FixedBytes::ZERO)), CommitPhaseEnded);
+}
+
+#[test]
+fn an_inquorate_commit_phase_expires_the_dispute() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.ok(OWNER, |c| c.set_judge_bond(usdc(10_000_000), 0));
+    protocol.register_judges(3);
+    let before = protocol.balance(REQUESTER);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1, 2, 3]);
+    protocol.commit(id, 1, VOTE_FOR);
+    protocol.warp(DEFAULT_COMMIT_PERIOD);
+    assert_reverts!(protocol.try_reveal(id, 1, VOTE_FOR), CommitPhaseEnded);
+    protocol.ok(STRANGER, |c| c.close_commit_phase(id));
+    assert_eq!(protocol.phase(id), PHASE_EXPIRED);
+    assert_eq!(protocol.balance(REQUESTER), before);
+    // The judge who committed gets the bond back; the silent ones fund the treasury
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(1))), usdc(10_000_000));
+    assert_eq!(protocol.view(|c| c.contract_balance()), usdc(20_000_000));
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+// 1612
+#[test]
+fn marketplace_disputes_can_wait_for_the_requester() {
+    let mut protocol = Protocol::new();
+    protocol.register_judges(1);
+    protocol.ok(OWNER, |c| c.set_require_requester_ack(MARKETPLACE, true));
+    protocol.ok(MARKETPLACE, |c| c.create_dispute(1, REQUESTER, String::new(), 0));
+    protocol.ok(MARKETPLACE, |c| c.create_dispute(2, REQUESTER, String::new(), 0));
+    assert_eq!(protocol.phase(1), PHASE_PENDING_ACK);
+    assert_reverts!(protocol.call(judge(1), |c| c.register_to_vote(1)), DisputeNotConfirmed);
+    assert_reverts!(protocol.call(STRANGER, |c| c.confirm_dispute(1)), NotTheRequester);
+    protocol.ok(REQUESTER, |c| c.confirm_dispute(1));
+    protocol.seat(1, &[1]);
+
+    let before = protocol.balance(MARKETPLACE);
+    assert_reverts!(protocol.call(STRANGER, |c| c.void_unconfirmed_dispute(2)), AckWindowNotEnded);
+    protocol.warp(DEFAULT_ACK_WINDOW + 1);
+    protocol.ok(STRANGER, |c| c.void_unconfirmed_dispute(2));
+    assert_eq!(protocol.phase(2), PHASE_EXPIRED);
+    assert_eq!(protocol.balance(MARKETPLACE), before + usdc(PRICE));
+}
+
+// 1613
+#[test]
+fn protocol_metrics_count_disputes_and_money() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(1));
+    protocol.register_judges(1);
+    let resolved = protocol.open_dispute(1);
+    let expired = protocol.open_dispute(2);
+    protocol.open_dispute(3);
+    protocol.vote(resolved, 1, &[VOTE_FOR]);
+    protocol.ok(STRANGER, |c| c.settle_original_panel(resolved));
+    protocol.warp(DEFAULT_PANEL_TIMEOUT);
+    protocol.ok(STRANGER, |c| c.expire_dispute(expired));
+
+    let metrics = protocol.view(|c| c.get_protocol_metrics());
+    assert_eq!(metrics.disputes_created, 3);
+    assert_eq!(metrics.disputes_open, 1);
+    assert_eq!(metrics.disputes_resolved, 1);
+    assert_eq!(metrics.disputes_expired, 1);
+    assert_eq!(metrics.total_fees_collected, usdc(3 * PRICE));
+    assert_eq!(metrics.total_judge_rewards_paid, usdc(40_000_000));
+    assert_eq!(metrics.treasury_surplus, usdc(10_000_000));
+}
+
+// 1614
+#[test]
+fn only_the_originating_marketplace_relays_proofs() {
+    let mut protocol = Protocol::new();
+    protocol.ok(MARKETPLACE, |c| c.create_dispute(1, REQUESTER, String::new(), 0));
+    protocol.ok(MARKETPLACE, |c| c.relay_proof_for_requester(1, "receipt".into()));
+    assert_eq!(protocol.view(|c| c.get_proof(1, REQUESTER)), "receipt");
+    assert_reverts!(protocol.call(STRANGER, |c| c.relay_proof_for_requester(1, "forged".into())), NotOriginatingContract);
+    assert_reverts!(protocol.call(MARKETPLACE, |c| c.relay_proof_for_requester(1, String::new())), ProofCannotBeEmpty);
+
+    let direct = protocol.open_dispute(2);
+    assert_reverts!(protocol.call(REQUESTER, |c| c.relay_proof_for_requester(direct, "proof".into())), NotOriginatingContract);
+    protocol.ok(REQUESTER, |c| c.update_dispute_for_payer(direct, REQUESTER, "proof".into()));
+}
+
+// 1615
+#[test]
+fn an_overturning_appeal_takes_the_original_rewards() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.ok(OWNER, |c| c.update_appeal_window(86_400));
+    protocol.register_judges(6);
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR, VOTE_AGAINST, VOTE_FOR]);
+    assert_reverts!(protocol.call(STRANGER, |c| c.settle_original_panel(id)), SettlementLocked);
+    assert_reverts!(protocol.call(REQUESTER, |c| c.appeal(id)), NotAParty);
+
+    protocol.ok(BENEFICIARY, |c| c.appeal(id));
+    let appeal_id = protocol.view(|c| c.get_appeal(id)).1;
+    assert_eq!(protocol.view(|c| c.get_appeal(appeal_id)), (id, 0, false));
+    assert_reverts!(protocol.call(judge(1), |c| c.register_to_vote(appeal_id)), JudgeAlreadyServed);
+
+    protocol.vote(appeal_id, 4, &[VOTE_AGAINST, VOTE_AGAINST, VOTE_FOR]);
+    assert!(protocol.view(|c| c.get_appeal(id)).2);
+    assert_eq!(protocol.outcome(id), OUTCOME_BENEFICIARY_WON);
+    assert_reverts!(protocol.call(REQUESTER, |c| c.appeal(appeal_id)), AppealNotAllowed);
+
+    protocol.ok(STRANGER, |c| c.settle_original_panel(id));
+    protocol.ok(STRANGER, |c| c.settle_original_panel(appeal_id));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(1))), U256::ZERO);
+    assert_eq!(protocol.view(|c| c.judge_reputation(judge(1))), -1);
+    assert_eq!(protocol.view(|c| c.judge_reputation(judge(2))), 1);
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(4))), usdc(40_000_000));
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+#[test]
+fn an_appeal_that_never_reveals_times_out_to_the_original_ruling() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(1));
+    protocol.ok(OWNER, |c| c.update_appeal_window(86_400));
+    protocol.register_judges(2);
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR]);
+    protocol.ok(BENEFICIARY, |c| c.appeal(id));
+    let appeal_id = protocol.view(|c| c.get_appeal(id)).1;
+    protocol.seat(appeal_id, &[2]);
+    protocol.commit(appeal_id, 2, VOTE_AGAINST);
+    assert_eq!(protocol.view(|c| c.get_pending_settlement(id)).2, u64::MAX);
+
+    protocol.warp(protocol.view(|c| c.get_dispute_deadlines(appeal_id)).3 - START);
+    protocol.ok(STRANGER, |c| c.finalize_dispute(appeal_id));
+    assert_eq!(protocol.outcome(appeal_id), OUTCOME_EXPIRED);
+    assert_eq!(protocol.logged::<AppealConcluded>(), 1);
+    assert_eq!(protocol.outcome(id), OUTCOME_REQUESTER_WON);
+    protocol.ok(STRANGER, |c| c.settle_original_panel(id));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(1))), usdc(40_000_000));
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+#[test]
+fn the_appealed_panel_cannot_return_as_a_delegate() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(1));
+    protocol.ok(OWNER, |c| c.update_appeal_window(86_400));
+    protocol.register_judges(2);
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR]);
+    protocol.ok(BENEFICIARY, |c| c.appeal(id));
+    let appeal_id = protocol.view(|c| c.get_appeal(id)).1;
+    protocol.seat(appeal_id, &[2]);
+    assert_reverts!(protocol.call(judge(2), |c| c.delegate_vote(appeal_id, judge(1))), JudgeAlreadyServed);
+}
+
+// 1616
+#[test]
+fn weak_secrets_and_replayed_commitments_are_rejected() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(2));
+    protocol.register_judges(4);
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR, VOTE_AGAINST]);
+    assert_reverts!(protocol.call(judge(1), |c| c.reveal_votes(id, VOTE_FOR, b"short".to_vec())), WeakSecret);
+
+    // The tied round's revealed commitment can't be reused by the next panel
+    protocol.seat(id, &[3, 4]);
+    let replayed = ProtocolContract::commit_hash(id, judge(1), VOTE_FOR, SECRET);
+    assert_reverts!(protocol.call(judge(3), |c| c.commit_vote(id, replayed)), CommitmentAlreadyRevealed);
+}
+
+// 1617
+#[test]
+fn contract_judges_register_only_when_allowed() {
+    let mut protocol = Protocol::new();
+    protocol.mark_contract(judge(1));
+    assert_reverts!(protocol.call(judge(1), |c| c.register_as_judge()), ContractJudgesNotAllowed);
+    protocol.ok(OWNER, |c| c.set_allow_contract_judges(true));
+    protocol.ok(judge(1), |c| c.register_as_judge());
+    assert!(protocol.view(|c| c.get_judge_info(judge(1))).is_contract);
+    assert_reverts!(
+        protocol.call(STRANGER, |c| c.commit_vote_by_sig(1, FixedBytes::ZERO, judge(1), U256::MAX, Bytes::from(vec![0; 65]))),
+        InvalidSignature
+    );
+}
+
+// 1618
+#[test]
+fn dispute_financials_show_the_fee_split() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_judge_reward_bps(7_000));
+    let id = protocol.open_dispute(1);
+    let financials = protocol.view(|c| c.get_dispute_financials(id)).unwrap_or_else(|_| panic!("view reverted"));
+    assert_eq!(financials.fee_payer, REQUESTER);
+    assert_eq!((financials.judge_reward_bps, financials.protocol_fee_bps), (7_000, 3_000));
+    assert_eq!(financials.escrow_remaining, usdc(PRICE));
+    assert_reverts!(protocol.view(|c| c.get_dispute_financials(9)), DisputeDoesNotExist);
+}
+
+// 1619
+#[test]
+fn reveals_after_resolution_revert_and_pay_nothing() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.register_judges(3);
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR, VOTE_FOR, VOTE_FOR]);
+    assert_reverts!(protocol.try_reveal(id, 3, VOTE_FOR), DisputeAlreadyResolved);
+    protocol.ok(STRANGER, |c| c.settle_original_panel(id));
+    assert_reverts!(protocol.call(STRANGER, |c| c.settle_original_panel(id)), NoPendingSettlement);
+    assert_eq!(protocol.view(|c| c.total_judge_rewards()), usdc(40_000_000));
+    assert_eq!(protocol.logged::<DisputeResolved>(), 1);
+}
+
+#[test]
+fn the_final_reveal_and_finalize_resolve_once_in_either_order() {
+    for reveal_first in [true, false] {
+        let mut protocol = Protocol::new();
+        protocol.register_judges(5);
+        let id = protocol.open_dispute(1);
+        protocol.seat(id, &[1, 2, 3, 4, 5]);
+        for (i, vote) in [(1, VOTE_FOR), (2, VOTE_FOR), (3, VOTE_FOR), (4, VOTE_AGAINST), (5, VOTE_AGAINST)] {
+            protocol.commit(id, i, vote);
+        }
+        protocol.reveal(id, 1, VOTE_FOR);
+        protocol.reveal(id, 2, VOTE_FOR);
+        protocol.reveal(id, 4, VOTE_AGAINST);
+
+        // The reveal that makes the majority and the finalize land in the block at the reveal deadline
+        protocol.warp(protocol.view(|c| c.get_dispute_deadlines(id)).3 - START);
+        if reveal_first {
+            protocol.reveal(id, 3, VOTE_FOR);
+            assert_reverts!(protocol.call(STRANGER, |c| c.finalize_dispute(id)), DisputeAlreadyResolved);
+        } else {
+            protocol.ok(STRANGER, |c| c.finalize_dispute(id));
+            assert_reverts!(protocol.try_reveal(id, 3, VOTE_FOR), DisputeAlreadyResolved);
+        }
+        assert_eq!(protocol.outcome(id), OUTCOME_REQUESTER_WON);
+        assert_eq!(protocol.logged::<DisputeResolved>(), 1);
+        protocol.ok(STRANGER, |c| c.settle_original_panel(id));
+        // Paid once: three winners share the judge share, or two if the third never revealed
+        let share = if reveal_first { usdc(13_333_333) } else { usdc(20_000_000) };
+        assert_eq!(protocol.view(|c| c.judge_balance(judge(1))), share);
+        assert_eq!(protocol.view(|c| c.total_judge_rewards()), share * U256::from(if reveal_first { 3 } else { 2 }));
+        assert_eq!(protocol.surplus(), I256::ZERO);
+    }
+}
+
+// 1620
+#[test]
+fn panels_settle_in_pages_without_paying_twice() {
+    let mut protocol = Protocol::new();
+    protocol.register_judges(5);
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR; 5]);
+    protocol.ok(STRANGER, |c| c.settle_judges(id, 0, 2));
+    assert!(protocol.view(|c| c.get_pending_settlement(id)).0);
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(1))), usdc(13_333_333));
+    protocol.ok(STRANGER, |c| c.settle_judges(id, 1, 10));
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(2))), usdc(13_333_333));
+    assert_eq!(protocol.view(|c| c.get_pending_settlement(id)), (false, U256::ZERO, START));
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+#[test]
+fn a_tied_round_settles_with_the_final_panel() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(2));
+    protocol.ok(OWNER, |c| c.set_judge_bond(usdc(10_000_000), 5_000));
+    protocol.register_judges(4);
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR, VOTE_AGAINST]);
+    protocol.vote(id, 3, &[VOTE_FOR, VOTE_FOR]);
+    assert_eq!(protocol.outcome(id), OUTCOME_REQUESTER_WON);
+    assert_ne!(protocol.view(|c| c.get_locked_judge_bond(id, judge(1))), U256::ZERO);
+
+    // The tied round's seats come first, the deciding panel's start at index 25
+    protocol.ok(STRANGER, |c| c.settle_judges(id, 0, 25));
+    assert!(protocol.view(|c| c.get_pending_settlement(id)).0);
+    assert_eq!(protocol.view(|c| c.get_locked_judge_bond(id, judge(2))), U256::ZERO);
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(2))), usdc(10_000_000));
+    assert_eq!(protocol.view(|c| c.get_judge_history_length(judge(2))), U256::from(1));
+    protocol.ok(STRANGER, |c| c.settle_judges(id, 25, 2));
+    assert!(!protocol.view(|c| c.get_pending_settlement(id)).0);
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(3))), usdc(30_000_000));
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
+
+// 1621
+#[test]
+fn judge_history_records_each_settled_seat() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(3));
+    protocol.register_judges(3);
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR, VOTE_AGAINST, VOTE_FOR]);
+    protocol.ok(STRANGER, |c| c.settle_original_panel(id));
+    let history = protocol.view(|c| c.get_judge_history(judge(2), U256::ZERO, U256::from(10)));
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].dispute_id, id);
+    assert!(history[0].vote_revealed);
+    assert!(!history[0].was_majority);
+    assert_eq!(history[0].reputation_delta, -1);
+    assert_eq!(protocol.view(|c| c.get_judge_history(judge(1), U256::ZERO, U256::from(10)))[0].reward_amount, usdc(20_000_000));
+}
+
+// 1622
+#[test]
+fn the_guardian_can_stop_things_but_not_move_funds() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(1));
+    protocol.register_judges(2);
+    protocol.ok(OWNER, |c| c.set_guardian(STRANGER));
+    assert_eq!(protocol.view(|c| c.guardian()), STRANGER);
+
+    let id = protocol.open_dispute(1);
+    protocol.vote(id, 1, &[VOTE_FOR]);
+    protocol.ok(OWNER, |c| c.queue_withdrawal());
+    assert_reverts!(protocol.call(STRANGER, |c| c.queue_withdrawal()), NotOwner);
+    assert_reverts!(protocol.call(STRANGER, |c| c.execute_withdrawal()), NotOwner);
+    protocol.ok(STRANGER, |c| c.cancel_withdrawal());
+    assert_eq!(protocol.view(|c| c.get_queued_withdrawal()), (U256::ZERO, 0));
+
+    let other = protocol.open_dispute(2);
+    protocol.seat(other, &[1]);
+    protocol.ok(STRANGER, |c| c.replace_judge(other, judge(1), judge(2)));
+}
+
+#[test]
+fn the_guardian_can_pause_but_not_withdraw_or_reprice() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(1));
+    protocol.register_judges(2);
+    protocol.ok(OWNER, |c| c.set_guardian(STRANGER));
+    assert_reverts!(protocol.call(judge(1), |c| c.pause()), NotOwnerOrGuardian);
+
+    protocol.ok(STRANGER, |c| c.pause());
+    assert!(protocol.view(|c| c.is_paused()));
+    assert_reverts!(protocol.call(REQUESTER, |c| c.create_dispute_direct(1, BENEFICIARY, "proof".into(), 0)), ProtocolPaused);
+    assert_reverts!(protocol.call(STRANGER, |c| c.unpause()), NotOwner);
+    assert_reverts!(protocol.call(STRANGER, |c| c.execute_withdrawal()), NotOwner);
+    assert_reverts!(protocol.call(STRANGER, |c| c.set_category(0, usdc(1), 1)), NotOwner);
+    assert_reverts!(protocol.call(STRANGER, |c| c.update_judge_reward_bps(10_000)), NotOwner);
+    protocol.ok(OWNER, |c| c.unpause());
+    protocol.open_dispute(1);
+}
+
+#[test]
+fn the_guardian_can_ban_judges_and_void_a_dispute() {
+    let mut protocol = Protocol::new();
+    protocol.ok(OWNER, |c| c.update_number_of_votes(2));
+    protocol.ok(OWNER, |c| c.set_judge_bond(usdc(10_000_000), 5_000));
+    protocol.register_judges(3);
+    protocol.ok(OWNER, |c| c.set_guardian(STRANGER));
+    let before = protocol.balance(REQUESTER);
+    let id = protocol.open_dispute(1);
+    protocol.seat(id, &[1]);
+
+    protocol.ok(STRANGER, |c| c.ban_judge(judge(2)));
+    assert_reverts!(protocol.call(judge(2), |c| c.register_to_vote(id)), JudgeNotActive);
+    assert_reverts!(protocol.call(judge(2), |c| c.set_availability(true)), JudgeIsBanned);
+    assert_reverts!(protocol.call(STRANGER, |c| c.unban_judge(judge(2))), NotOwner);
+
+    assert_reverts!(protocol.call(judge(3), |c| c.emergency_resolve(id)), NotOwnerOrGuardian);
+    protocol.ok(STRANGER, |c| c.emergency_resolve(id));
+    assert_eq!(protocol.outcome(id), OUTCOME_EXPIRED);
+    assert_eq!(protocol.balance(REQUESTER), before);
+    assert_eq!(protocol.view(|c| c.judge_balance(judge(1))), usdc(10_000_000));
+    assert_eq!(protocol.surplus(), I256::ZERO);
+}
@@ -1,6 +1,9 @@
 import deployStylusContract from "./deploy_contract";
 import {
+  extendTsAbi,
+  getContractData,
   getDeploymentConfig,
+  getFacetFeaturesFromCargoToml,
   getRpcUrlFromChain,
   printDeployedAddresses,
 } from "./utils/";
@@ -29,12 +32,32 @@ export default async function deployScript(deployOptions: DeployOptions) {
   console.log(`📁 Deployment directory: ${config.deploymentDir}`);
   console.log(`\n`);
 
-  // Deploy a single contract
+  // Deploy the protocol router, then one contract per facet feature
   await deployStylusContract({
     contract: "contracts",
     ...deployOptions,
   });
 
+  const facets = getFacetFeaturesFromCargoToml("contracts");
+  for (const facet of facets) {
+    await deployStylusContract({
+      ...deployOptions,
+      contract: "contracts",
+      name: `protocol-${facet}`,
+      features: facet,
+    });
+  }
+
+  // Calls to the facets go through the router, so its entry gets their ABI as well. The
+  // routes themselves are set by the owner after init (yarn register-facets)
+  if (!deployOptions.estimateGas) {
+    const chainId = config.chain.id.toString();
+    for (const facet of facets) {
+      const facetData = getContractData(chainId, `protocol-${facet}`);
+      await extendTsAbi(chainId, "protocol", facetData.abi);
+    }
+  }
+
   // EXAMPLE: Deploy your contract with a custom name, uncomment to try
   // await deployStylusContract({
   //   contract: "contracts",
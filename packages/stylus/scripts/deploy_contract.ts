@@ -78,6 +78,7 @@ export default async function deployStylusContract(
       config.contractName,
       false,
       config.chain.id.toString(),
+      deployOptions.features,
     );
 
     // Get contract data from deployed contracts after ABI export
@@ -126,7 +127,7 @@ export default async function deployStylusContract(
     if (deployOptions.verify) {
       try {
         const output = await executeCommand(
-          `cargo stylus verify --endpoint=${getRpcUrlFromChain(config.chain)} --deployment-tx=${deploymentInfo.txHash}`,
+          `cargo stylus verify --endpoint=${getRpcUrlFromChain(config.chain)} --deployment-tx=${deploymentInfo.txHash}${deployOptions.features ? ` --features=${deployOptions.features}` : ""}`,
           deployOptions.contract!,
           "Verifying contract with cargo stylus",
         );
@@ -13,6 +13,7 @@ export async function exportStylusAbi(
   contractName: string,
   isScript: boolean = true,
   chainId?: string,
+  features?: string,
 ) {
   console.log("📄 Starting Stylus ABI export...");
 
@@ -35,18 +36,19 @@ export async function exportStylusAbi(
   try {
     ensureDeploymentDirectory(config.deploymentDir);
 
-    // Export ABI
-    const exportCommand = `cargo stylus export-abi --output='../${config.deploymentDir}/${config.contractFolder}' --json`;
+    // Export ABI; each feature build of a folder gets its own file
+    const abiFile = features
+      ? `${config.contractFolder}-${features}`
+      : config.contractFolder;
+    const featuresFlag = features ? ` --features=${features}` : "";
+    const exportCommand = `cargo stylus export-abi --output='../${config.deploymentDir}/${abiFile}' --json${featuresFlag}`;
     await executeCommand(exportCommand, contractFolder, "Exporting ABI");
 
     console.log(
-      `📄 ABI file location: ${config.deploymentDir}/${config.contractFolder}`,
+      `📄 ABI file location: ${config.deploymentDir}/${abiFile}`,
     );
 
-    const abiFilePath = path.resolve(
-      config.deploymentDir,
-      `${config.contractFolder}`,
-    );
+    const abiFilePath = path.resolve(config.deploymentDir, abiFile);
     if (fs.existsSync(abiFilePath)) {
       console.log(`✅ ABI file verified at: ${abiFilePath}`);
     } else {
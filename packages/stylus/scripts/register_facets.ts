@@ -0,0 +1,71 @@
+import {
+  Abi,
+  Chain,
+  createPublicClient,
+  createWalletClient,
+  getFunctionSelector,
+  http,
+} from "viem";
+import { privateKeyToAccount } from "viem/accounts";
+import {
+  getChain,
+  getContractData,
+  getPrivateKey,
+  loadDeployedContracts,
+} from "./utils/";
+
+/**
+ * Route the functions of every deployed facet (contracts named `<router>-<facet>`) through the
+ * router with set_facet. Only the owner can set routes, so this runs after init.
+ * @param chain - The chain the contracts are deployed on
+ * @param privateKey - The owner's private key
+ * @param routerName - The router's contract name
+ */
+export async function registerFacets(
+  chain: Chain,
+  privateKey: string,
+  routerName: string = "protocol",
+) {
+  const chainId = chain.id.toString();
+  const router = getContractData(chainId, routerName);
+  const facetNames = Object.keys(loadDeployedContracts()[chainId]).filter(
+    (name) => name.startsWith(`${routerName}-`),
+  );
+
+  const publicClient = createPublicClient({ chain, transport: http() });
+  const walletClient = createWalletClient({ chain, transport: http() });
+  const account = privateKeyToAccount(privateKey as `0x${string}`);
+
+  for (const facetName of facetNames) {
+    const facet = getContractData(chainId, facetName);
+    const selectors = (facet.abi as Abi)
+      .filter((item) => item.type === "function")
+      .map((item) => getFunctionSelector(item));
+
+    console.log(
+      `🔀 Routing ${selectors.length} functions to ${facetName} (${facet.address})`,
+    );
+    const { request } = await publicClient.simulateContract({
+      account,
+      address: router.address,
+      abi: router.abi as Abi,
+      functionName: "setFacet",
+      args: [facet.address, selectors],
+    });
+    const hash = await walletClient.writeContract(request);
+    await publicClient.waitForTransactionReceipt({ hash });
+  }
+}
+
+if (require.main === module) {
+  const network = process.argv[2] || "devnet";
+  const chain = getChain(network);
+  if (!chain) {
+    console.error(`❌ Network ${network} not found`);
+    process.exit(1);
+  }
+  registerFacets(chain, getPrivateKey(network)).catch((error) => {
+    console.error("Fatal error:", error);
+    process.exit(1);
+  });
+}
@@ -5,13 +5,14 @@
  * Tests: init, register judges, create dispute, vote, reveal, check winner
  */
 
-import { createPublicClient, createWalletClient, http, keccak256, toBytes, formatUnits } from "viem";
+import { createPublicClient, createWalletClient, getFunctionSelector, http, keccak256, toBytes, toHex, formatUnits, encodePacked, encodeFunctionData, parseAbi, parseSignature } from "viem";
 import { privateKeyToAccount } from "viem/accounts";
 import { config as dotenvConfig } from "dotenv";
 import * as path from "path";
 import * as fs from "fs";
 import { getChain } from "./utils/network";
 import { getContractData } from "./utils/contract";
+import { registerFacets } from "./register_facets";
 
 const envPath = path.resolve(__dirname, "../.env");
 if (fs.existsSync(envPath)) {
@@ -22,10 +23,42 @@ const NETWORK = process.env["NETWORK"] || "devnet";
 const DEPLOYER_PRIVATE_KEY = process.env["DEPLOYER_PRIVATE_KEY_DEVNET"] || "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
 const CHAIN_ID = "412346";
 
-function generateCommitHash(vote: boolean, secret: string) {
-  const voteStr = vote ? "true" : "false";
-  const data = new TextEncoder().encode(voteStr + secret);
-  return keccak256(data);
+// Vote options: 1 = FOR (requester), 2 = AGAINST (beneficiary), 3 = ABSTAIN
+const VOTE_FOR = 1;
+const VOTE_AGAINST = 2;
+const VOTE_ABSTAIN = 3;
+const VOTE_LABELS = ["NONE", "FOR", "AGAINST", "ABSTAIN"];
+
+// XOR of createDispute, getDisputeWinner, getDisputeOutcome, checkIfDisputeIsResolved and
+// getDisputeIdForDeal selectors; update deliberately if the dispute interface changes
+const DISPUTE_INTERFACE_ID = "0x4c43409e";
+const ERC165_INTERFACE_ID = "0x01ffc9a7";
+
+// Hand-assembled mock USDC: balances, allowances, an open mint, EIP-2612 permit under the
+// domain ("Mock USD", "1") and a settable fee in bps that is burnt on every transfer
+const MOCK_USDC_RUNTIME =
+  "60003560e01c806370a082311461006f578063a9059cbb146100e157806323b872dd146100f6578063095ea7b3146100aa578063dd62ed3e1461008f57806340c10f19146100c857806372c27b62146100d65780637ecebe001461007c578063d505accf14610166575b60006000fd5b6004355460005260206000f35b600435600260a01b175460005260206000f35b60043560005260243560205260406000205460005260206000f35b60243533600052600435602052604060002055600160005260206000f35b602435600435540160043555005b600435600160a01b55005b3360805260043560a05260243560c05261012a565b60043560805260243560a05260443560c05260805160005233602052604060002080548060c051116100695760c051900390555b608051548060c051116100695760c051900360805155612710600160a01b5460c051020460c0510360a051540160a05155600160005260206000f35b6064354211610069577f6e71edae12b1b97f4d1f60370fef10105fa2faae0126114a169c64845d6126c961010052600435610120526024356101405260443561016052600435600260a01b1754610180526064356101a0527f8b73c3c69bb8fe3d512ecc4cf759cc79239f7b179b0ffacaa9a75d522b39400f610200527fd2dc350f78c96d45d7ff6969c05e0d435ea44ed1aafac92f5a6770394defb47b610220527fc89efdaa54c0f20c7adf612882df0950f5a951637e0307cdcb4c672f298b8bc661024052466102605230610280526119016103005260a0610200206103205260c06101002061034052604261031e20610400526084356104205260a4356104405260c435610460526000610480526020610480608061040060015afa50610480518015610069576004351415610069576001600435600260a01b175401600435600260a01b175560443560043560005260243560205260406000205500";
+const tokenAbi = parseAbi([
+  "function balanceOf(address account) view returns (uint256)",
+  "function transfer(address to, uint256 amount) returns (bool)",
+  "function transferFrom(address from, address to, uint256 amount) returns (bool)",
+  "function approve(address spender, uint256 amount) returns (bool)",
+  "function allowance(address owner, address spender) view returns (uint256)",
+  "function mint(address to, uint256 amount)",
+  "function setFeeBps(uint256 feeBps)",
+  "function nonces(address owner) view returns (uint256)",
+  "function permit(address owner, address spender, uint256 value, uint256 deadline, uint8 v, bytes32 r, bytes32 s)",
+]);
+
+// Reveals reject secrets shorter than 16 bytes; test secrets are readable labels padded to that length
+const MIN_SECRET_LENGTH = 16;
+function secretBytes(secret: string) {
+  return Array.from(new TextEncoder().encode(secret.padEnd(MIN_SECRET_LENGTH, "#")));
+}
+
+// Mirrors the contract's preimage: abi.encodePacked(uint64 disputeId, address judge, uint8 vote, bytes secret)
+function generateCommitHash(disputeId: bigint, judge: `0x${string}`, vote: number, secret: string) {
+  return keccak256(encodePacked(["uint64", "address", "uint8", "bytes"], [disputeId, judge, vote, toHex(new Uint8Array(secretBytes(secret)))]));
 }
 
 async function runProtocolTest() {
@@ -52,6 +85,25 @@ async function runProtocolTest() {
   const protocolAddress = protocolData.address as `0x${string}`;
   const protocolAbi = protocolData.abi;
 
+  // Simulate and send a transaction, returning false if it reverted
+  const write = async (account: any, functionName: string, args: any[]) => {
+    const wallet = createWalletClient({ account, chain, transport: http() });
+    try {
+      const { request } = await publicClient.simulateContract({
+        account,
+        address: protocolAddress,
+        abi: protocolAbi,
+        functionName,
+        args,
+      });
+      const hash = await wallet.writeContract(request);
+      await publicClient.waitForTransactionReceipt({ hash });
+      return true;
+    } catch (e: any) {
+      return false;
+    }
+  };
+
   console.log(`\n📄 Protocol: ${protocolAddress}\n`);
 
   // Fund accounts with ETH
@@ -72,10 +124,53 @@ async function runProtocolTest() {
   }
   console.log("✅ All accounts funded\n");
 
+  // Deploy a mock ERC-20 with the init code prefix: copy the runtime after it and return it
+  const deployToken = async () => {
+    const size = (MOCK_USDC_RUNTIME.length / 2).toString(16).padStart(4, "0");
+    const hash = await deployerWalletFund.sendTransaction({ data: `0x61${size}80600c6000396000f3${MOCK_USDC_RUNTIME}` });
+    return (await publicClient.waitForTransactionReceipt({ hash })).contractAddress!;
+  };
+  const tokenWrite = async (account: any, token: `0x${string}`, functionName: string, args: any[]) => {
+    const wallet = createWalletClient({ account, chain, transport: http() });
+    try {
+      const { request } = await publicClient.simulateContract({ account, address: token, abi: tokenAbi, functionName, args });
+      const hash = await wallet.writeContract(request);
+      await publicClient.waitForTransactionReceipt({ hash });
+      return true;
+    } catch (e: any) {
+      return false;
+    }
+  };
+  const readTokenBalance = async (account: `0x${string}`, token: `0x${string}` = usdc) =>
+    (await publicClient.readContract({ address: token, abi: tokenAbi, functionName: "balanceOf", args: [account] })) as bigint;
+
+  console.log("💵 Deploying mock USDC...");
+  const usdc = await deployToken();
+  for (const account of [deployer, judge1, judge2, judge3, judge4, judge5, requester]) {
+    await tokenWrite(deployer, usdc, "mint", [account.address, 1_000_000n * 10n ** 6n]);
+    await tokenWrite(account, usdc, "approve", [protocolAddress, 2n ** 255n]);
+  }
+  console.log(`✅ Mock USDC at ${usdc}, every account funded and approved\n`);
+
+  // Every token the protocol holds is owed to someone: dispute escrow, judge balances and stakes, or the treasury
+  const checkSolvency = async (label: string) => {
+    const [escrowed, owedToJudges, treasury, staked] = (await Promise.all(
+      ["totalEscrowed", "totalJudgeRewards", "contractBalance", "totalStaked"].map(functionName =>
+        publicClient.readContract({ address: protocolAddress, abi: protocolAbi, functionName, args: [] }),
+      ),
+    )) as bigint[];
+    const held = await readTokenBalance(protocolAddress);
+    const owed = escrowed + owedToJudges + treasury + staked;
+    console.log(
+      held === owed
+        ? `✅ Solvent after ${label}: ${formatUnits(held, 6)} USDC held and owed`
+        : `❌ After ${label} the protocol holds ${formatUnits(held, 6)} USDC but owes ${formatUnits(owed, 6)} USDC`,
+    );
+  };
+
   // Step 1: Init
   console.log("Step 1: Initialize");
   const deployerWallet = createWalletClient({ account: deployer, chain, transport: http() });
-  const dummyUsdc = "0x0000000000000000000000000000000000000001" as `0x${string}`;
 
   try {
     const { request } = await publicClient.simulateContract({
@@ -83,7 +178,7 @@ async function runProtocolTest() {
       address: protocolAddress,
       abi: protocolAbi,
       functionName: "init",
-      args: [deployer.address, dummyUsdc],
+      args: [deployer.address, usdc],
     });
     const hash = await deployerWallet.writeContract(request);
     await publicClient.waitForTransactionReceipt({ hash });
@@ -91,6 +186,8 @@ async function runProtocolTest() {
   } catch (e: any) {
     console.log("⚠️  Already initialized or error:", e.message.split('\n')[0], "\n");
   }
+  await registerFacets(chain, DEPLOYER_PRIVATE_KEY);
+  console.log("✅ Facets routed through the router\n");
 
   // Step 2: Register Judges
   console.log("Step 2: Register Judges");
@@ -102,7 +199,8 @@ async function runProtocolTest() {
     { account: judge5, name: "Judge5" },
   ];
 
-  for (const j of judges) {
+  // Judge5 registers later, in Step 4, to cover the unregistered panel join
+  for (const j of judges.slice(0, 4)) {
     const wallet = createWalletClient({ account: j.account, chain, transport: http() });
     try {
       const { request } = await publicClient.simulateContract({
@@ -130,7 +228,7 @@ async function runProtocolTest() {
       address: protocolAddress,
       abi: protocolAbi,
       functionName: "createDisputeDirect",
-      args: [1n, judge1.address, "Work not completed"],
+      args: [1n, judge1.address, "Work not completed", 0n],
     });
     const hash = await requesterWallet.writeContract(request);
     await publicClient.waitForTransactionReceipt({ hash });
@@ -141,6 +239,24 @@ async function runProtocolTest() {
 
   const disputeId = 1n;
 
+  const logProgress = async (label: string) => {
+    const [assigned, commits, reveals, required]: any = await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getDisputeProgress",
+      args: [disputeId],
+    });
+    const [createdAt, panelDeadline, commitDeadline]: any = await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getDisputeDeadlines",
+      args: [disputeId],
+    });
+    console.log(`📈 ${label}: ${assigned}/${required} assigned, ${commits} committed, ${reveals} revealed`);
+    console.log(`   Created=${createdAt}, PanelDeadline=${panelDeadline}, CommitDeadline=${commitDeadline}`);
+  };
+  await logProgress("After creation");
+
   // Check dispute state
   const disputeInfo: any = await publicClient.readContract({
     address: protocolAddress,
@@ -152,17 +268,51 @@ async function runProtocolTest() {
   console.log(`  Requester=${disputeInfo[2]}, Beneficiary=${disputeInfo[3]}`);
   console.log(`  WaitingForJudges=${disputeInfo[4]}, IsOpen=${disputeInfo[5]}, Resolved=${disputeInfo[6]}\n`);
 
+  // Named struct view decodes to an object keyed by field name
+  const disputeStruct: any = await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getDisputeInfo",
+    args: [disputeId],
+  });
+  console.log(`  DealId=${disputeStruct.deal_id}, Price=${formatUnits(disputeStruct.price, 6)} USDC, RequiredVotes=${disputeStruct.required_votes}`);
+  console.log(disputeStruct.requester === disputeInfo[2] ? "✅ getDisputeInfo matches getDispute" : "❌ getDisputeInfo mismatch");
+
   // Step 4: Register to Vote
   console.log("\nStep 4: Judges Register to Vote");
+
+  // Phases: 0 NotFound, 1 WaitingForJudges, 2 Commit, 3 Reveal, 4 Resolved, 5 reserved, 6 Expired, 7 PendingAck
+  // No commits before the panel exists
+  const earlyCommit = await write(judge1, "commitVote", [disputeId, generateCommitHash(disputeId, judge1.address, VOTE_FOR, "early")]);
+  console.log(earlyCommit ? "❌ Commit accepted while waiting for judges" : "✅ Commit rejected while waiting for judges");
+
+  // Reputation threshold: judges start at 0
+  await write(deployer, "updateMinReputation", [2]);
+  const belowThreshold = await write(judge1, "registerToVote", [disputeId]);
+  console.log(belowThreshold ? "❌ Judge below min reputation joined" : "✅ Judge below min reputation rejected");
+  await write(deployer, "updateMinReputation", [-1]);
+
+  // Only registered judges can join a panel
+  const unregisteredJoined = await write(judge5, "registerToVote", [disputeId]);
+  console.log(unregisteredJoined ? "❌ Unregistered address joined the panel" : "✅ Unregistered address rejected");
+  const judge5Registered = await write(judge5, "registerAsJudge", []);
+  console.log(judge5Registered ? "✅ Judge5 registered" : "❌ Judge5 registration failed");
+
+  // Inactive judges can't join a panel
+  await write(judge5, "setAvailability", [false]);
+  const inactiveJoined = await write(judge5, "registerToVote", [disputeId]);
+  console.log(inactiveJoined ? "❌ Inactive judge joined the panel" : "✅ Inactive judge rejected");
+  await write(judge5, "setAvailability", [true]);
+
   for (const j of judges) {
     // First check if they're registered
     const judgeInfo: any = await publicClient.readContract({
       address: protocolAddress,
       abi: protocolAbi,
-      functionName: "getJudge",
+      functionName: "getJudgeInfo",
       args: [j.account.address],
     });
-    console.log(`${j.name} info: Address=${judgeInfo[0]}, Balance=${judgeInfo[1]}, Rep=${judgeInfo[2]}`);
+    console.log(`${j.name} info: Address=${judgeInfo.judge_address}, Balance=${judgeInfo.balance}, Rep=${judgeInfo.reputation}`);
     
     const wallet = createWalletClient({ account: j.account, chain, transport: http() });
     try {
@@ -181,21 +331,125 @@ async function runProtocolTest() {
     }
   }
 
+  const panel: any = await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getDisputePanel",
+    args: [disputeId],
+  });
+  for (const [judge, repAtAssignment] of panel) {
+    console.log(`  Panel: ${judge} (Rep at assignment=${repAtAssignment})`);
+  }
+
+  await logProgress("After panel registration");
+
+  // A full panel rejects a sixth judge and leaves the counts untouched
+  const readProgress = async () =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getDisputeProgress",
+      args: [disputeId],
+    })) as any;
+  const progressBefore = await readProgress();
+  await write(deployer, "registerAsJudge", []);
+  const sixthJoined = await write(deployer, "registerToVote", [disputeId]);
+  const progressAfter = await readProgress();
+  const countsUnchanged = progressBefore.every((value: bigint, i: number) => value === progressAfter[i]);
+  console.log(!sixthJoined && countsUnchanged ? "✅ Sixth judge rejected from a full panel" : "❌ Full panel accepted another judge");
+  const openedEvents = await publicClient.getContractEvents({
+    address: protocolAddress,
+    abi: protocolAbi,
+    eventName: "DisputeOpened",
+    args: { dispute_id: disputeId },
+    fromBlock: 0n,
+  });
+  console.log(openedEvents.length === 1 ? "✅ DisputeOpened emitted once" : `❌ DisputeOpened emitted ${openedEvents.length} times`);
+  await write(deployer, "updateMinReputation", [0]);
+
+  // A delegate can't hold their own seat plus a delegated one
+  const delegatedToPanelist = await write(judge1, "delegateVote", [disputeId, judge2.address]);
+  console.log(delegatedToPanelist ? "❌ Delegated to a panel member" : "✅ Delegation to a panel member rejected");
+
+  // An open dispute can no longer expire
+  const expiredOpen = await write(requester, "expireDispute", [disputeId]);
+  console.log(expiredOpen ? "❌ Open dispute was expired" : "✅ Open dispute cannot expire");
+
   // Step 5: Commit Votes
   console.log("\nStep 5: Commit Votes");
   const votes = [
-    { judge: judges[0], vote: true, secret: "secret1" },
-    { judge: judges[1], vote: true, secret: "secret2" },
-    { judge: judges[2], vote: false, secret: "secret3" },
-    { judge: judges[3], vote: true, secret: "secret4" },
-    { judge: judges[4], vote: false, secret: "secret5" },
+    { judge: judges[0], vote: VOTE_FOR, secret: "secret1" },
+    { judge: judges[1], vote: VOTE_FOR, secret: "secret2" },
+    { judge: judges[2], vote: VOTE_AGAINST, secret: "secret3" },
+    { judge: judges[3], vote: VOTE_FOR, secret: "secret4" },
+    { judge: judges[4], vote: VOTE_ABSTAIN, secret: "secret5" },
   ];
 
   let totalCommitGas = 0n;
   for (const v of votes) {
     const wallet = createWalletClient({ account: v.judge.account, chain, transport: http() });
-    const commitHash = generateCommitHash(v.vote, v.secret);
-    console.log(`${v.judge.name}: ${v.vote ? "FOR" : "AGAINST"}`);
+    // Commit with the contract's own helper and make sure the local encoding agrees
+    const commitHash = (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "computeCommitHash",
+      args: [disputeId, v.judge.account.address, v.vote, secretBytes(v.secret)],
+    })) as `0x${string}`;
+    if (commitHash !== generateCommitHash(disputeId, v.judge.account.address, v.vote, v.secret)) {
+      console.log(`❌ ${v.judge.name}: computeCommitHash differs from the local encoding`);
+    }
+    console.log(`${v.judge.name}: ${VOTE_LABELS[v.vote]}`);
+
+    // Reveals stay closed until every judge committed (or the deadline passes)
+    if (v === votes[3]) {
+      const revealSecret = secretBytes(votes[0].secret);
+      const earlyReveal = await write(votes[0].judge.account, "revealVotes", [disputeId, votes[0].vote, revealSecret]);
+      console.log(earlyReveal ? "❌ Reveal accepted during commit phase" : "✅ Reveal rejected with 3/5 commits");
+    }
+    
+    // The last judge signs an EIP-712 commit and the deployer relays it
+    if (v === votes[4]) {
+      const signCommit = (account: any, nonce: bigint, deadline: bigint) =>
+        account.signTypedData({
+          domain: { name: "Lancer Protocol", version: "1", chainId: chain.id, verifyingContract: protocolAddress },
+          types: {
+            Commit: [
+              { name: "disputeId", type: "uint64" },
+              { name: "commitHash", type: "bytes32" },
+              { name: "nonce", type: "uint256" },
+              { name: "deadline", type: "uint256" },
+            ],
+          },
+          primaryType: "Commit",
+          message: { disputeId, commitHash, nonce, deadline },
+        });
+      const readNonce = async () =>
+        (await publicClient.readContract({
+          address: protocolAddress,
+          abi: protocolAbi,
+          functionName: "nonces",
+          args: [v.judge.account.address],
+        })) as bigint;
+      const nonce = await readNonce();
+      const deadline = BigInt(Math.floor(Date.now() / 1000) + 3600);
+
+      const forged = await signCommit(requester, nonce, deadline);
+      const forgedCommit = await write(deployer, "commitVoteBySig", [disputeId, commitHash, v.judge.account.address, deadline, forged]);
+      console.log(forgedCommit ? "❌ Commit signed by another account accepted" : "✅ Commit with invalid signature rejected");
+
+      const stale = await signCommit(v.judge.account, nonce, 1n);
+      const staleCommit = await write(deployer, "commitVoteBySig", [disputeId, commitHash, v.judge.account.address, 1n, stale]);
+      console.log(staleCommit ? "❌ Expired signature accepted" : "✅ Expired signature rejected");
+
+      const signature = await signCommit(v.judge.account, nonce, deadline);
+      const relayed = await write(deployer, "commitVoteBySig", [disputeId, commitHash, v.judge.account.address, deadline, signature]);
+      const nonceAfter = await readNonce();
+      console.log(relayed && nonceAfter === nonce + 1n ? `✅ ${v.judge.name} committed via relayer` : `❌ Relayed commit failed`);
+
+      const replayed = await write(deployer, "commitVoteBySig", [disputeId, commitHash, v.judge.account.address, deadline, signature]);
+      console.log(replayed ? "❌ Signed commit replayed" : "✅ Signed commit replay rejected");
+      continue;
+    }
     
     try {
       const { request } = await publicClient.simulateContract({
@@ -214,15 +468,70 @@ async function runProtocolTest() {
       console.log(`⚠️  ${v.judge.name}:`, e.message.split('\n')[0]);
     }
   }
-  console.log(`\n📊 Total gas used for 5 commits: ${totalCommitGas.toLocaleString()}`);
-  console.log(`📊 Average gas per commit: ${(totalCommitGas / 5n).toLocaleString()}`);
+  const phaseAfterCommits = await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getDisputePhase",
+    args: [disputeId],
+  });
+  console.log(phaseAfterCommits === 3 ? "✅ Dispute in reveal phase" : `❌ Unexpected phase ${phaseAfterCommits}`);
+  await logProgress("After commits");
+  const extraCommit = await write(requester, "commitVote", [disputeId, generateCommitHash(disputeId, requester.address, VOTE_FOR, "extra")]);
+  console.log(extraCommit ? "❌ Sixth commit accepted" : "✅ Sixth commit rejected");
+  console.log(`\n📊 Total gas used for 4 direct commits: ${totalCommitGas.toLocaleString()}`);
+  console.log(`📊 Average gas per commit: ${(totalCommitGas / 4n).toLocaleString()}`);
+
+  // Individual votes must stay hidden until the dispute resolves
+  try {
+    await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getRevealedVotes",
+      args: [disputeId],
+    });
+    console.log("❌ getRevealedVotes should revert before resolution");
+  } catch (e: any) {
+    console.log("✅ getRevealedVotes blocked before resolution");
+  }
 
   // Step 6: Reveal Votes
   console.log("\nStep 6: Reveal Votes");
+  const readTreasury = async () =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "contractBalance",
+      args: [],
+    })) as bigint;
+  const treasuryBeforeRuling = await readTreasury();
+  const readResolved = async (id: bigint) =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "checkIfDisputeIsResolved",
+      args: [id],
+    })) as boolean;
+  // The final reveal only records the ruling; bonds, rewards and reputation land when the panel is settled
+  const settlePanel = (id: bigint) => write(deployer, "settleOriginalPanel", [id]);
   let totalGasUsed = 0n;
   for (const v of votes) {
     const wallet = createWalletClient({ account: v.judge.account, chain, transport: http() });
-    const secretBytes = Array.from(new TextEncoder().encode(v.secret));
+    const revealSecret = secretBytes(v.secret);
+    
+    // FOR reached 3 of 5 on the previous reveal, so the dispute is already decided
+    if (v === votes[4]) {
+      const lateReveal = await write(v.judge.account, "revealVotes", [disputeId, v.vote, revealSecret]);
+      const decided = await readResolved(disputeId);
+      console.log(decided && !lateReveal ? "✅ Resolved at 3-1 and late reveal rejected" : "❌ Quorum resolution failed");
+      continue;
+    }
+    
+    // A reveal that doesn't match the commit is rejected
+    if (v === votes[0]) {
+      const wrongSecret = secretBytes("wrong");
+      const mismatched = await write(v.judge.account, "revealVotes", [disputeId, v.vote, wrongSecret]);
+      console.log(mismatched ? "❌ Reveal with the wrong secret accepted" : "✅ Reveal with the wrong secret rejected");
+    }
     
     try {
       const { request } = await publicClient.simulateContract({
@@ -230,7 +539,7 @@ async function runProtocolTest() {
         address: protocolAddress,
         abi: protocolAbi,
         functionName: "revealVotes",
-        args: [disputeId, v.vote, secretBytes],
+        args: [disputeId, v.vote, revealSecret],
       });
       const hash = await wallet.writeContract(request);
       const receipt = await publicClient.waitForTransactionReceipt({ hash });
@@ -240,9 +549,34 @@ async function runProtocolTest() {
     } catch (e: any) {
       console.log(`⚠️  ${v.judge.name}:`, e.message.split('\n')[0]);
     }
+
+    // A second reveal from the same judge must not touch the tallies
+    if (v === votes[0]) {
+      const tallyBefore: any = await publicClient.readContract({
+        address: protocolAddress,
+        abi: protocolAbi,
+        functionName: "getDisputeVotes",
+        args: [disputeId],
+      });
+      const doubleReveal = await write(v.judge.account, "revealVotes", [disputeId, v.vote, revealSecret]);
+      const tallyAfter: any = await publicClient.readContract({
+        address: protocolAddress,
+        abi: protocolAbi,
+        functionName: "getDisputeVotes",
+        args: [disputeId],
+      });
+      const unchanged = tallyBefore[0] === tallyAfter[0] && tallyBefore[1] === tallyAfter[1];
+      console.log(!doubleReveal && unchanged ? "✅ Double reveal rejected" : "❌ Double reveal accepted");
+    }
+
+    // 2-1 leaves the outcome open, so the dispute waits for more reveals
+    if (v === votes[2]) {
+      console.log((await readResolved(disputeId)) ? "❌ Resolved early at 2-1" : "✅ Still open at 2-1");
+    }
   }
-  console.log(`\n📊 Total gas used for 5 reveals: ${totalGasUsed.toLocaleString()}`);
-  console.log(`📊 Average gas per reveal: ${(totalGasUsed / 5n).toLocaleString()}`);
+  await logProgress("After reveals");
+  console.log(`\n📊 Total gas used for 4 reveals: ${totalGasUsed.toLocaleString()}`);
+  console.log(`📊 Average gas per reveal: ${(totalGasUsed / 4n).toLocaleString()}`);
 
   // Step 7: Check Result
   console.log("\nStep 7: Check Results");
@@ -263,26 +597,2498 @@ async function runProtocolTest() {
     });
     console.log(`Winner: ${winner ? "REQUESTER" : "BENEFICIARY"}`);
 
+    const lateCommit = await write(judge1, "commitVote", [disputeId, generateCommitHash(disputeId, judge1.address, VOTE_FOR, "late")]);
+    console.log(lateCommit ? "❌ Commit accepted after resolution" : "✅ Commit rejected after resolution");
+
     const voteResults: any = await publicClient.readContract({
       address: protocolAddress,
       abi: protocolAbi,
       functionName: "getDisputeVotes",
       args: [disputeId],
     });
-    console.log(`Votes FOR: ${voteResults[0]}, AGAINST: ${voteResults[1]}`);
+    console.log(`Votes FOR: ${voteResults[0]}, AGAINST: ${voteResults[1]}, ABSTAIN: ${voteResults[2]}`);
+
+    const revealedVotes: any = await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getRevealedVotes",
+      args: [disputeId],
+    });
+    for (const [judge, didReveal, vote] of revealedVotes) {
+      console.log(`  ${judge}: revealed=${didReveal}, vote=${VOTE_LABELS[vote]}`);
+    }
+    if (revealedVotes.length !== votes.length) {
+      console.log(`❌ Expected ${votes.length} revealed votes, got ${revealedVotes.length}`);
+    }
   }
 
   // Step 8: Check Rewards
   console.log("\nStep 8: Judge Rewards");
+  const rewardsBeforeSettle: any = await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getJudgeInfo",
+    args: [judge1.address],
+  });
+  const panelSettled = await settlePanel(disputeId);
+  const panelResettled = await settlePanel(disputeId);
+  console.log(
+    rewardsBeforeSettle.balance === 0n && panelSettled && !panelResettled
+      ? "✅ Rewards credited when the panel is settled, and only once"
+      : "❌ Panel settlement failed",
+  );
+  const price = disputeStruct.price as bigint;
+  const rewardBps = BigInt(
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "judgeRewardBps",
+      args: [],
+    })) as number,
+  );
+  const judgePool = (price * rewardBps) / 10000n;
+  let totalPaid = 0n;
   for (const j of judges) {
     const info: any = await publicClient.readContract({
       address: protocolAddress,
       abi: protocolAbi,
-      functionName: "getJudge",
+      functionName: "getJudgeInfo",
       args: [j.account.address],
     });
-    console.log(`${j.name}: Balance=${formatUnits(info[1], 6)} USDC, Rep=${info[2]}`);
+    console.log(`${j.name}: Balance=${formatUnits(info.balance, 6)} USDC, Rep=${info.reputation}`);
+    totalPaid += info.balance;
+    const [isJudge, reputation, balance] = await Promise.all(
+      ["isJudge", "judgeReputation", "judgeBalance"].map(functionName =>
+        publicClient.readContract({ address: protocolAddress, abi: protocolAbi, functionName, args: [j.account.address] }),
+      ),
+    );
+    if (!isJudge || reputation !== info.reputation || balance !== info.balance) {
+      console.log(`❌ ${j.name}: judge views disagree with getJudgeInfo`);
+    }
+  }
+  // The three winners split the judge pool equally; the treasury keeps the protocol share
+  // plus the rounding remainder, so the two add up to the fee exactly
+  console.log(`Judge pool: ${formatUnits(judgePool, 6)} USDC, paid: ${formatUnits(totalPaid, 6)} USDC`);
+  const treasuryShare = (await readTreasury()) - treasuryBeforeRuling;
+  console.log(
+    totalPaid === (judgePool / 3n) * 3n && totalPaid + treasuryShare === price
+      ? `✅ Judges got ${formatUnits(totalPaid, 6)} USDC and the treasury ${formatUnits(treasuryShare, 6)} USDC, exactly the fee`
+      : `❌ Judges ${totalPaid} plus treasury ${treasuryShare} don't make the fee ${price}`,
+  );
+  const totalJudgeRewards = (await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "totalJudgeRewards",
+    args: [],
+  })) as bigint;
+  console.log(
+    totalJudgeRewards === totalPaid
+      ? "✅ Judge rewards ledger matches judge balances"
+      : `❌ Judge rewards ledger is ${formatUnits(totalJudgeRewards, 6)} USDC, balances sum to ${formatUnits(totalPaid, 6)} USDC`,
+  );
+
+  // The fee token is locked while judges are still owed rewards in it
+  const newToken = "0x0000000000000000000000000000000000000002";
+  const tokenSwapped = await write(deployer, "setFeeToken", [newToken]);
+  const zeroToken = await write(deployer, "setFeeToken", ["0x0000000000000000000000000000000000000000"]);
+  const feeToken = await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "feeToken",
+    args: [],
+  });
+  console.log(
+    !tokenSwapped && !zeroToken && feeToken.toLowerCase() === usdc.toLowerCase()
+      ? "✅ Fee token change blocked while claims are outstanding"
+      : "❌ Fee token changed with outstanding claims",
+  );
+  // Partial withdrawal leaves the remainder claimable
+  const readJudgeBalance = async () =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "judgeBalance",
+      args: [judge1.address],
+    })) as bigint;
+  const fullBalance = await readJudgeBalance();
+  const half = fullBalance / 2n;
+  const zeroWithdraw = await write(judge1, "judgeWithdrawAmount", [0n]);
+  const partialPaid = await write(judge1, "judgeWithdrawAmount", [half]);
+  const afterPartial = await readJudgeBalance();
+  const overWithdraw = await write(judge1, "judgeWithdrawAmount", [afterPartial + 1n]);
+  const afterOver = await readJudgeBalance();
+  console.log(
+    !zeroWithdraw && partialPaid && afterPartial === fullBalance - half
+      ? `✅ Partial withdrawal of ${formatUnits(half, 6)} USDC, ${formatUnits(afterPartial, 6)} USDC left`
+      : "❌ Partial withdrawal failed",
+  );
+  console.log(!overWithdraw && afterOver === afterPartial ? "✅ Over-withdrawal rejected, balance unchanged" : "❌ Over-withdrawal accepted");
+  const walletBeforeClaim = await readTokenBalance(judge1.address);
+  const claimPaid = await write(judge1, "judgeWithdraw", []);
+  const claimReceived = (await readTokenBalance(judge1.address)) - walletBeforeClaim;
+  console.log(
+    claimPaid && claimReceived === afterOver
+      ? "✅ Existing judge claim still payable, tokens sent to the judge"
+      : "❌ Judge claim failed after fee token attempt",
+  );
+
+  // A foreign token sent to the protocol can be rescued in full
+  const strayToken = await deployToken();
+  await tokenWrite(deployer, strayToken, "mint", [protocolAddress, 500n]);
+  const strangerRescue = await write(requester, "rescueTokens", [strayToken, requester.address, 500n]);
+  console.log(strangerRescue ? "❌ Non-owner rescued tokens" : "✅ Non-owner rescue rejected");
+  const foreignRescued = await write(deployer, "rescueTokens", [strayToken, deployer.address, 500n]);
+  console.log(
+    foreignRescued && (await readTokenBalance(protocolAddress, strayToken)) === 0n &&
+      (await readTokenBalance(deployer.address, strayToken)) === 500n
+      ? "✅ Foreign token rescued in full"
+      : "❌ Foreign token rescue failed",
+  );
+  // Fee tokens sent outside the dispute flow are rescuable, but judge rewards, escrow and treasury aren't
+  await tokenWrite(deployer, usdc, "transfer", [protocolAddress, 700n]);
+  const overRescue = await write(deployer, "rescueTokens", [usdc, deployer.address, 701n]);
+  console.log(overRescue ? "❌ Fee token over-rescue accepted" : "✅ Fee token rescue capped at the stray amount");
+  const strayRescued = await write(deployer, "rescueTokens", [usdc, deployer.address, 700n]);
+  console.log(strayRescued ? "✅ Stray fee tokens rescued" : "❌ Stray fee tokens not rescuable");
+  await checkSolvency("resolution and judge withdrawals");
+
+  // Judge views never revert for unknown addresses
+  const stranger = "0x00000000000000000000000000000000000000aa";
+  const [strangerIsJudge, strangerRep, strangerBalance] = await Promise.all(
+    ["isJudge", "judgeReputation", "judgeBalance"].map(functionName =>
+      publicClient.readContract({ address: protocolAddress, abi: protocolAbi, functionName, args: [stranger] }),
+    ),
+  );
+  console.log(
+    !strangerIsJudge && strangerRep === 0 && strangerBalance === 0n
+      ? "✅ Judge views return defaults for unknown addresses"
+      : "❌ Judge views returned data for an unknown address",
+  );
+
+  // Step 9: Defense bond
+  console.log("\nStep 9: Defense Bond");
+  const readNextDisputeId = async () =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "disputeCount",
+      args: [],
+    })) as bigint;
+  await write(deployer, "setDefenseBond", [true, 10n * 10n ** 6n, 2n]);
+
+  const bondedId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [2n, judge1.address, "Bonded dispute", 0n]);
+  const bondPosted = await write(judge1, "postDefenseBond", [bondedId]);
+  console.log(bondPosted ? "✅ Beneficiary posted the defense bond" : "❌ Posting the defense bond failed");
+
+  const defaultedId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [3n, judge1.address, "Undefended dispute", 0n]);
+  const earlyDefault = await write(requester, "defaultJudgment", [defaultedId]);
+  console.log(earlyDefault ? "❌ Default judgment before the bond window ended" : "✅ Default judgment blocked during the bond window");
+  await new Promise(resolve => setTimeout(resolve, 3000));
+  await write(requester, "defaultJudgment", [defaultedId]);
+  const defaultWinner = await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getDisputeWinner",
+    args: [defaultedId],
+  });
+  console.log(defaultWinner ? "✅ Default judgment went to the requester" : "❌ Default judgment winner mismatch");
+
+  // The bonded dispute never gets a panel: expiry refunds the fee and returns the bond
+  const readBondedEscrow = async () =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getDisputeEscrow",
+      args: [bondedId],
+    })) as bigint;
+  const bondedEscrow = await readBondedEscrow();
+  const [requesterBeforeExpiry, beneficiaryBeforeExpiry] = await Promise.all(
+    [requester, judge1].map(a => readTokenBalance(a.address)),
+  );
+  await write(deployer, "updatePanelTimeout", [1n]);
+  const bondedExpired = await write(requester, "expireDispute", [bondedId]);
+  await write(deployer, "updatePanelTimeout", [7n * 24n * 60n * 60n]);
+  const feeRefund = (await readTokenBalance(requester.address)) - requesterBeforeExpiry;
+  const bondRefund = (await readTokenBalance(judge1.address)) - beneficiaryBeforeExpiry;
+  console.log(
+    bondedExpired && bondRefund === 10n * 10n ** 6n && feeRefund + bondRefund === bondedEscrow && (await readBondedEscrow()) === 0n
+      ? "✅ Expiry returned the defense bond to the beneficiary and the fee to the requester, escrow cleared"
+      : `❌ Expiry refunded ${feeRefund} fee and ${bondRefund} bond of ${bondedEscrow} escrowed`,
+  );
+  await checkSolvency("defense bond expiry");
+  await write(deployer, "setDefenseBond", [false, 0n, 0n]);
+
+  // Step 10: Judge cooldown
+  console.log("\nStep 10: Judge Cooldown");
+  const cooldownId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [4n, judge1.address, "Cooldown dispute", 0n]);
+
+  // Judge1 was assigned in Step 4, well inside a one-hour window
+  await write(deployer, "updateJudgeCooldown", [3600n]);
+  const insideWindow = await write(judge1, "registerToVote", [cooldownId]);
+  console.log(insideWindow ? "❌ Judge joined during cooldown" : "✅ Judge on cooldown rejected");
+
+  // ...and well outside a one-second window
+  await write(deployer, "updateJudgeCooldown", [1n]);
+  const outsideWindow = await write(judge1, "registerToVote", [cooldownId]);
+  console.log(outsideWindow ? "✅ Judge joined after cooldown" : "❌ Judge rejected after cooldown");
+
+  // Judge2 is inside a one-hour window, but zero disables the cooldown
+  await write(deployer, "updateJudgeCooldown", [0n]);
+  const bypassed = await write(judge2, "registerToVote", [cooldownId]);
+  console.log(bypassed ? "✅ Zero cooldown lets judges join back to back" : "❌ Judge rejected with cooldown disabled");
+
+  // Step 11: Settlement
+  console.log("\nStep 11: Settlement");
+  const readSettlement = async (id: bigint) =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getSettlement",
+      args: [id],
+    })) as any;
+  const settledId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [5n, judge3.address, "Settled dispute", 0n]);
+
+  const outsiderSettle = await write(judge2, "settleDispute", [settledId, true]);
+  console.log(outsiderSettle ? "❌ Non-party proposed a settlement" : "✅ Non-party settlement rejected");
+
+  // Requester proposes, beneficiary counters, requester accepts the counter
+  await write(requester, "settleDispute", [settledId, true]);
+  await write(judge3, "settleDispute", [settledId, false]);
+  const [proposer, proposedOutcome, settledEarly] = await readSettlement(settledId);
+  console.log(
+    proposer === judge3.address && !proposedOutcome && !settledEarly
+      ? "✅ Counter-proposal replaced the pending one"
+      : "❌ Counter-proposal not recorded",
+  );
+  await write(requester, "settleDispute", [settledId, false]);
+  const [, , settled] = await readSettlement(settledId);
+  const settledWinner = await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getDisputeWinner",
+    args: [settledId],
+  });
+  console.log(settled && settledWinner === false ? "✅ Settlement accepted, beneficiary wins" : "❌ Settlement handshake failed");
+
+  // Settlement is closed once any vote has been revealed
+  const revealedId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [6n, judge3.address, "Partially revealed dispute", 0n]);
+  for (const j of judges) {
+    await write(j.account, "registerToVote", [revealedId]);
+    await write(j.account, "commitVote", [revealedId, generateCommitHash(revealedId, j.account.address, VOTE_FOR, "settle")]);
+  }
+  const secret = secretBytes("settle");
+  const partialReveal = await write(judge1, "revealVotes", [revealedId, VOTE_FOR, secret]);
+  await write(requester, "settleDispute", [revealedId, true]);
+  const lateSettle = await write(judge3, "settleDispute", [revealedId, true]);
+  console.log(partialReveal && !lateSettle ? "✅ Settlement blocked after a reveal" : "❌ Settlement accepted after a reveal");
+  await checkSolvency("settlement");
+
+  // Step 12: Tie re-vote
+  console.log("\nStep 12: Tie Re-vote");
+  await write(deployer, "setCategory", [1n, 1n * 10n ** 6n, 2]);
+  const tiedId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [7n, judge3.address, "Tied dispute", 1n]);
+  // `delegates` maps a seat to the judge voting it
+  const castRound = async (pair: any[], secret: string, delegates: Map<any, any> = new Map()) => {
+    const ballots = [VOTE_FOR, VOTE_AGAINST];
+    for (const [i, j] of pair.entries()) {
+      await write(j, "registerToVote", [tiedId]);
+      const voter = delegates.get(j) ?? j;
+      if (voter !== j) await write(j, "delegateVote", [tiedId, voter.address]);
+      await write(voter, "commitVote", [tiedId, generateCommitHash(tiedId, j.address, ballots[i], secret)]);
+    }
+    for (const [i, j] of pair.entries()) {
+      await write(delegates.get(j) ?? j, "revealVotes", [tiedId, ballots[i], secretBytes(secret)]);
+    }
+  };
+  const readRound = async () =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getDisputeRound",
+      args: [tiedId],
+    })) as number;
+
+  await castRound([judge1, judge2], "round0", new Map([[judge1, judge5]]));
+  const tiedPhase = await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getDisputePhase",
+    args: [tiedId],
+  });
+  console.log((await readRound()) === 1 && tiedPhase === 1 ? "✅ Tie sent the dispute to a fresh panel" : "❌ Tie did not trigger a re-vote");
+
+  const rejoined = await write(judge1, "registerToVote", [tiedId]);
+  console.log(rejoined ? "❌ Previous round judge re-joined" : "✅ Previous round judge barred from the re-vote");
+  const delegateRejoined = await write(judge5, "registerToVote", [tiedId]);
+  console.log(delegateRejoined ? "❌ Previous round delegate re-joined" : "✅ Previous round delegate barred from the re-vote");
+
+  // Round 2 ties again, and round 1's commits must not be revealable
+  for (const j of [judge3, judge4]) {
+    await write(j, "registerToVote", [tiedId]);
+  }
+  await write(judge3, "commitVote", [tiedId, generateCommitHash(tiedId, judge3.address, VOTE_FOR, "round1")]);
+  await write(judge4, "commitVote", [tiedId, generateCommitHash(tiedId, judge4.address, VOTE_AGAINST, "round1")]);
+  const staleReveal = await write(judge1, "revealVotes", [tiedId, VOTE_FOR, secretBytes("round0")]);
+  console.log(staleReveal ? "❌ Stale round 1 commit revealed" : "✅ Stale round 1 commit rejected");
+  for (const [j, ballot] of [[judge3, VOTE_FOR], [judge4, VOTE_AGAINST]] as any[]) {
+    await write(j, "revealVotes", [tiedId, ballot, secretBytes("round1")]);
+  }
+  await settlePanel(tiedId);
+  const [, , , , , , tiedResolved] = (await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getDispute",
+    args: [tiedId],
+  })) as any;
+  let noRuling = false;
+  try {
+    await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getDisputeWinner",
+      args: [tiedId],
+    });
+  } catch (e: any) {
+    noRuling = true;
+  }
+  console.log(tiedResolved && noRuling ? "✅ Final tie closed without a ruling" : "❌ Final tie was not closed without a ruling");
+
+  // Delegation conflicts: one delegated seat per delegate, no seat of their own on top, one
+  // delegate per seat, and only an uncommitted panel seat can be handed over
+  const conflictId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [150n, judge3.address, "Delegation conflicts", 1n]);
+  await write(judge1, "registerToVote", [conflictId]);
+  const conflictDelegated = await write(judge1, "delegateVote", [conflictId, judge4.address]);
+  const delegateTakesSeat = await write(judge4, "registerToVote", [conflictId]);
+  const redelegated = await write(judge1, "delegateVote", [conflictId, judge5.address]);
+  const outsiderDelegates = await write(judge5, "delegateVote", [conflictId, judge2.address]);
+  await write(judge2, "registerToVote", [conflictId]);
+  const secondSeatForDelegate = await write(judge2, "delegateVote", [conflictId, judge4.address]);
+  const selfDelegated = await write(judge2, "delegateVote", [conflictId, judge2.address]);
+  const nonJudgeDelegate = await write(judge2, "delegateVote", [conflictId, requester.address]);
+  await write(judge4, "commitVote", [conflictId, generateCommitHash(conflictId, judge1.address, VOTE_FOR, "conflict")]);
+  await write(judge2, "commitVote", [conflictId, generateCommitHash(conflictId, judge2.address, VOTE_FOR, "conflict")]);
+  const delegatedAfterCommit = await write(judge2, "delegateVote", [conflictId, judge5.address]);
+  console.log(
+    conflictDelegated && !delegateTakesSeat
+      ? "✅ Delegate can't register for a seat of their own on the same dispute"
+      : "❌ Delegate took a second seat",
+  );
+  console.log(
+    !redelegated && !secondSeatForDelegate && !outsiderDelegates
+      ? "✅ Seat delegated once, delegate holds one seat, only panel members delegate"
+      : "❌ Delegation conflict accepted",
+  );
+  console.log(
+    !selfDelegated && !nonJudgeDelegate && !delegatedAfterCommit
+      ? "✅ Self, non-judge and post-commit delegation rejected"
+      : "❌ Invalid delegation accepted",
+  );
+  await write(judge4, "revealVotes", [conflictId, VOTE_FOR, secretBytes("conflict")]);
+  await write(judge2, "revealVotes", [conflictId, VOTE_FOR, secretBytes("conflict")]);
+  await settlePanel(conflictId);
+
+  // Step 13: Judge migration
+  console.log("\nStep 13: Judge Migration");
+  const migrated = [7, -3].map((reputation, i) => ({
+    address: privateKeyToAccount(keccak256(toBytes(`${DEPLOYER_PRIVATE_KEY}migrated${i}`))).address,
+    reputation,
+  }));
+  const mismatched = await write(deployer, "importJudges", [[migrated[0].address], [1, 2]]);
+  console.log(mismatched ? "❌ Import with mismatched arrays accepted" : "✅ Import with mismatched arrays rejected");
+  const imported = await write(deployer, "importJudges", [migrated.map(m => m.address), migrated.map(m => m.reputation)]);
+  let importsMatch = imported;
+  for (const m of migrated) {
+    const info: any = await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getJudgeInfo",
+      args: [m.address],
+    });
+    importsMatch &&= info.judge_address === m.address && info.reputation === m.reputation && info.balance === 0n;
+  }
+  console.log(importsMatch ? "✅ Judges imported with their reputation" : "❌ Imported judges don't match");
+
+  await write(deployer, "lockMigration", []);
+  const lateImport = await write(deployer, "importJudges", [[requester.address], [1]]);
+  console.log(lateImport ? "❌ Import accepted after the migration was locked" : "✅ Import rejected after the migration was locked");
+
+  // Judge pool pages back every registered and imported judge once
+  const readJudgeCount = async () =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "judgeCount",
+      args: [],
+    })) as bigint;
+  const judgeCount = await readJudgeCount();
+  await write(judge1, "registerAsJudge", []);
+  const pool: string[] = [];
+  for (let offset = 0n; offset < judgeCount; offset += 3n) {
+    const page = (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getJudges",
+      args: [offset, 3n],
+    })) as string[];
+    pool.push(...page);
+  }
+  const expectedPool = [...judges.map(j => j.account.address), deployer.address, ...migrated.map(m => m.address)];
+  const poolMatches =
+    (await readJudgeCount()) === judgeCount &&
+    pool.length === expectedPool.length &&
+    expectedPool.every(address => pool.includes(address));
+  console.log(poolMatches ? `✅ Paged through ${pool.length} registered judges` : "❌ Judge pool listing mismatch");
+
+  // Step 14: Panel size changes never touch open disputes
+  console.log("\nStep 14: Panel Size Updates");
+  const readRequired = async (id: bigint) => ((await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getDisputeProgress",
+    args: [id],
+  })) as any)[3];
+  const requiredBefore = await readRequired(cooldownId);
+  const oversized = await write(deployer, "updateNumberOfVotes", [26]);
+  console.log(oversized ? "❌ Panel size above the cap accepted" : "✅ Panel size above the cap rejected");
+  await write(deployer, "setStrictMajority", [true]);
+  const evenSize = await write(deployer, "updateNumberOfVotes", [4]);
+  const oddSize = await write(deployer, "updateNumberOfVotes", [3]);
+  console.log(!evenSize && oddSize ? "✅ Strict majority only accepts odd panel sizes" : "❌ Strict majority check failed");
+  const requiredAfter = await readRequired(cooldownId);
+  console.log(requiredBefore === requiredAfter ? "✅ Open dispute kept its panel size" : "❌ Open dispute panel size changed");
+  await write(deployer, "updateNumberOfVotes", [5]);
+  await write(deployer, "setStrictMajority", [false]);
+
+  // Step 15: Reward tiers
+  console.log("\nStep 15: Reward Tiers");
+  // Judge1 won the main dispute (rep 1 → 150%), Judge5 abstained (rep 0 → 100%)
+  await write(deployer, "setRewardTiers", [[1, 5], [15000, 20000]]);
+  const readBalance = async (account: any) =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "judgeBalance",
+      args: [account.address],
+    })) as bigint;
+  const tieredId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [8n, judge3.address, "Tiered dispute", 1n]);
+  const tierPanel = [judge5, judge1];
+  const balancesBefore = await Promise.all(tierPanel.map(readBalance));
+  for (const j of tierPanel) {
+    await write(j, "registerToVote", [tieredId]);
+    await write(j, "commitVote", [tieredId, generateCommitHash(tieredId, j.address, VOTE_FOR, "tiers")]);
+  }
+  for (const j of tierPanel) {
+    await write(j, "revealVotes", [tieredId, VOTE_FOR, secretBytes("tiers")]);
+  }
+  await settlePanel(tieredId);
+  const balancesAfter = await Promise.all(tierPanel.map(readBalance));
+  const tierPool = (1n * 10n ** 6n * rewardBps) / 10000n;
+  const expectedShares = [(tierPool * 10000n) / 25000n, (tierPool * 15000n) / 25000n];
+  const shares = balancesAfter.map((balance, i) => balance - balancesBefore[i]);
+  const sharesMatch = shares.every((share, i) => share === expectedShares[i]);
+  const conserved = shares[0] + shares[1] <= tierPool;
+  console.log(
+    sharesMatch && conserved
+      ? `✅ Tiered shares paid: ${shares.map(share => formatUnits(share, 6)).join(" / ")} USDC`
+      : `❌ Tiered shares ${shares.join(", ")} expected ${expectedShares.join(", ")}`,
+  );
+  // Judge1 crosses a tier while seated: the dispute pays at the reputation it joined with
+  const readRep = async (account: any) =>
+    Number(
+      await publicClient.readContract({
+        address: protocolAddress,
+        abi: protocolAbi,
+        functionName: "judgeReputation",
+        args: [account.address],
+      }),
+    );
+  const seatedRep = await readRep(judge1);
+  await write(deployer, "setRewardTiers", [[Math.max(seatedRep, await readRep(judge5)) + 1], [20000]]);
+  const repSnapshotId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [9n, judge3.address, "Reputation snapshot", 1n]);
+  for (const j of tierPanel) {
+    await write(j, "registerToVote", [repSnapshotId]);
+    await write(j, "commitVote", [repSnapshotId, generateCommitHash(repSnapshotId, j.address, VOTE_FOR, "rep-snapshot")]);
+  }
+  const promotionId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [10n, judge3.address, "Promotion", 1n]);
+  for (const j of [judge1, judge2]) {
+    await write(j, "registerToVote", [promotionId]);
+    await write(j, "commitVote", [promotionId, generateCommitHash(promotionId, j.address, VOTE_FOR, "promotion")]);
+  }
+  for (const j of [judge1, judge2]) {
+    await write(j, "revealVotes", [promotionId, VOTE_FOR, secretBytes("promotion")]);
+  }
+  await settlePanel(promotionId);
+  const promotedRep = await readRep(judge1);
+  const snapshotBalancesBefore = await Promise.all(tierPanel.map(readBalance));
+  for (const j of tierPanel) {
+    await write(j, "revealVotes", [repSnapshotId, VOTE_FOR, secretBytes("rep-snapshot")]);
+  }
+  await settlePanel(repSnapshotId);
+  const snapshotShares = (await Promise.all(tierPanel.map(readBalance))).map((b, i) => b - snapshotBalancesBefore[i]);
+  const repSnapshotPanel = (await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getDisputePanel",
+    args: [repSnapshotId],
+  })) as any[];
+  console.log(
+    promotedRep === seatedRep + 1 &&
+      repSnapshotPanel.some(([judge, rep]: any[]) => judge === judge1.address && rep === seatedRep) &&
+      snapshotShares.every(share => share === tierPool / 2n)
+      ? `✅ Reputation rose to ${promotedRep} mid-dispute, reward paid at the snapshot of ${seatedRep}`
+      : `❌ Snapshot ignored: shares ${snapshotShares.join(", ")}, reputation ${seatedRep} → ${promotedRep}`,
+  );
+  await write(deployer, "setRewardTiers", [[], []]);
+
+  // Step 16: Deal lookup
+  console.log("\nStep 16: Deal Lookup");
+  const lookupDeal = async (creator: string, dealId: bigint) =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getDisputeIdForDeal",
+      args: [creator, dealId],
+    })) as bigint;
+  console.log((await lookupDeal(requester.address, 1n)) === disputeId ? "✅ Direct dispute found by deal id" : "❌ Direct dispute lookup failed");
+
+  // Two marketplaces can reuse the same deal number; Judge4 and Judge5 stand in for them
+  const marketplaceA = judge4;
+  const marketplaceB = judge5;
+  const firstDealId = await readNextDisputeId();
+  await write(marketplaceA, "createDispute", [100n, requester.address, "Marketplace A deal", 0n]);
+  await write(marketplaceB, "createDispute", [100n, requester.address, "Marketplace B deal", 0n]);
+  const [dealA, dealB] = [await lookupDeal(marketplaceA.address, 100n), await lookupDeal(marketplaceB.address, 100n)];
+  console.log(
+    dealA === firstDealId && dealB === firstDealId + 1n
+      ? "✅ Marketplaces sharing a deal id map to separate disputes"
+      : `❌ Deal lookups returned ${dealA} and ${dealB}`,
+  );
+  const duplicateDeal = await write(marketplaceA, "createDispute", [100n, requester.address, "Same deal again", 0n]);
+  console.log(duplicateDeal ? "❌ Second dispute for the same deal accepted" : "✅ Second dispute for the same deal rejected");
+  console.log((await lookupDeal(marketplaceA.address, 999n)) === 0n ? "✅ Unknown deal returns 0" : "❌ Unknown deal lookup returned an id");
+
+  // Step 17: Resolution records
+  console.log("\nStep 17: Resolution Records");
+  const readInfo = async (id: bigint) =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getDisputeInfo",
+      args: [id],
+    })) as any;
+  // 1 ruling, 2 no ruling, 3 default judgment, 4 settlement, 5 expired
+  const expectedKinds: [bigint, number, string][] = [
+    [disputeId, 1, "ruling"],
+    [tiedId, 2, "no ruling"],
+    [defaultedId, 3, "default judgment"],
+    [settledId, 4, "settlement"],
+    [cooldownId, 0, "pending"],
+  ];
+  for (const [id, kind, label] of expectedKinds) {
+    const info = await readInfo(id);
+    const stamped = kind === 0 ? info.resolved_at === 0n : info.resolved_at >= info.created_at && info.resolved_at > 0n;
+    console.log(
+      info.resolution_kind === kind && stamped
+        ? `✅ Dispute ${id} recorded as ${label}`
+        : `❌ Dispute ${id}: kind=${info.resolution_kind}, resolvedAt=${info.resolved_at}`,
+    );
+  }
+  const resolvedAtBefore = (await readInfo(settledId)).resolved_at;
+  await write(requester, "settleDispute", [settledId, true]);
+  await write(requester, "defaultJudgment", [settledId]);
+  console.log(
+    (await readInfo(settledId)).resolved_at === resolvedAtBefore
+      ? "✅ Resolution timestamp is only set once"
+      : "❌ Resolution timestamp changed after resolution",
+  );
+
+  // Step 18: Treasury withdrawal timelock
+  console.log("\nStep 18: Treasury Withdrawal");
+  const treasury = (await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "contractBalance",
+    args: [],
+  })) as bigint;
+  const owedToJudges = (await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "totalJudgeRewards",
+    args: [],
+  })) as bigint;
+  const shortDelay = await write(deployer, "updateWithdrawDelay", [60n]);
+  console.log(shortDelay ? "❌ Withdraw delay below 24h accepted" : "✅ Withdraw delay below 24h rejected");
+  const queued = await write(deployer, "queueWithdrawal", []);
+  const [queuedAmount, unlockTime]: any = await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getQueuedWithdrawal",
+    args: [],
+  });
+  console.log(
+    queued && queuedAmount === treasury
+      ? `✅ Queued ${formatUnits(queuedAmount, 6)} USDC (judges still owed ${formatUnits(owedToJudges, 6)} USDC), unlocks at ${unlockTime}`
+      : "❌ Queued withdrawal doesn't match the treasury",
+  );
+  const earlyExecute = await write(deployer, "executeWithdrawal", []);
+  console.log(earlyExecute ? "❌ Withdrawal executed before the delay" : "✅ Withdrawal blocked until the delay passes");
+  const cancelled = await write(deployer, "cancelWithdrawal", []);
+  const afterCancel = await write(deployer, "executeWithdrawal", []);
+  console.log(cancelled && !afterCancel ? "✅ Queued withdrawal cancelled" : "❌ Withdrawal cancel failed");
+  // Executing after the 24h delay needs a node that can move its clock (anvil can, the
+  // Nitro devnode can't); elsewhere the case is skipped rather than shortening the delay
+  await write(deployer, "queueWithdrawal", []);
+  const advancedClock = await (async () => {
+    try {
+      await publicClient.request({ method: "evm_increaseTime", params: [24 * 60 * 60 + 1] } as any);
+      await publicClient.request({ method: "evm_mine", params: [] } as any);
+      return true;
+    } catch {
+      return false;
+    }
+  })();
+  if (advancedClock) {
+    const ownerWalletBefore = await readTokenBalance(deployer.address);
+    const executed = await write(deployer, "executeWithdrawal", []);
+    const ownerReceived = (await readTokenBalance(deployer.address)) - ownerWalletBefore;
+    const [withdrawnLog]: any[] = await publicClient.getContractEvents({
+      address: protocolAddress,
+      abi: protocolAbi,
+      eventName: "OwnerWithdrawn",
+      fromBlock: 0n,
+    });
+    const treasuryAfter = (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "contractBalance",
+      args: [],
+    })) as bigint;
+    const owedAfter = (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "totalJudgeRewards",
+      args: [],
+    })) as bigint;
+    console.log(
+      executed && withdrawnLog?.args.amount === treasury && ownerReceived === treasury && treasuryAfter === 0n && owedAfter === owedToJudges
+        ? "✅ Withdrawal executed after the delay, limited to the surplus"
+        : "❌ Withdrawal after the delay failed or touched judge funds",
+    );
+  } else {
+    await write(deployer, "cancelWithdrawal", []);
+    console.log("⚠️  Node can't advance time, withdrawal after the delay not tested");
+  }
+  await checkSolvency("the treasury withdrawal");
+
+  // Step 19: Quorum at 3-0
+  console.log("\nStep 19: Quorum Resolution");
+  // The partially revealed dispute from Step 11 has five FOR commits and one reveal
+  const settleSecret = secretBytes("settle");
+  await write(judge2, "revealVotes", [revealedId, VOTE_FOR, settleSecret]);
+  await write(judge3, "revealVotes", [revealedId, VOTE_FOR, settleSecret]);
+  const quorumReached = await readResolved(revealedId);
+  const straggler = await write(judge4, "revealVotes", [revealedId, VOTE_FOR, settleSecret]);
+  await settlePanel(revealedId);
+  console.log(quorumReached && !straggler ? "✅ Resolved at 3-0, straggler reveal rejected" : "❌ Quorum at 3-0 failed");
+
+  // Step 20: Fee-on-transfer accounting
+  console.log("\nStep 20: Received Amount Accounting");
+  // A 1% transfer fee means less than the category price arrives
+  await tokenWrite(deployer, usdc, "setFeeBps", [100n]);
+  const feeId = await readNextDisputeId();
+  const heldBeforeFee = await readTokenBalance(protocolAddress);
+  await write(requester, "createDisputeDirect", [2020n, judge3.address, "Fee-on-transfer", 1n]);
+  const feeReceived = (await readTokenBalance(protocolAddress)) - heldBeforeFee;
+  await tokenWrite(deployer, usdc, "setFeeBps", [0n]);
+  const feeFinancials: any = await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getDisputeFinancials",
+    args: [feeId],
+  });
+  console.log(
+    feeFinancials.amount_received < feeFinancials.nominal_price &&
+      feeFinancials.amount_received === feeReceived &&
+      feeFinancials.escrow_remaining === feeReceived &&
+      (await readInfo(feeId)).price === feeReceived
+      ? `✅ Escrowed ${formatUnits(feeReceived, 6)} USDC received, not the ${formatUnits(feeFinancials.nominal_price, 6)} USDC price`
+      : "❌ Escrow doesn't match the amount received",
+  );
+  // The full cycle pays judges out of what was received and leaves the protocol solvent
+  for (const j of [judge1, judge2]) {
+    await write(j, "registerToVote", [feeId]);
+    await write(j, "commitVote", [feeId, generateCommitHash(feeId, j.address, VOTE_FOR, "fee-token")]);
+  }
+  for (const j of [judge1, judge2]) {
+    await write(j, "revealVotes", [feeId, VOTE_FOR, secretBytes("fee-token")]);
+  }
+  await settlePanel(feeId);
+  for (const j of [judge1, judge2]) {
+    await write(j, "judgeWithdraw", []);
+  }
+  await checkSolvency("a fee-on-transfer cycle");
+
+  // Step 21: Dispute outcomes
+  console.log("\nStep 21: Dispute Outcomes");
+  // 0 pending, 1 requester won, 2 beneficiary won, 3 no ruling, 4 tied, 5 expired
+  const expectedOutcomes: [bigint, number, string][] = [
+    [revealedId, 1, "requester won"],
+    [defaultedId, 1, "requester won by default"],
+    [settledId, 2, "beneficiary won by settlement"],
+    [tiedId, 4, "tied"],
+    [cooldownId, 0, "pending"],
+  ];
+  for (const [id, outcome, label] of expectedOutcomes) {
+    const actual = await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getDisputeOutcome",
+      args: [id],
+    });
+    console.log(actual === outcome ? `✅ Dispute ${id} outcome: ${label}` : `❌ Dispute ${id}: outcome=${actual}, expected ${outcome}`);
+  }
+  let tiedError = "";
+  try {
+    await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getDisputeWinner",
+      args: [tiedId],
+    });
+  } catch (e: any) {
+    tiedError = e.message ?? "";
+  }
+  console.log(tiedError.includes("DisputeTied") ? "✅ Legacy winner view reverts with DisputeTied" : "❌ Tied dispute didn't revert with DisputeTied");
+  // Expired: a panel timeout short enough to lapse during the run
+  const outcomeExpiredId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [30n, judge3.address, "Expired outcome", 1n]);
+  await write(deployer, "updatePanelTimeout", [1n]);
+  await new Promise(resolve => setTimeout(resolve, 3000));
+  await write(requester, "expireDispute", [outcomeExpiredId]);
+  await write(deployer, "updatePanelTimeout", [7n * 24n * 60n * 60n]);
+  // No ruling: the whole panel abstains
+  const outcomeAbstainId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [31n, judge3.address, "All-abstain outcome", 1n]);
+  for (const j of [judge1, judge2]) {
+    await write(j, "registerToVote", [outcomeAbstainId]);
+    await write(j, "commitVote", [outcomeAbstainId, generateCommitHash(outcomeAbstainId, j.address, VOTE_ABSTAIN, "abstain")]);
+  }
+  for (const j of [judge1, judge2]) {
+    await write(j, "revealVotes", [outcomeAbstainId, VOTE_ABSTAIN, secretBytes("abstain")]);
+  }
+  await settlePanel(outcomeAbstainId);
+  for (const [id, outcome, label, error] of [
+    [outcomeExpiredId, 5, "expired", "DisputeIsExpired"],
+    [outcomeAbstainId, 3, "no ruling", "NoRuling"],
+  ] as [bigint, number, string, string][]) {
+    const actual = await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getDisputeOutcome",
+      args: [id],
+    });
+    let winnerError = "";
+    try {
+      await publicClient.readContract({ address: protocolAddress, abi: protocolAbi, functionName: "getDisputeWinner", args: [id] });
+    } catch (e: any) {
+      winnerError = e.message ?? "";
+    }
+    console.log(
+      actual === outcome && winnerError.includes(error)
+        ? `✅ Dispute ${id} outcome: ${label}, legacy winner view reverts with ${error}`
+        : `❌ Dispute ${id}: outcome=${actual}, expected ${outcome} and ${error}`,
+    );
+  }
+
+  // Step 22: Event metadata
+  console.log("\nStep 22: Event Metadata");
+  const [createdLog]: any[] = await publicClient.getContractEvents({
+    address: protocolAddress,
+    abi: protocolAbi,
+    eventName: "DisputeCreated",
+    args: { dispute_id: settledId },
+    fromBlock: 0n,
+  });
+  console.log(
+    createdLog?.args.deal_id === 5n && createdLog.args.beneficiary === judge3.address && createdLog.args.requester === requester.address
+      ? "✅ DisputeCreated carries the deal id and both parties"
+      : "❌ DisputeCreated metadata mismatch",
+  );
+  // Deal ids are u64 end to end; the legacy u32 getDispute field reads zero when one doesn't fit
+  const wideDealId = 2n ** 40n + 7n;
+  const wideDealDispute = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [wideDealId, judge3.address, "Wide deal id", 1n]);
+  const wideInfo: any = await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getDisputeInfo",
+    args: [wideDealDispute],
+  });
+  const [legacyDealId]: any = await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getDispute",
+    args: [wideDealDispute],
+  });
+  console.log(
+    wideInfo.deal_id === wideDealId && legacyDealId === 0
+      ? "✅ Deal id above 2^32 stored and returned in full"
+      : `❌ Wide deal id read back as ${wideInfo.deal_id} / ${legacyDealId}`,
+  );
+  await write(deployer, "updatePanelTimeout", [1n]);
+  await new Promise(resolve => setTimeout(resolve, 3000));
+  await write(requester, "expireDispute", [wideDealDispute]);
+  await write(deployer, "updatePanelTimeout", [7n * 24n * 60n * 60n]);
+  const [resolvedLog]: any[] = await publicClient.getContractEvents({
+    address: protocolAddress,
+    abi: protocolAbi,
+    eventName: "DisputeResolved",
+    args: { dispute_id: revealedId },
+    fromBlock: 0n,
+  });
+  console.log(
+    resolvedLog?.args.requester_won === true && resolvedLog.args.votes_for === 3 && resolvedLog.args.votes_against === 0
+      ? `✅ DisputeResolved carries the winner ${resolvedLog.args.winner} and a 3-0 tally`
+      : "❌ DisputeResolved metadata mismatch",
+  );
+  const [settledLog]: any[] = await publicClient.getContractEvents({
+    address: protocolAddress,
+    abi: protocolAbi,
+    eventName: "DisputeResolved",
+    args: { dispute_id: settledId },
+    fromBlock: 0n,
+  });
+  console.log(
+    settledLog?.args.requester_won === false && settledLog.args.winner === judge3.address
+      ? "✅ Settlement emits DisputeResolved for the beneficiary"
+      : "❌ Settlement DisputeResolved mismatch",
+  );
+
+  // Step 23: Per-dispute escrow
+  console.log("\nStep 23: Per-Dispute Escrow");
+  // Every dispute created in this run is checked: closed ones hold nothing and the
+  // open ones add up to the global ledger
+  const disputeTotal = await readNextDisputeId();
+  let escrowSum = 0n;
+  const leaked: bigint[] = [];
+  for (let id = 1n; id < disputeTotal; id++) {
+    const [escrow, outcome] = (await Promise.all(
+      ["getDisputeEscrow", "getDisputeOutcome"].map(functionName =>
+        publicClient.readContract({ address: protocolAddress, abi: protocolAbi, functionName, args: [id] }),
+      ),
+    )) as [bigint, number];
+    escrowSum += escrow;
+    if (outcome !== 0 && escrow !== 0n) leaked.push(id);
+  }
+  const globalEscrow = (await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "totalEscrowed",
+    args: [],
+  })) as bigint;
+  console.log(leaked.length === 0 ? "✅ Closed disputes hold no escrow" : `❌ Closed disputes still holding escrow: ${leaked.join(", ")}`);
+  console.log(
+    escrowSum === globalEscrow
+      ? `✅ Open disputes hold ${formatUnits(escrowSum, 6)} USDC, matching totalEscrowed`
+      : `❌ Dispute escrow sums to ${formatUnits(escrowSum, 6)} USDC, totalEscrowed is ${formatUnits(globalEscrow, 6)} USDC`,
+  );
+
+  // Step 24: Versioning and migration
+  console.log("\nStep 24: Versioning");
+  const readStorageVersion = async () =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "storageVersion",
+      args: [],
+    })) as number;
+  const contractVersion = await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "version",
+    args: [],
+  });
+  const versionBefore = await readStorageVersion();
+  const strangerMigrate = await write(requester, "migrate", [1n, 100n]);
+  const ownerMigrate = await write(deployer, "migrate", [1n, 100n]);
+  const versionAfter = await readStorageVersion();
+  console.log(`Contract ${contractVersion}, storage v${versionBefore}`);
+  console.log(strangerMigrate ? "❌ Non-owner ran the migration" : "✅ Non-owner migration rejected");
+  console.log(
+    ownerMigrate && versionBefore === 2 && versionAfter === versionBefore
+      ? "✅ Migration is a no-op on current storage"
+      : "❌ Migration changed current storage",
+  );
+
+  // Deploy a raw runtime of up to 255 bytes behind a copy-and-return init prefix
+  const deployMock = async (runtime: string) => {
+    const size = (runtime.length / 2).toString(16).padStart(2, "0");
+    const hash = await deployerWalletFund.sendTransaction({ data: `0x60${size}600c60003960${size}6000f3${runtime}` });
+    return (await publicClient.waitForTransactionReceipt({ hash })).contractAddress!;
+  };
+  // Old storage is rebuilt behind a minimal EVM proxy that delegates to this deployment and
+  // adds poke(bytes32 slot, bytes32 value) (selector 0x000be3b5) for writing raw slots
+  const proxy = await deployMock(
+    `63000be3b560003560e01c14610047573660006000376000600036600073${protocolAddress.slice(2)}5af43d600060003e610042573d6000fd5b3d6000f35b6024356004355500`,
+  );
+  const proxyWrite = async (account: any, functionName: string, args: any[]) => {
+    const wallet = createWalletClient({ account, chain, transport: http() });
+    try {
+      const { request } = await publicClient.simulateContract({ account, address: proxy, abi: protocolAbi, functionName, args });
+      const hash = await wallet.writeContract(request);
+      await publicClient.waitForTransactionReceipt({ hash });
+      return true;
+    } catch (e: any) {
+      return false;
+    }
+  };
+  const proxyRead = async (functionName: string, args: any[]) =>
+    (await publicClient.readContract({ address: proxy, abi: protocolAbi, functionName, args })) as any;
+  const poke = async (slot: bigint, value: bigint) => {
+    const data = `0x000be3b5${toHex(slot, { size: 32 }).slice(2)}${toHex(value, { size: 32 }).slice(2)}` as `0x${string}`;
+    const hash = await deployerWalletFund.sendTransaction({ to: proxy, data });
+    await publicClient.waitForTransactionReceipt({ hash });
+  };
+  // Slots follow the Solidity layout of ProtocolContract: dispute_count and number_of_votes
+  // share slot 5, storage_version sits in slot 23 behind reward_tier_count, disputes is slot 16
+  const disputeSlot = (id: bigint, field: bigint) =>
+    BigInt(keccak256(encodePacked(["uint256", "uint256"], [id, 16n]))) + field;
+  await proxyWrite(deployer, "init", [deployer.address, usdc]);
+  // The proxy's storage has no routes yet; give it the router's
+  const routes = new Map<string, string[]>();
+  for (const item of protocolAbi.filter((item: any) => item.type === "function")) {
+    const selector = getFunctionSelector(item);
+    const facet = (await publicClient.readContract({ address: protocolAddress, abi: protocolAbi, functionName: "facetAddress", args: [selector] })) as string;
+    if (facet !== "0x0000000000000000000000000000000000000000") routes.set(facet, [...(routes.get(facet) ?? []), selector]);
+  }
+  for (const [facet, selectors] of routes) await proxyWrite(deployer, "setFacet", [facet, selectors]);
+
+  // Three disputes as a version 1 deployment left them: no nominal price and no
+  // waiting-for-judges index. 1 waits for judges, 2 is resolved, 3 waits with a posted defense bond
+  const createdAt = BigInt(Math.floor(Date.now() / 1000));
+  const oldPrice = 50n * 10n ** 6n;
+  const oldBond = 10n * 10n ** 6n;
+  await poke(5n, 4n | (5n << 64n));
+  for (const id of [1n, 2n, 3n]) {
+    await poke(disputeSlot(id, 0n), id);
+    await poke(disputeSlot(id, 1n), oldPrice);
+    await poke(disputeSlot(id, 3n), createdAt << 8n);
+    await poke(disputeSlot(id, 6n), BigInt(requester.address));
+  }
+  await poke(disputeSlot(1n, 24n), 1n << 24n); // waiting_for_judges
+  await poke(disputeSlot(2n, 24n), 3n | (1n << 40n)); // 3 votes for, resolved
+  await poke(disputeSlot(3n, 24n), 1n << 24n);
+  await poke(disputeSlot(3n, 4n), oldBond);
+  await poke(disputeSlot(3n, 5n), 1n << 64n); // bond_posted
+
+  // Version 0 is the unversioned original layout, which can't be upgraded in place
+  await poke(23n, 0n);
+  const v0Migrated = await proxyWrite(deployer, "migrate", [1n, 10n]);
+  console.log(
+    !v0Migrated && (await proxyRead("storageVersion", [])) === 0
+      ? "✅ Version 0 storage rejected, it needs a fresh deployment"
+      : "❌ Version 0 storage was migrated",
+  );
+
+  await poke(23n, 1n);
+  const strangerPage = await proxyWrite(requester, "migrate", [1n, 2n]);
+  const gapPage = await proxyWrite(deployer, "migrate", [3n, 1n]);
+  const firstMigrationPage = await proxyWrite(deployer, "migrate", [1n, 2n]);
+  const midVersion = await proxyRead("storageVersion", []);
+  const midCursor = await proxyRead("migrationCursor", []);
+  console.log(!strangerPage && !gapPage ? "✅ Non-owner page and a page past the cursor rejected" : "❌ Migration page checks failed");
+  console.log(
+    firstMigrationPage && midVersion === 1 && midCursor === 3n
+      ? "✅ First migration page left the cursor at dispute 3"
+      : `❌ Paged migration: after page 1 v${midVersion} cursor ${midCursor}`,
+  );
+
+  const v2Migrated = await proxyWrite(deployer, "migrate", [3n, 10n]);
+  const v2Version = await proxyRead("storageVersion", []);
+  const nominal = (await proxyRead("getDisputeFinancials", [1n])).nominal_price;
+  const recruiting = await proxyRead("openForRegistrationCount", []);
+  const repeated = await proxyWrite(deployer, "migrate", [1n, 10n]);
+  console.log(
+    v2Migrated && v2Version === 2 && nominal === oldPrice && recruiting === 2n
+      ? "✅ Version 1 -> 2 ran in two pages, backfilled the nominal price and listed both disputes waiting for judges"
+      : `❌ v1 -> v2: version ${v2Version}, nominal ${nominal}, recruiting ${recruiting}`,
+  );
+  console.log(
+    repeated && (await proxyRead("storageVersion", [])) === 2 && (await proxyRead("openForRegistrationCount", [])) === 2n
+      ? "✅ Migrating current storage is a no-op"
+      : "❌ Repeated migration changed storage",
+  );
+
+  // Step 25: Judge replacement
+  console.log("\nStep 25: Judge Replacement");
+  const readReputation = async (account: any) =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "judgeReputation",
+      args: [account.address],
+    })) as number;
+  const replacedId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [20n, judge3.address, "Abandoned seat", 1n]);
+  await write(judge1, "registerToVote", [replacedId]);
+  await write(judge2, "registerToVote", [replacedId]);
+  await write(judge1, "commitVote", [replacedId, generateCommitHash(replacedId, judge1.address, VOTE_FOR, "replace")]);
+  const repBefore = await readReputation(judge2);
+  const committedSwap = await write(deployer, "replaceJudge", [replacedId, judge1.address, judge5.address]);
+  const strangerSwap = await write(requester, "replaceJudge", [replacedId, judge2.address, judge5.address]);
+  const panelSwap = await write(deployer, "replaceJudge", [replacedId, judge2.address, judge1.address]);
+  const swapped = await write(deployer, "replaceJudge", [replacedId, judge2.address, judge5.address]);
+  const repAfter = await readReputation(judge2);
+  console.log(committedSwap ? "❌ Judge replaced after committing" : "✅ Replacement rejected once the judge committed");
+  console.log(!strangerSwap && !panelSwap ? "✅ Non-owner and already-seated replacements rejected" : "❌ Invalid replacement accepted");
+  console.log(swapped && repAfter === repBefore - 1 ? "✅ Idle judge replaced and penalized" : "❌ Idle judge replacement failed");
+  const rejoin = await write(judge2, "registerToVote", [replacedId]);
+  await write(judge5, "commitVote", [replacedId, generateCommitHash(replacedId, judge5.address, VOTE_FOR, "replace")]);
+  for (const j of [judge1, judge5]) {
+    await write(j, "revealVotes", [replacedId, VOTE_FOR, secretBytes("replace")]);
+  }
+  await settlePanel(replacedId);
+  console.log(
+    !rejoin && (await readResolved(replacedId))
+      ? "✅ Replacement judge committed and revealed, replaced judge can't re-join"
+      : "❌ Dispute with a replaced judge didn't resolve",
+  );
+
+  // Step 26: Reputation oracle
+  console.log("\nStep 26: Reputation Oracle");
+  // Minimal mock oracles as raw bytecode: one always returns 300, one always reverts
+  const highOracle = await deployMock("61012c60005260206000f3");
+  const brokenOracle = await deployMock("60006000fd");
+  const readStanding = async (account: any) =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "effectiveReputation",
+      args: [account.address],
+    })) as number;
+  const oracleId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [21n, judge3.address, "Oracle dispute", 1n]);
+  await write(deployer, "updateMinReputation", [50]);
+  const internalJoin = await write(judge3, "registerToVote", [oracleId]);
+  const strangerOracle = await write(requester, "setReputationOracle", [highOracle]);
+  await write(deployer, "setReputationOracle", [highOracle]);
+  const oracleStanding = await readStanding(judge3);
+  const oracleJoin = await write(judge3, "registerToVote", [oracleId]);
+  console.log(strangerOracle ? "❌ Non-owner set the oracle" : "✅ Non-owner oracle change rejected");
+  console.log(
+    !internalJoin && oracleStanding === 300 && oracleJoin
+      ? "✅ Oracle reputation used for eligibility"
+      : `❌ Oracle eligibility failed (standing ${oracleStanding})`,
+  );
+  // Standings beyond the int8 range are snapshotted as-is
+  const oraclePanel = (await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getDisputePanel",
+    args: [oracleId],
+  })) as any[];
+  console.log(
+    oraclePanel.some(([judge, rep]: any[]) => judge === judge3.address && rep === 300)
+      ? "✅ Snapshot keeps the full oracle standing"
+      : "❌ Snapshot truncated the oracle standing",
+  );
+  await write(deployer, "setReputationOracle", [brokenOracle]);
+  const fallbackStanding = await readStanding(judge4);
+  const fallbackJoin = await write(judge4, "registerToVote", [oracleId]);
+  console.log(
+    fallbackStanding === (await readReputation(judge4)) && !fallbackJoin
+      ? "✅ Failing oracle falls back to internal reputation"
+      : "❌ Failing oracle wasn't ignored",
+  );
+  await write(deployer, "setReputationOracle", ["0x0000000000000000000000000000000000000000"]);
+  await write(deployer, "updateMinReputation", [0]);
+
+  // Step 27: Interface detection
+  console.log("\nStep 27: Interface Detection");
+  const supports = async (interfaceId: string) =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "supportsInterface",
+      args: [interfaceId],
+    })) as boolean;
+  const interfaceId = await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "disputeInterfaceId",
+    args: [],
+  });
+  console.log(
+    interfaceId === DISPUTE_INTERFACE_ID
+      ? `✅ Dispute interface id is ${interfaceId}`
+      : `❌ Dispute interface id changed to ${interfaceId}, expected ${DISPUTE_INTERFACE_ID}`,
+  );
+  const [disputeSupported, erc165Supported, unknownSupported] = await Promise.all(
+    [DISPUTE_INTERFACE_ID, ERC165_INTERFACE_ID, "0xffffffff"].map(supports),
+  );
+  console.log(
+    disputeSupported && erc165Supported && !unknownSupported
+      ? "✅ supportsInterface recognizes the dispute and ERC-165 ids only"
+      : "❌ supportsInterface answered incorrectly",
+  );
+
+  // Step 28: Sponsored disputes
+  console.log("\nStep 28: Sponsored Disputes");
+  const sponsor = judge4;
+  const readRequesterNonce = async () =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "nonces",
+      args: [requester.address],
+    })) as bigint;
+  const signSponsor = (account: any, dealId: bigint, nonce: bigint, deadline: bigint) =>
+    account.signTypedData({
+      domain: { name: "Lancer Protocol", version: "1", chainId: chain.id, verifyingContract: protocolAddress },
+      types: {
+        Sponsor: [
+          { name: "dealId", type: "uint64" },
+          { name: "beneficiary", type: "address" },
+          { name: "categoryId", type: "uint64" },
+          { name: "sponsor", type: "address" },
+          { name: "nonce", type: "uint256" },
+          { name: "deadline", type: "uint256" },
+        ],
+      },
+      primaryType: "Sponsor",
+      message: { dealId, beneficiary: judge3.address, categoryId: 1n, sponsor: sponsor.address, nonce, deadline },
+    });
+  const sponsorDeadline = BigInt(Math.floor(Date.now() / 1000) + 3600);
+  const unconsented = await write(sponsor, "createDisputeFor", [30n, requester.address, judge3.address, "Sponsored", 1n, sponsorDeadline, "0x"]);
+  const forgedConsent = await signSponsor(judge2, 30n, await readRequesterNonce(), sponsorDeadline);
+  const forgedSponsor = await write(sponsor, "createDisputeFor", [30n, requester.address, judge3.address, "Sponsored", 1n, sponsorDeadline, forgedConsent]);
+  console.log(!unconsented && !forgedSponsor ? "✅ Sponsoring without the requester's consent rejected" : "❌ Dispute opened for a requester without consent");
+  const sponsoredId = await readNextDisputeId();
+  const consent = await signSponsor(requester, 30n, await readRequesterNonce(), sponsorDeadline);
+  const sponsored = await write(sponsor, "createDisputeFor", [30n, requester.address, judge3.address, "Sponsored", 1n, sponsorDeadline, consent]);
+  const sponsoredInfo = await readInfo(sponsoredId);
+  const feePayer = await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getFeePayer",
+    args: [sponsoredId],
+  });
+  console.log(
+    sponsored && sponsoredInfo.requester === requester.address && feePayer === sponsor.address
+      ? "✅ Sponsor paid, requester remains the party of record"
+      : "❌ Sponsored dispute recorded the wrong parties",
+  );
+  const replayedConsent = await write(sponsor, "createDisputeFor", [31n, requester.address, judge3.address, "Sponsored", 1n, sponsorDeadline, consent]);
+  console.log(replayedConsent ? "❌ Consent signature replayed" : "✅ Consent signature replay rejected");
+  await write(deployer, "setSponsor", [sponsor.address, true]);
+  const trustedSponsor = await write(sponsor, "createDisputeFor", [32n, requester.address, judge3.address, "Sponsored", 1n, 0n, "0x"]);
+  await write(deployer, "setSponsor", [sponsor.address, false]);
+  console.log(trustedSponsor ? "✅ Whitelisted sponsor opens disputes without a signature" : "❌ Whitelisted sponsor was rejected");
+
+  // Step 29: Dispute lifecycle phases
+  console.log("\nStep 29: Dispute Phases");
+  // 0 NotFound, 1 WaitingForJudges, 2 Commit, 3 Reveal, 4 Resolved, 5 Cancelled (reserved), 6 Expired
+  const readPhase = async (id: bigint) =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getDisputePhase",
+      args: [id],
+    })) as number;
+  const lifecycleId = await readNextDisputeId();
+  const phases: [string, number][] = [["unknown id", await readPhase(lifecycleId)]];
+  await write(requester, "createDisputeDirect", [40n, judge3.address, "Lifecycle dispute", 1n]);
+  phases.push(["created", await readPhase(lifecycleId)]);
+  await write(judge1, "registerToVote", [lifecycleId]);
+  phases.push(["half panel", await readPhase(lifecycleId)]);
+  await write(judge2, "registerToVote", [lifecycleId]);
+  phases.push(["panel full", await readPhase(lifecycleId)]);
+  for (const j of [judge1, judge2]) {
+    await write(j, "commitVote", [lifecycleId, generateCommitHash(lifecycleId, j.address, VOTE_AGAINST, "phases")]);
+  }
+  phases.push(["all committed", await readPhase(lifecycleId)]);
+  for (const j of [judge1, judge2]) {
+    await write(j, "revealVotes", [lifecycleId, VOTE_AGAINST, secretBytes("phases")]);
+  }
+  await settlePanel(lifecycleId);
+  phases.push(["revealed", await readPhase(lifecycleId)]);
+  const expectedPhases = [0, 1, 1, 2, 3, 4];
+  phases.forEach(([label, phase], i) =>
+    console.log(phase === expectedPhases[i] ? `✅ ${label}: phase ${phase}` : `❌ ${label}: phase ${phase}, expected ${expectedPhases[i]}`),
+  );
+
+  // A panel that never forms expires once the timeout passes
+  const lapsedId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [41n, judge3.address, "Lapsed dispute", 1n]);
+  await write(deployer, "updatePanelTimeout", [1n]);
+  await new Promise(resolve => setTimeout(resolve, 3000));
+  const lapsed = await write(requester, "expireDispute", [lapsedId]);
+  await write(deployer, "updatePanelTimeout", [7n * 24n * 60n * 60n]);
+  const lapsedPhase = await readPhase(lapsedId);
+  const lateJoin = await write(judge1, "registerToVote", [lapsedId]);
+  console.log(lapsed && lapsedPhase === 6 && !lateJoin ? "✅ expired: phase 6, registration closed" : `❌ expired: phase ${lapsedPhase}`);
+  await checkSolvency("expiry");
+
+  // Step 30: Judge bonds
+  console.log("\nStep 30: Judge Bonds");
+  const judgeBond = 100_000n; // 0.1 USDC
+  const badBps = await write(deployer, "setJudgeBond", [judgeBond, 10_001]);
+  await write(deployer, "setJudgeBond", [judgeBond, 5_000]);
+  console.log(!badBps ? "✅ Slash share above 100% rejected" : "❌ Slash share above 100% accepted");
+  const readLockedBond = async (id: bigint, account: any) =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getLockedJudgeBond",
+      args: [id, account],
+    })) as bigint;
+  const readBalanceOf = async (account: any) =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "judgeBalance",
+      args: [account],
+    })) as bigint;
+  const judgeBondId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [42n, judge3.address, "Bonded dispute", 1n]);
+  for (const j of [judge1, judge2]) {
+    await write(j, "registerToVote", [judgeBondId]);
+  }
+  const locked = await readLockedBond(judgeBondId, judge1.address);
+  console.log(locked === judgeBond ? "✅ Bond locked when joining the panel" : `❌ Locked bond ${locked}`);
+  // Both judges rule with the majority, so both bonds come back with the reward
+  const judgeBalancesBefore = await Promise.all([judge1, judge2].map(j => readBalanceOf(j.address)));
+  for (const j of [judge1, judge2]) {
+    await write(j, "commitVote", [judgeBondId, generateCommitHash(judgeBondId, j.address, VOTE_FOR, "bonded")]);
+  }
+  for (const j of [judge1, judge2]) {
+    await write(j, "revealVotes", [judgeBondId, VOTE_FOR, secretBytes("bonded")]);
+  }
+  const bondsBeforeSettle = await readLockedBond(judgeBondId, judge1.address);
+  await settlePanel(judgeBondId);
+  const judgeBalancesAfter = await Promise.all([judge1, judge2].map(j => readBalanceOf(j.address)));
+  const bondEscrow = (await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getDisputeEscrow",
+    args: [judgeBondId],
+  })) as bigint;
+  const returned = judgeBalancesAfter.every((b, i) => b - judgeBalancesBefore[i] >= judgeBond);
+  const unlocked = (await readLockedBond(judgeBondId, judge1.address)) === 0n;
+  console.log(
+    bondsBeforeSettle === judgeBond && returned && unlocked && bondEscrow === 0n
+      ? "✅ Bonds returned to majority judges, escrow cleared"
+      : `❌ Bonds not settled (escrow ${bondEscrow})`,
+  );
+
+  // Conservation across every way a bonded dispute ends: what the parties and judges hold in
+  // tokens, plus what the protocol owes them, plus the treasury, is unchanged once it closes
+  await write(deployer, "setCategory", [3n, 1n * 10n ** 6n, 3]);
+  await write(deployer, "updateMinReputation", [-1000]);
+  const categoryPrice = 1n * 10n ** 6n;
+  const judgesShare = (categoryPrice * 8_000n) / 10_000n;
+  const holders = [judge1, judge2, judge3, judge4, judge5, requester, deployer].map(a => a.address);
+  const readHoldings = async () => {
+    const held = await Promise.all(holders.map(async a => (await readTokenBalance(a)) + (await readBalanceOf(a))));
+    const treasury = (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "contractBalance",
+      args: [],
+    })) as bigint;
+    return held.reduce((sum, h) => sum + h, treasury);
+  };
+  const checkConservation = async (label: string, id: bigint, before: bigint) => {
+    const after = await readHoldings();
+    const escrow = (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getDisputeEscrow",
+      args: [id],
+    })) as bigint;
+    console.log(
+      after === before && escrow === 0n
+        ? `✅ ${label}: funds conserved, escrow cleared`
+        : `❌ ${label}: holdings moved by ${after - before}, escrow ${escrow}`,
+    );
+  };
+  const panelOfThree = [judge1, judge2, judge4];
+
+  // Loser slash: 2-1, the loser gets half the bond back and the winners split the rest with the fee
+  const readRewardsPaid = async () =>
+    ((await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getProtocolMetrics",
+    })) as any).total_judge_rewards_paid as bigint;
+  const rewardsPaidBeforeSlash = await readRewardsPaid();
+  let conservedBefore = await readHoldings();
+  const slashId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [43n, judge3.address, "Slashed loser", 3n]);
+  const slashVotes = [VOTE_FOR, VOTE_FOR, VOTE_AGAINST];
+  for (const j of panelOfThree) {
+    await write(j, "registerToVote", [slashId]);
+  }
+  for (const [i, j] of panelOfThree.entries()) {
+    await write(j, "commitVote", [slashId, generateCommitHash(slashId, j.address, slashVotes[i], "slash")]);
+  }
+  const [loserBefore, winnerBefore] = await Promise.all([readBalanceOf(judge4.address), readBalanceOf(judge1.address)]);
+  for (const i of [2, 0, 1]) {
+    await write(panelOfThree[i], "revealVotes", [slashId, slashVotes[i], secretBytes("slash")]);
+  }
+  await settlePanel(slashId);
+  const loserCredit = (await readBalanceOf(judge4.address)) - loserBefore;
+  const winnerCredit = (await readBalanceOf(judge1.address)) - winnerBefore;
+  console.log(
+    loserCredit === judgeBond / 2n && winnerCredit === judgeBond + (judgesShare + judgeBond / 2n) / 2n
+      ? "✅ Loser kept half the bond, the slashed half went to the winners"
+      : `❌ Loser credited ${loserCredit}, winner ${winnerCredit}`,
+  );
+  // The rewards-paid metric counts the slashed half the winners received, but not their returned bonds
+  const slashRewardsPaid = (await readRewardsPaid()) - rewardsPaidBeforeSlash;
+  console.log(
+    slashRewardsPaid === ((judgesShare + judgeBond / 2n) / 2n) * 2n
+      ? "✅ Rewards-paid metric includes the redistributed bond"
+      : `❌ Rewards-paid metric moved by ${slashRewardsPaid}`,
+  );
+  await checkConservation("Loser slash", slashId, conservedBefore);
+
+  // Never committed: the commit phase is closed on a quorate 2 of 3 and the silent bond is forfeited
+  await write(deployer, "updateCommitPeriod", [5n]);
+  conservedBefore = await readHoldings();
+  const silentId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [44n, judge3.address, "Silent judge", 3n]);
+  for (const j of panelOfThree) {
+    await write(j, "registerToVote", [silentId]);
+  }
+  for (const j of [judge1, judge2]) {
+    await write(j, "commitVote", [silentId, generateCommitHash(silentId, j.address, VOTE_FOR, "silent")]);
+  }
+  await new Promise(resolve => setTimeout(resolve, 6000));
+  await write(requester, "closeCommitPhase", [silentId]);
+  await write(deployer, "updateCommitPeriod", [3n * 24n * 60n * 60n]);
+  const silentBefore = await readBalanceOf(judge4.address);
+  for (const j of [judge1, judge2]) {
+    await write(j, "revealVotes", [silentId, VOTE_FOR, secretBytes("silent")]);
+  }
+  await settlePanel(silentId);
+  console.log(
+    (await readBalanceOf(judge4.address)) === silentBefore ? "✅ Judge who never committed forfeited the whole bond" : "❌ Silent judge got bond back",
+  );
+  await checkConservation("Non-committer", silentId, conservedBefore);
+
+  // Committed straggler: a 2-0 majority closes the vote before the third reveal, so the
+  // third judge gets the whole bond back with no reward and no reputation change
+  conservedBefore = await readHoldings();
+  const stragglerId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [45n, judge3.address, "Straggler", 3n]);
+  for (const j of panelOfThree) {
+    await write(j, "registerToVote", [stragglerId]);
+  }
+  for (const j of panelOfThree) {
+    await write(j, "commitVote", [stragglerId, generateCommitHash(stragglerId, j.address, VOTE_FOR, "straggler")]);
+  }
+  const [stragglerBefore, stragglerRep] = await Promise.all([readBalanceOf(judge4.address), readReputation(judge4)]);
+  for (const j of [judge1, judge2]) {
+    await write(j, "revealVotes", [stragglerId, VOTE_FOR, secretBytes("straggler")]);
+  }
+  const stragglerReveal = await write(judge4, "revealVotes", [stragglerId, VOTE_FOR, secretBytes("straggler")]);
+  await settlePanel(stragglerId);
+  const stragglerCredit = (await readBalanceOf(judge4.address)) - stragglerBefore;
+  console.log(
+    !stragglerReveal && stragglerCredit === judgeBond && (await readReputation(judge4)) === stragglerRep
+      ? "✅ Committed straggler got the bond back, no reward, no reputation change"
+      : `❌ Straggler credited ${stragglerCredit}`,
+  );
+  await checkConservation("Straggler", stragglerId, conservedBefore);
+
+  // Expiry: one judge joined before the panel timed out; fee and bond both go back
+  conservedBefore = await readHoldings();
+  const bondExpiryId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [46n, judge3.address, "Expired with a bond", 3n]);
+  await write(judge1, "registerToVote", [bondExpiryId]);
+  await write(deployer, "updatePanelTimeout", [1n]);
+  await new Promise(resolve => setTimeout(resolve, 3000));
+  await write(requester, "expireDispute", [bondExpiryId]);
+  await write(deployer, "updatePanelTimeout", [7n * 24n * 60n * 60n]);
+  await checkConservation("Expiry", bondExpiryId, conservedBefore);
+
+  // Inquorate expiry: 1 of 3 committed by the deadline, so closing the commit phase expires
+  // the dispute. The committer gets the bond back, the two silent seats forfeit theirs to the treasury
+  await write(deployer, "updateCommitPeriod", [5n]);
+  conservedBefore = await readHoldings();
+  const inquorateBondId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [49n, judge3.address, "Inquorate with bonds", 3n]);
+  for (const j of panelOfThree) {
+    await write(j, "registerToVote", [inquorateBondId]);
+  }
+  await write(judge1, "commitVote", [inquorateBondId, generateCommitHash(inquorateBondId, judge1.address, VOTE_FOR, "inquorate-bond")]);
+  const inquorateCreditsBefore = await Promise.all(panelOfThree.map(j => readBalanceOf(j.address)));
+  const treasuryBeforeInquorate = await readTreasury();
+  await new Promise(resolve => setTimeout(resolve, 6000));
+  const inquorateBondClosed = await write(requester, "closeCommitPhase", [inquorateBondId]);
+  await write(deployer, "updateCommitPeriod", [3n * 24n * 60n * 60n]);
+  const inquorateCredits = await Promise.all(panelOfThree.map(async (j, i) => (await readBalanceOf(j.address)) - inquorateCreditsBefore[i]));
+  const inquorateForfeit = (await readTreasury()) - treasuryBeforeInquorate;
+  console.log(
+    inquorateBondClosed && (await readPhase(inquorateBondId)) === 6 &&
+      inquorateCredits[0] === judgeBond && inquorateCredits[1] === 0n && inquorateCredits[2] === 0n &&
+      inquorateForfeit === judgeBond * 2n
+      ? "✅ Inquorate expiry returned the committer's bond, silent bonds went to the treasury"
+      : `❌ Inquorate expiry credited ${inquorateCredits.join(", ")}, treasury moved by ${inquorateForfeit}`,
+  );
+  await checkConservation("Inquorate expiry", inquorateBondId, conservedBefore);
+
+  // Settlement after every judge committed
+  conservedBefore = await readHoldings();
+  const bondSettleId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [47n, judge5.address, "Settled with bonds", 3n]);
+  for (const j of panelOfThree) {
+    await write(j, "registerToVote", [bondSettleId]);
+  }
+  for (const j of panelOfThree) {
+    await write(j, "commitVote", [bondSettleId, generateCommitHash(bondSettleId, j.address, VOTE_FOR, "settle")]);
+  }
+  await write(requester, "settleDispute", [bondSettleId, true]);
+  await write(judge5, "settleDispute", [bondSettleId, true]);
+  await checkConservation("Settlement", bondSettleId, conservedBefore);
+
+  // Re-vote: a 1-1 tie returns the first panel's bonds, a fresh panel rules
+  conservedBefore = await readHoldings();
+  const bondTieId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [48n, judge3.address, "Tied with bonds", 1n]);
+  for (const [round, panel, votes] of [
+    [0, [judge1, judge2], [VOTE_FOR, VOTE_AGAINST]],
+    [1, [judge4, judge5], [VOTE_FOR, VOTE_FOR]],
+  ] as [number, any[], number[]][]) {
+    for (const j of panel) {
+      await write(j, "registerToVote", [bondTieId]);
+    }
+    for (const [i, j] of panel.entries()) {
+      await write(j, "commitVote", [bondTieId, generateCommitHash(bondTieId, j.address, votes[i], `tie${round}`)]);
+    }
+    for (const [i, j] of panel.entries()) {
+      await write(j, "revealVotes", [bondTieId, votes[i], secretBytes(`tie${round}`)]);
+    }
+  }
+  await settlePanel(bondTieId);
+  console.log((await readResolved(bondTieId)) ? "✅ Re-vote panel ruled" : "❌ Re-vote panel didn't rule");
+  await checkConservation("Re-vote", bondTieId, conservedBefore);
+  await write(deployer, "updateMinReputation", [0]);
+  await checkSolvency("judge bond outcomes");
+  await write(deployer, "setJudgeBond", [0n, 0]);
+
+  // Step 31: Open-for-registration index
+  console.log("\nStep 31: Open For Registration Index");
+  const readOpenIds = async () => {
+    const count = (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "openForRegistrationCount",
+    })) as bigint;
+    return (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getOpenForRegistration",
+      args: [0n, count],
+    })) as bigint[];
+  };
+  const checkIndex = async (label: string, mustContain: bigint[], mustNotContain: bigint[]) => {
+    const ids = await readOpenIds();
+    const phases = await Promise.all(ids.map(id => readPhase(id)));
+    const allWaiting = phases.every(phase => phase === 1);
+    const unique = new Set(ids.map(String)).size === ids.length;
+    const contains = mustContain.every(id => ids.includes(id));
+    const excludes = mustNotContain.every(id => !ids.includes(id));
+    console.log(
+      allWaiting && unique && contains && excludes
+        ? `✅ ${label}: ${ids.length} disputes listed, all waiting for judges`
+        : `❌ ${label}: index [${ids.join(", ")}] phases [${phases.join(", ")}]`,
+    );
+  };
+  // Churn: open three, fill the middle one, expire the first, open another
+  const churnIds: bigint[] = [];
+  for (const dealId of [50n, 51n, 52n]) {
+    churnIds.push(await readNextDisputeId());
+    await write(requester, "createDisputeDirect", [dealId, judge3.address, "Churn dispute", 1n]);
+  }
+  await checkIndex("after creations", churnIds, []);
+  for (const j of [judge1, judge2]) {
+    await write(j, "registerToVote", [churnIds[1]]);
+  }
+  await checkIndex("after panel fill", [churnIds[0], churnIds[2]], [churnIds[1]]);
+  await write(deployer, "updatePanelTimeout", [1n]);
+  await new Promise(resolve => setTimeout(resolve, 3000));
+  await write(requester, "expireDispute", [churnIds[0]]);
+  await write(deployer, "updatePanelTimeout", [7n * 24n * 60n * 60n]);
+  await checkIndex("after expiry", [churnIds[2]], [churnIds[0], churnIds[1]]);
+  const lateId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [53n, judge3.address, "Churn dispute", 1n]);
+  await checkIndex("after re-creation", [churnIds[2], lateId], [churnIds[0], churnIds[1]]);
+
+  // Step 32: Panel size cap
+  console.log("\nStep 32: Panel Size Cap");
+  // 25 judges keeps the uint8 tallies and per-dispute loops bounded
+  const votesAtCap = await write(deployer, "updateNumberOfVotes", [25]);
+  const votesOverCap = await write(deployer, "updateNumberOfVotes", [26]);
+  await write(deployer, "updateNumberOfVotes", [5]);
+  const categoryAtCap = await write(deployer, "setCategory", [9n, 1n * 10n ** 6n, 25]);
+  const categoryOverCap = await write(deployer, "setCategory", [9n, 1n * 10n ** 6n, 26]);
+  console.log(
+    votesAtCap && !votesOverCap && categoryAtCap && !categoryOverCap
+      ? "✅ Panel size 25 accepted, 26 rejected"
+      : "❌ Panel size cap not enforced at the boundary",
+  );
+
+  // Step 33: Closing the commit phase
+  console.log("\nStep 33: Close Commit Phase");
+  await write(deployer, "setCategory", [3n, 1n * 10n ** 6n, 3]);
+  // On time: a full set of commits moves to reveal by itself, nothing to close
+  const onTimeId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [60n, judge3.address, "On-time commits", 1n]);
+  for (const j of [judge1, judge2]) {
+    await write(j, "registerToVote", [onTimeId]);
+    await write(j, "commitVote", [onTimeId, generateCommitHash(onTimeId, j.address, VOTE_FOR, "ontime")]);
+  }
+  const closeOnTime = await write(requester, "closeCommitPhase", [onTimeId]);
+  console.log(!closeOnTime && (await readPhase(onTimeId)) === 3 ? "✅ Complete commit set needs no closing" : "❌ Closed a complete commit set");
+  for (const j of [judge1, judge2]) {
+    await write(j, "revealVotes", [onTimeId, VOTE_FOR, secretBytes("ontime")]);
+  }
+  await settlePanel(onTimeId);
+
+  // Late but quorate: 2 of 3 committed, the silent judge is penalized and reveals open
+  await write(deployer, "updateCommitPeriod", [5n]);
+  const quorateId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [61n, judge3.address, "Late quorate commits", 3n]);
+  for (const j of [judge1, judge2, judge4]) {
+    await write(j, "registerToVote", [quorateId]);
+  }
+  for (const j of [judge1, judge2]) {
+    await write(j, "commitVote", [quorateId, generateCommitHash(quorateId, j.address, VOTE_FOR, "quorate")]);
+  }
+  const closeEarly = await write(requester, "closeCommitPhase", [quorateId]);
+  const silentRepBefore = await readReputation(judge4);
+  await new Promise(resolve => setTimeout(resolve, 6000));
+  const lateCommit = await write(judge4, "commitVote", [quorateId, generateCommitHash(quorateId, judge4.address, VOTE_AGAINST, "late")]);
+  // A replacement could never commit now, so the silent seat can't be swapped before or after the close
+  const swapPastDeadline = await write(deployer, "replaceJudge", [quorateId, judge4.address, judge5.address]);
+  const closedQuorate = await write(requester, "closeCommitPhase", [quorateId]);
+  const swapAfterClose = await write(deployer, "replaceJudge", [quorateId, judge4.address, judge5.address]);
+  const silentRepAfter = await readReputation(judge4);
+  const quoratePhase = await readPhase(quorateId);
+  for (const j of [judge1, judge2]) {
+    await write(j, "revealVotes", [quorateId, VOTE_FOR, secretBytes("quorate")]);
+  }
+  await settlePanel(quorateId);
+  const quorateResolved = await readResolved(quorateId);
+  console.log(!closeEarly ? "✅ Close rejected before the deadline" : "❌ Closed before the deadline");
+  console.log(!lateCommit ? "✅ Commit after the deadline rejected" : "❌ Commit after the deadline accepted");
+  console.log(
+    closedQuorate && quoratePhase === 3 && quorateResolved
+      ? "✅ Quorate commit set frozen, revealed and resolved"
+      : `❌ Quorate close failed (phase ${quoratePhase})`,
+  );
+  console.log(
+    Number(silentRepAfter) === Number(silentRepBefore) - 1 ? "✅ Judge who never committed penalized once" : "❌ Silent judge not penalized exactly once",
+  );
+  console.log(
+    !swapPastDeadline && !swapAfterClose
+      ? "✅ Silent judge can't be replaced after the deadline or once the phase is closed"
+      : "❌ Replacement seated after the commit phase ended",
+  );
+
+  // Fresh, funded and registered judges for panels the original five are barred from
+  const enlistJudges = async (label: string, count: number) => {
+    const enlisted = Array.from({ length: count }, (_, i) =>
+      privateKeyToAccount(keccak256(toBytes(`${DEPLOYER_PRIVATE_KEY}${label}${i}`))),
+    );
+    for (const j of enlisted) {
+      const hash = await deployerWalletFund.sendTransaction({ to: j.address, value: 10n * 10n ** 15n });
+      await publicClient.waitForTransactionReceipt({ hash });
+      await tokenWrite(deployer, usdc, "mint", [j.address, 1_000n * 10n ** 6n]);
+      await tokenWrite(j, usdc, "approve", [protocolAddress, 2n ** 255n]);
+      await write(j, "registerAsJudge", []);
+    }
+    return enlisted;
+  };
+  const readDisputeRound = async (id: bigint) =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getDisputeRound",
+      args: [id],
+    })) as number;
+  // A round 2 panel commits and reveals for the requester, with time to commit all five
+  const decideRevote = async (id: bigint, panel: any[], secret: string) => {
+    await write(deployer, "updateCommitPeriod", [3n * 24n * 60n * 60n]);
+    for (const j of panel) {
+      await write(j, "registerToVote", [id]);
+    }
+    const phaseOnceSeated = await readPhase(id);
+    for (const j of panel) {
+      await write(j, "commitVote", [id, generateCommitHash(id, j.address, VOTE_FOR, secret)]);
+    }
+    for (const j of panel.slice(0, 3)) {
+      await write(j, "revealVotes", [id, VOTE_FOR, secretBytes(secret)]);
+    }
+    await settlePanel(id);
+    await write(deployer, "updateCommitPeriod", [5n]);
+    return phaseOnceSeated;
+  };
+  const revoteJudges = await enlistJudges("revote", 5);
+
+  // Frozen round that ties: 4 of 5 commit and split 2-2 after closeCommitPhase. The re-vote
+  // must reopen the commit phase for the fresh panel instead of carrying the frozen flag over
+  await write(deployer, "updateMinReputation", [-1000]);
+  const frozenTieId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [64n, deployer.address, "Frozen tie", 0n]);
+  const frozenTie: [any, number][] = [[judge1, VOTE_FOR], [judge2, VOTE_FOR], [judge3, VOTE_AGAINST], [judge4, VOTE_AGAINST]];
+  for (const j of [judge1, judge2, judge3, judge4, judge5]) {
+    await write(j, "registerToVote", [frozenTieId]);
+  }
+  for (const [j, ballot] of frozenTie) {
+    await write(j, "commitVote", [frozenTieId, generateCommitHash(frozenTieId, j.address, ballot, "frozen-tie")]);
+  }
+  await new Promise(resolve => setTimeout(resolve, 6000));
+  const frozenTieClosed = await write(requester, "closeCommitPhase", [frozenTieId]);
+  for (const [j, ballot] of frozenTie) {
+    await write(j, "revealVotes", [frozenTieId, ballot, secretBytes("frozen-tie")]);
+  }
+  const frozenTieRound = await readDisputeRound(frozenTieId);
+  const frozenTiePhase = await readPhase(frozenTieId);
+  const frozenTieReseated = await decideRevote(frozenTieId, revoteJudges, "frozen-tie-round1");
+  await write(deployer, "updateMinReputation", [0]);
+  console.log(
+    frozenTieClosed && frozenTieRound === 1 && frozenTiePhase === 1 && frozenTieReseated === 2
+      ? "✅ Tie on a frozen commit set went back to WaitingForJudges, then Commit for the new panel"
+      : `❌ Frozen tie re-vote stuck (round ${frozenTieRound}, phase ${frozenTiePhase} → ${frozenTieReseated})`,
+  );
+  console.log(
+    (await readResolved(frozenTieId)) ? "✅ Re-vote after a frozen tie resolved" : "❌ Re-vote after a frozen tie never resolved",
+  );
+
+  // Full panel with 4 of 5 commits: reveals open once the deadline passes, the first one
+  // freezing the commit set and penalizing the silent seat exactly as closeCommitPhase would
+  await write(deployer, "updateMinReputation", [-1000]);
+  const fourOfFiveId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [63n, deployer.address, "Four of five commits", 0n]);
+  const fourOfFive: [any, number][] = [[judge4, VOTE_AGAINST], [judge1, VOTE_FOR], [judge2, VOTE_FOR], [judge3, VOTE_FOR]];
+  for (const j of [judge1, judge2, judge3, judge4, judge5]) {
+    await write(j, "registerToVote", [fourOfFiveId]);
+  }
+  for (const [j, ballot] of fourOfFive) {
+    await write(j, "commitVote", [fourOfFiveId, generateCommitHash(fourOfFiveId, j.address, ballot, "four-of-five")]);
+  }
+  const revealBeforeDeadline = await write(judge4, "revealVotes", [fourOfFiveId, VOTE_AGAINST, secretBytes("four-of-five")]);
+  const fifthRepBefore = await readReputation(judge5);
+  await new Promise(resolve => setTimeout(resolve, 6000));
+  let revealsAfterDeadline = true;
+  for (const [j, ballot] of fourOfFive) {
+    revealsAfterDeadline &&= await write(j, "revealVotes", [fourOfFiveId, ballot, secretBytes("four-of-five")]);
+  }
+  const closeAfterReveal = await write(requester, "closeCommitPhase", [fourOfFiveId]);
+  const fifthRepAfter = await readReputation(judge5);
+  await settlePanel(fourOfFiveId);
+  await write(deployer, "updateMinReputation", [0]);
+  console.log(!revealBeforeDeadline ? "✅ Reveal rejected with 4/5 commits before the deadline" : "❌ Reveal accepted before the deadline");
+  console.log(
+    revealsAfterDeadline && (await readResolved(fourOfFiveId))
+      ? "✅ Reveals accepted after the deadline with 4/5 commits, dispute resolved"
+      : "❌ Reveal after the deadline with 4/5 commits failed",
+  );
+  console.log(
+    !closeAfterReveal && Number(fifthRepAfter) === Number(fifthRepBefore) - 1
+      ? "✅ First late reveal froze the commit set and penalized the silent judge once"
+      : `❌ Commit set not frozen by the reveal (reputation ${fifthRepBefore} → ${fifthRepAfter})`,
+  );
+
+  // Same 4 of 5, split 2-2: the late reveal that froze the commit set must not carry the
+  // freeze into the re-vote, so the fresh panel can commit and decide
+  await write(deployer, "updateMinReputation", [-1000]);
+  const lateTieId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [65n, deployer.address, "Four of five tie", 0n]);
+  const lateTie: [any, number][] = [[judge1, VOTE_FOR], [judge2, VOTE_AGAINST], [judge3, VOTE_FOR], [judge4, VOTE_AGAINST]];
+  for (const j of [judge1, judge2, judge3, judge4, judge5]) {
+    await write(j, "registerToVote", [lateTieId]);
+  }
+  for (const [j, ballot] of lateTie) {
+    await write(j, "commitVote", [lateTieId, generateCommitHash(lateTieId, j.address, ballot, "late-tie")]);
+  }
+  await new Promise(resolve => setTimeout(resolve, 6000));
+  let lateTieRevealed = true;
+  for (const [j, ballot] of lateTie) {
+    lateTieRevealed &&= await write(j, "revealVotes", [lateTieId, ballot, secretBytes("late-tie")]);
+  }
+  const lateTieRound = await readDisputeRound(lateTieId);
+  const lateTiePhase = await readPhase(lateTieId);
+  const lateTieReseated = await decideRevote(lateTieId, revoteJudges, "late-tie-round1");
+  await write(deployer, "updateMinReputation", [0]);
+  console.log(
+    lateTieRevealed && lateTieRound === 1 && lateTiePhase === 1 && lateTieReseated === 2
+      ? "✅ Tie on a commit set frozen by a late reveal went back to WaitingForJudges, then Commit"
+      : `❌ Late-reveal tie re-vote stuck (round ${lateTieRound}, phase ${lateTiePhase} → ${lateTieReseated})`,
+  );
+  console.log(
+    (await readResolved(lateTieId)) ? "✅ Re-vote after a late-reveal tie resolved" : "❌ Re-vote after a late-reveal tie never resolved",
+  );
+
+  // Late and inquorate: 1 of 3 committed, the dispute expires and the fee is refunded
+  const inquorateId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [62n, judge3.address, "Late inquorate commits", 3n]);
+  for (const j of [judge1, judge2, judge4]) {
+    await write(j, "registerToVote", [inquorateId]);
+  }
+  await write(judge1, "commitVote", [inquorateId, generateCommitHash(inquorateId, judge1.address, VOTE_FOR, "inquorate")]);
+  await new Promise(resolve => setTimeout(resolve, 6000));
+  const inquorateReveal = await write(judge1, "revealVotes", [inquorateId, VOTE_FOR, secretBytes("inquorate")]);
+  const closedInquorate = await write(requester, "closeCommitPhase", [inquorateId]);
+  const inquoratePhase = await readPhase(inquorateId);
+  console.log(
+    !inquorateReveal && closedInquorate && inquoratePhase === 6
+      ? "✅ Inquorate commit set can't be revealed, dispute expired and refunded"
+      : `❌ Inquorate close failed (phase ${inquoratePhase})`,
+  );
+  await write(deployer, "updateCommitPeriod", [3n * 24n * 60n * 60n]);
+
+  // Step 34: Requester confirmation of marketplace disputes
+  console.log("\nStep 34: Requester Acknowledgment");
+  const ackMarketplace = judge5;
+  await write(deployer, "setRequireRequesterAck", [ackMarketplace.address, true]);
+  // Confirmed: judges can only join after the named requester confirms
+  const ackedId = await readNextDisputeId();
+  await write(ackMarketplace, "createDispute", [200n, requester.address, "Needs confirmation", 1n]);
+  const pendingPhase = await readPhase(ackedId);
+  const earlyJoin = await write(judge1, "registerToVote", [ackedId]);
+  const earlyVoid = await write(judge2, "voidUnconfirmedDispute", [ackedId]);
+  const wrongCaller = await write(judge2, "confirmDispute", [ackedId]);
+  const confirmed = await write(requester, "confirmDispute", [ackedId]);
+  const confirmedPhase = await readPhase(ackedId);
+  const joinAfterConfirm = await write(judge1, "registerToVote", [ackedId]);
+  console.log(pendingPhase === 7 && !earlyJoin ? "✅ Unconfirmed dispute closed to judges" : `❌ Pending dispute in phase ${pendingPhase}`);
+  console.log(!earlyVoid ? "✅ Void rejected inside the confirmation window" : "❌ Voided inside the confirmation window");
+  console.log(!wrongCaller ? "✅ Only the named requester can confirm" : "❌ Another account confirmed the dispute");
+  console.log(
+    confirmed && confirmedPhase === 1 && joinAfterConfirm ? "✅ Confirmed dispute open to judges" : `❌ Confirmation failed (phase ${confirmedPhase})`,
+  );
+
+  // Unconfirmed: once the window passes anyone can void it and the marketplace is refunded
+  await write(deployer, "updateAckWindow", [1n]);
+  const unackedId = await readNextDisputeId();
+  await write(ackMarketplace, "createDispute", [201n, requester.address, "Never confirmed", 1n]);
+  await new Promise(resolve => setTimeout(resolve, 3000));
+  const lateConfirm = await write(requester, "confirmDispute", [unackedId]);
+  const voided = await write(judge2, "voidUnconfirmedDispute", [unackedId]);
+  const voidedPhase = await readPhase(unackedId);
+  const voidedEscrow = (await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getDisputeEscrow",
+    args: [unackedId],
+  })) as bigint;
+  console.log(!lateConfirm ? "✅ Confirmation after the window rejected" : "❌ Late confirmation accepted");
+  console.log(
+    voided && voidedPhase === 6 && voidedEscrow === 0n
+      ? "✅ Unconfirmed dispute voided and fee returned"
+      : `❌ Void failed (phase ${voidedPhase}, escrow ${voidedEscrow})`,
+  );
+  await write(deployer, "updateAckWindow", [3n * 24n * 60n * 60n]);
+  await write(deployer, "setRequireRequesterAck", [ackMarketplace.address, false]);
+
+  // Step 35: Protocol metrics
+  console.log("\nStep 35: Protocol Metrics");
+  // Every lifecycle above (rulings, no rulings, defaults, settlements, expiries, voids) feeds the counters
+  const metrics = (await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getProtocolMetrics",
+  })) as any;
+  const lastId = (await readNextDisputeId()) - 1n;
+  let [openCount, resolvedCount, expiredCount, feeSum] = [0n, 0n, 0n, 0n];
+  for (let id = 1n; id <= lastId; id++) {
+    const info = await readInfo(id);
+    if (info.resolved) resolvedCount++;
+    else if (info.expired) expiredCount++;
+    else openCount++;
+    feeSum += info.price;
+  }
+  console.log(
+    metrics.disputes_created === lastId &&
+      metrics.disputes_open === openCount &&
+      metrics.disputes_resolved === resolvedCount &&
+      metrics.disputes_expired === expiredCount
+      ? `✅ Counters match dispute states (${resolvedCount} resolved, ${expiredCount} expired, ${openCount} open)`
+      : `❌ Counters ${metrics.disputes_resolved}/${metrics.disputes_expired}/${metrics.disputes_open}, states ${resolvedCount}/${expiredCount}/${openCount}`,
+  );
+  console.log(metrics.total_fees_collected === feeSum ? "✅ Fees collected match dispute prices" : `❌ Fees ${metrics.total_fees_collected} vs ${feeSum}`);
+  console.log(
+    metrics.total_judge_rewards_paid > 0n
+      ? `✅ Judge rewards paid: ${formatUnits(metrics.total_judge_rewards_paid, 6)} USDC`
+      : "❌ No judge rewards recorded",
+  );
+  // The surplus is exactly what the protocol holds beyond escrow, judge balances and stakes
+  const [escrowedNow, owedNow, stakedNow] = (await Promise.all(
+    ["totalEscrowed", "totalJudgeRewards", "totalStaked"].map(functionName =>
+      publicClient.readContract({ address: protocolAddress, abi: protocolAbi, functionName, args: [] }),
+    ),
+  )) as bigint[];
+  const expectedSurplus = (await readTokenBalance(protocolAddress)) - escrowedNow - owedNow - stakedNow;
+  console.log(
+    metrics.treasury_surplus === expectedSurplus
+      ? `✅ Treasury surplus is exactly ${formatUnits(expectedSurplus, 6)} USDC`
+      : `❌ Treasury surplus ${metrics.treasury_surplus}, expected ${expectedSurplus}`,
+  );
+
+  // Step 36: Proof relaying by the originating marketplace
+  console.log("\nStep 36: Proof Relaying");
+  const relayMarketplace = judge4;
+  const relayedId = await readNextDisputeId();
+  await write(relayMarketplace, "createDispute", [300n, requester.address, "Relayed proofs", 1n]);
+  const readProof = async (id: bigint, party: any) =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getProof",
+      args: [id, party],
+    })) as string;
+  const relayed = await write(relayMarketplace, "relayProofForRequester", [relayedId, "ipfs://relayed-evidence"]);
+  const [relayLog]: any[] = await publicClient.getContractEvents({
+    address: protocolAddress,
+    abi: protocolAbi,
+    eventName: "ProofSubmitted",
+    args: { dispute_id: relayedId },
+    fromBlock: 0n,
+  });
+  console.log(
+    relayed &&
+      (await readProof(relayedId, requester.address)) === "ipfs://relayed-evidence" &&
+      relayLog?.args.party === requester.address &&
+      relayLog?.args.submitted_by === relayMarketplace.address
+      ? "✅ Originating marketplace relayed the requester's proof"
+      : "❌ Relayed proof not recorded for the requester",
+  );
+  // Marketplace disputes have no beneficiary on record, so there is nobody to relay for
+  const unknownBeneficiary = await write(relayMarketplace, "relayProofForBeneficiary", [relayedId, "ipfs://orphan"]);
+  console.log(
+    !unknownBeneficiary && (await readProof(relayedId, "0x0000000000000000000000000000000000000000")) === ""
+      ? "✅ Relay for a missing beneficiary rejected"
+      : "❌ Relay for a missing beneficiary accepted",
+  );
+  const foreignRelay = await write(judge5, "relayProofForRequester", [relayedId, "ipfs://forged"]);
+  console.log(!foreignRelay ? "✅ Relay from another contract rejected" : "❌ Relay from another contract accepted");
+  // Direct disputes are opened on the protocol itself, so nobody can relay for them
+  const directRelay = await write(relayMarketplace, "relayProofForBeneficiary", [lateId, "ipfs://forged"]);
+  console.log(!directRelay ? "✅ Relay on a direct dispute rejected" : "❌ Relay on a direct dispute accepted");
+  // Parties can still submit directly, but only for themselves
+  const directProof = await write(requester, "updateDisputeForPayer", [lateId, requester.address, "ipfs://direct"]);
+  const impersonated = await write(judge2, "updateDisputeForPayer", [lateId, requester.address, "ipfs://forged"]);
+  console.log(
+    directProof && !impersonated && (await readProof(lateId, requester.address)) === "ipfs://direct"
+      ? "✅ Direct submissions accepted only from the party"
+      : "❌ Direct submission check failed",
+  );
+
+  // Step 37: Deferred panel settlement
+  console.log("\nStep 37: Deferred Panel Settlement");
+  await write(deployer, "updateAppealWindow", [5n]);
+  const deferredId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [70n, judge3.address, "Deferred settlement", 1n]);
+  for (const j of [judge1, judge2]) {
+    await write(j, "registerToVote", [deferredId]);
+    await write(j, "commitVote", [deferredId, generateCommitHash(deferredId, j.address, VOTE_FOR, "deferred")]);
+  }
+  const snapshot = async () =>
+    Promise.all([judge1, judge2].map(async j => [await readBalanceOf(j.address), Number(await readReputation(j))] as const));
+  const beforeRuling = await snapshot();
+  for (const j of [judge1, judge2]) {
+    await write(j, "revealVotes", [deferredId, VOTE_FOR, secretBytes("deferred")]);
+  }
+  const readPending = async () =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getPendingSettlement",
+      args: [deferredId],
+    })) as [boolean, bigint, bigint];
+  const [pendingFlag, pendingAmount] = await readPending();
+  const afterRuling = await snapshot();
+  const untouched = afterRuling.every(([b, r], i) => b === beforeRuling[i][0] && r === beforeRuling[i][1]);
+  console.log(
+    (await readResolved(deferredId)) && pendingFlag && pendingAmount > 0n && untouched
+      ? `✅ Ruling held ${formatUnits(pendingAmount, 6)} USDC pending, balances and reputation unchanged`
+      : "❌ Ruling settled the panel immediately",
+  );
+  const earlyRelease = await write(judge3, "settleOriginalPanel", [deferredId]);
+  console.log(!earlyRelease ? "✅ Release rejected inside the appeal window" : "❌ Released inside the appeal window");
+  await new Promise(resolve => setTimeout(resolve, 6000));
+  const released = await write(judge3, "settleOriginalPanel", [deferredId]);
+  const afterRelease = await snapshot();
+  const credited = afterRelease.reduce((sum, [b], i) => sum + (b - beforeRuling[i][0]), 0n);
+  const repApplied = afterRelease.every(([, r], i) => r === beforeRuling[i][1] + 1);
+  const [stillPending] = await readPending();
+  const deferredEscrow = (await publicClient.readContract({
+    address: protocolAddress,
+    abi: protocolAbi,
+    functionName: "getDisputeEscrow",
+    args: [deferredId],
+  })) as bigint;
+  console.log(
+    released && credited === pendingAmount && repApplied && !stillPending && deferredEscrow === 0n
+      ? "✅ Pending rewards and reputation released after the window"
+      : `❌ Release mismatch (credited ${credited} of ${pendingAmount})`,
+  );
+  const secondRelease = await write(judge3, "settleOriginalPanel", [deferredId]);
+  console.log(!secondRelease ? "✅ Settlement released only once" : "❌ Settlement released twice");
+  const lateAppeal = await write(judge3, "appeal", [deferredId]);
+  console.log(!lateAppeal ? "✅ Appeal rejected once the window lapsed" : "❌ Appeal accepted after the window");
+
+  // Appeals: judge1 and judge2 rule for the requester, the beneficiary (judge3) appeals and a
+  // panel of judge4 and judge5 either upholds or reverses the ruling
+  await write(deployer, "updateMinReputation", [-1000]);
+  const appealRewardBps = BigInt(
+    (await publicClient.readContract({ address: protocolAddress, abi: protocolAbi, functionName: "judgeRewardBps", args: [] })) as number,
+  );
+  const appealPool = (1n * 10n ** 6n * appealRewardBps) / 10_000n;
+  const appealJudges = [judge1, judge2, judge4, judge5];
+  const readAppealState = async () =>
+    Promise.all(appealJudges.map(async j => [await readBalanceOf(j.address), Number(await readReputation(j))] as const));
+  const runAppeal = async (label: string, appealVote: number) => {
+    const originalId = await readNextDisputeId();
+    await write(requester, "createDisputeDirect", [71n + BigInt(appealVote), judge3.address, label, 1n]);
+    for (const j of [judge1, judge2]) {
+      await write(j, "registerToVote", [originalId]);
+      await write(j, "commitVote", [originalId, generateCommitHash(originalId, j.address, VOTE_FOR, label)]);
+    }
+    const stateBefore = await readAppealState();
+    const appellantBefore = await readTokenBalance(judge3.address);
+    for (const j of [judge1, judge2]) {
+      await write(j, "revealVotes", [originalId, VOTE_FOR, secretBytes(label)]);
+    }
+    const winnerAppeal = await write(requester, "appeal", [originalId]);
+    const appealed = await write(judge3, "appeal", [originalId]);
+    const appealId = originalId + 1n;
+    const appellantPaid = appellantBefore - (await readTokenBalance(judge3.address));
+    const originalJudgeJoins = await write(judge1, "registerToVote", [appealId]);
+    await new Promise(resolve => setTimeout(resolve, 6000));
+    const settledDuringAppeal = await write(judge3, "settleOriginalPanel", [originalId]);
+    for (const j of [judge4, judge5]) {
+      await write(j, "registerToVote", [appealId]);
+      await write(j, "commitVote", [appealId, generateCommitHash(appealId, j.address, appealVote, label)]);
+    }
+    for (const j of [judge4, judge5]) {
+      await write(j, "revealVotes", [appealId, appealVote, secretBytes(label)]);
+    }
+    const appealOfAppeal = await write(requester, "appeal", [appealId]);
+    await settlePanel(originalId);
+    await settlePanel(appealId);
+    const stateAfter = await readAppealState();
+    const outcome = Number(
+      await publicClient.readContract({ address: protocolAddress, abi: protocolAbi, functionName: "getDisputeOutcome", args: [originalId] }),
+    );
+    const [, , overturned] = (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getAppeal",
+      args: [originalId],
+    })) as [bigint, bigint, boolean];
+    console.log(
+      !winnerAppeal && appealed && appellantPaid === 1n * 10n ** 6n && !originalJudgeJoins && !settledDuringAppeal && !appealOfAppeal
+        ? `✅ ${label}: loser appealed for the category price, original panel barred and held until the appeal ruled`
+        : `❌ ${label}: appeal flow failed`,
+    );
+    return {
+      outcome,
+      overturned,
+      credits: stateAfter.map(([b], i) => b - stateBefore[i][0]),
+      repDeltas: stateAfter.map(([, r], i) => r - stateBefore[i][1]),
+    };
+  };
+  const upheld = await runAppeal("Upheld appeal", VOTE_FOR);
+  console.log(
+    upheld.outcome === 1 && !upheld.overturned &&
+      upheld.credits.every(c => c === appealPool / 2n) && upheld.repDeltas.every(d => d === 1)
+      ? `✅ Upheld appeal: both panels paid ${formatUnits(appealPool / 2n, 6)} USDC a seat, +1 reputation each`
+      : `❌ Upheld appeal credits ${upheld.credits.join(", ")}, reputation ${upheld.repDeltas.join(", ")}`,
+  );
+  const reversed = await runAppeal("Overturned appeal", VOTE_AGAINST);
+  console.log(
+    reversed.outcome === 2 && reversed.overturned &&
+      reversed.credits[0] === 0n && reversed.credits[1] === 0n &&
+      reversed.credits[2] === appealPool && reversed.credits[3] === appealPool &&
+      reversed.repDeltas.join() === [-1, -1, 1, 1].join()
+      ? "✅ Overturned appeal: beneficiary wins, original rewards moved to the appeal panel and reputation inverted"
+      : `❌ Overturned appeal credits ${reversed.credits.join(", ")}, reputation ${reversed.repDeltas.join(", ")}`,
+  );
+  await write(deployer, "updateMinReputation", [0]);
+  await write(deployer, "updateAppealWindow", [0n]);
+  await checkSolvency("appeals");
+
+  // Step 38: Secret length and commitment replay
+  console.log("\nStep 38: Reveal Secrets");
+  const entropyId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [80n, judge3.address, "Secret entropy", 1n]);
+  const exactSecret = "sixteen-bytes-ok"; // exactly MIN_SECRET_LENGTH bytes
+  for (const j of [judge1, judge2]) {
+    await write(j, "registerToVote", [entropyId]);
+    await write(j, "commitVote", [entropyId, generateCommitHash(entropyId, j.address, VOTE_FOR, exactSecret)]);
+  }
+  let weakHashRejected = false;
+  try {
+    await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "computeCommitHash",
+      args: [entropyId, judge1.address, VOTE_FOR, [0x61]],
+    });
+  } catch {
+    weakHashRejected = true;
+  }
+  const shortReveal = await write(judge1, "revealVotes", [entropyId, VOTE_FOR, secretBytes(exactSecret).slice(0, 15)]);
+  const exactReveal = await write(judge1, "revealVotes", [entropyId, VOTE_FOR, secretBytes(exactSecret)]);
+  console.log(weakHashRejected ? "✅ Hash helper rejects short secrets" : "❌ Hash helper accepted a short secret");
+  console.log(!shortReveal ? "✅ 15-byte secret rejected at reveal" : "❌ 15-byte secret accepted");
+  console.log(exactReveal ? "✅ 16-byte secret accepted" : "❌ 16-byte secret rejected");
+  await write(judge2, "revealVotes", [entropyId, VOTE_FOR, secretBytes(exactSecret)]);
+
+  // A tie resets the panel; a commitment revealed in round 0 can't be replayed in round 1
+  const replayId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [81n, judge3.address, "Commitment replay", 1n]);
+  const replayBallots: [any, number][] = [[judge1, VOTE_FOR], [judge2, VOTE_AGAINST]];
+  for (const [j, ballot] of replayBallots) {
+    await write(j, "registerToVote", [replayId]);
+    await write(j, "commitVote", [replayId, generateCommitHash(replayId, j.address, ballot, "replay-round0")]);
+  }
+  for (const [j, ballot] of replayBallots) {
+    await write(j, "revealVotes", [replayId, ballot, secretBytes("replay-round0")]);
+  }
+  await write(judge4, "registerToVote", [replayId]);
+  const replayedCommit = await write(judge4, "commitVote", [replayId, generateCommitHash(replayId, judge1.address, VOTE_FOR, "replay-round0")]);
+  console.log(!replayedCommit ? "✅ Revealed commitment replay rejected in the next round" : "❌ Revealed commitment replayed");
+
+  // Step 39: Contract judges
+  console.log("\nStep 39: Contract Judges");
+  // Forwarder mock: relays any calldata to the protocol and bubbles up reverts, standing in for a Safe
+  const forwarderRuntime =
+    `36600060003760006000366000600073${protocolAddress.slice(2).toLowerCase()}5af16033573d600060003e3d6000fd5b00`;
+  const registerThrough = async (forwarder: `0x${string}`) => {
+    try {
+      const hash = await deployerWalletFund.sendTransaction({
+        to: forwarder,
+        data: encodeFunctionData({ abi: protocolAbi, functionName: "registerAsJudge", args: [] }),
+      });
+      return (await publicClient.waitForTransactionReceipt({ hash })).status === "success";
+    } catch {
+      return false;
+    }
+  };
+  const readJudgeInfo = async (account: any) =>
+    (await publicClient.readContract({
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "getJudgeInfo",
+      args: [account],
+    })) as any;
+  const blockedSafe = await deployMock(forwarderRuntime);
+  const blockedRegistration = await registerThrough(blockedSafe);
+  console.log(!blockedRegistration ? "✅ Contract judge rejected while contract judges are off" : "❌ Contract registered as judge");
+  await write(deployer, "setAllowContractJudges", [true]);
+  const allowedSafe = await deployMock(forwarderRuntime);
+  const allowedRegistration = await registerThrough(allowedSafe);
+  const safeInfo = await readJudgeInfo(allowedSafe);
+  const eoaInfo = await readJudgeInfo(judge1.address);
+  console.log(
+    allowedRegistration && safeInfo.is_contract && !eoaInfo.is_contract
+      ? "✅ Contract judge registered and flagged as a contract"
+      : "❌ Contract judge registration or flag wrong",
+  );
+  await write(deployer, "setAllowContractJudges", [false]);
+
+  // Step 40: Per-dispute financials
+  console.log("\nStep 40: Dispute Financials");
+  const readProtocol = async (functionName: string, args: any[] = []) =>
+    (await publicClient.readContract({ address: protocolAddress, abi: protocolAbi, functionName, args })) as any;
+  const financialsId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [90n, judge3.address, "Financials", 0n]);
+  const financials = await readProtocol("getDisputeFinancials", [financialsId]);
+  const currentPrice = (await readProtocol("disputePrice")) as bigint;
+  const currentRewardBps = Number(await readProtocol("judgeRewardBps"));
+  console.log(
+    financials.nominal_price === currentPrice &&
+      financials.amount_received === currentPrice &&
+      financials.fee_payer.toLowerCase() === requester.address.toLowerCase() &&
+      financials.judge_reward_bps === currentRewardBps &&
+      financials.protocol_fee_bps + financials.judge_reward_bps === 10000 &&
+      financials.escrow_remaining === currentPrice
+      ? "✅ Financials show price, payer, bps split and escrow"
+      : "❌ Financials wrong",
+  );
+  let unknownFinancialsRejected = false;
+  try {
+    await readProtocol("getDisputeFinancials", [financialsId + 1000n]);
+  } catch {
+    unknownFinancialsRejected = true;
+  }
+  console.log(unknownFinancialsRejected ? "✅ Unknown dispute rejected" : "❌ Unknown dispute returned financials");
+  // Changing the split mid-dispute leaves the open dispute on the bps it was created with
+  const snapshotBpsId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [91n, judge3.address, "Bps snapshot", 1n]);
+  for (const j of [judge1, judge2]) {
+    await write(j, "registerToVote", [snapshotBpsId]);
+    await write(j, "commitVote", [snapshotBpsId, generateCommitHash(snapshotBpsId, j.address, VOTE_FOR, "bps-snapshot")]);
+  }
+  const changedBps = currentRewardBps === 5000 ? 6000 : 5000;
+  await write(deployer, "updateJudgeRewardBps", [changedBps]);
+  const treasuryBeforeSnapshotRuling = (await readProtocol("contractBalance")) as bigint;
+  for (const j of [judge1, judge2]) {
+    await write(j, "revealVotes", [snapshotBpsId, VOTE_FOR, secretBytes("bps-snapshot")]);
+  }
+  const [, snapshotPool] = await readProtocol("getPendingSettlement", [snapshotBpsId]);
+  const snapshotTreasuryShare = ((await readProtocol("contractBalance")) as bigint) - treasuryBeforeSnapshotRuling;
+  const snapshotFinancials = await readProtocol("getDisputeFinancials", [snapshotBpsId]);
+  const snapshotPrice = snapshotFinancials.amount_received as bigint;
+  console.log(
+    snapshotFinancials.judge_reward_bps === currentRewardBps &&
+      snapshotPool === (snapshotPrice * BigInt(currentRewardBps)) / 10_000n &&
+      snapshotPool + snapshotTreasuryShare === snapshotPrice
+      ? `✅ Bps changed to ${changedBps} mid-dispute, the open dispute still split at ${currentRewardBps}`
+      : `❌ Open dispute split ${snapshotPool} / ${snapshotTreasuryShare} after the bps change`,
+  );
+  await settlePanel(snapshotBpsId);
+  await write(deployer, "updateJudgeRewardBps", [currentRewardBps]);
+
+  // Step 41: Single resolution around the final reveal
+  console.log("\nStep 41: Single Resolution");
+  // Category 3 seats three judges, so two matching reveals decide the dispute early
+  const raceId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [100n, judge3.address, "Resolution race", 3n]);
+  const raceBallots: [any, number][] = [[judge1, VOTE_FOR], [judge2, VOTE_FOR], [judge4, VOTE_AGAINST]];
+  for (const [j, ballot] of raceBallots) {
+    await write(j, "registerToVote", [raceId]);
+    await write(j, "commitVote", [raceId, generateCommitHash(raceId, j.address, ballot, "race-secret")]);
+  }
+  for (const [j, ballot] of raceBallots.slice(0, 2)) {
+    await write(j, "revealVotes", [raceId, ballot, secretBytes("race-secret")]);
+  }
+  const raceResolved = await readInfo(raceId);
+  const racePaid = await Promise.all([judge1, judge2].map(j => readBalanceOf(j.address)));
+  const lateReveal = await write(judge4, "revealVotes", [raceId, VOTE_AGAINST, secretBytes("race-secret")]);
+  const raceAfter = await readInfo(raceId);
+  await settlePanel(raceId);
+  const racePaidAfter = await Promise.all([judge1, judge2].map(j => readBalanceOf(j.address)));
+  const raceResettled = await write(judge4, "settleJudges", [raceId, 0n, 3n]);
+  const racePaidFinal = await Promise.all([judge1, judge2].map(j => readBalanceOf(j.address)));
+  console.log(
+    raceResolved.resolved && !lateReveal && raceAfter.votes_against === raceResolved.votes_against &&
+      raceAfter.votes_for === raceResolved.votes_for
+      ? "✅ Late reveal after resolution rejected, tallies unchanged"
+      : "❌ Late reveal reopened the tally",
+  );
+  console.log(
+    !raceResettled && racePaidAfter.every((b, i) => b > racePaid[i] && racePaidFinal[i] === b)
+      ? "✅ Panel paid once"
+      : "❌ Panel paid more than once",
+  );
+
+  // Step 42: Paginated panel settlement
+  console.log("\nStep 42: Paginated Settlement");
+  // A full panel at the cap (category 9 from Step 32) needs 25 fresh judges
+  const capJudges = await enlistJudges("cap", 25);
+  // Gas of a reveal, to compare the resolving reveal of a small and a full panel
+  const revealGas = async (account: any, args: any[]) => {
+    const wallet = createWalletClient({ account, chain, transport: http() });
+    const { request } = await publicClient.simulateContract({
+      account,
+      address: protocolAddress,
+      abi: protocolAbi,
+      functionName: "revealVotes",
+      args,
+    });
+    const hash = await wallet.writeContract(request);
+    return (await publicClient.waitForTransactionReceipt({ hash })).gasUsed;
+  };
+  const smallPanelId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [110n, judge3.address, "Small panel", 1n]);
+  for (const j of capJudges.slice(0, 2)) {
+    await write(j, "registerToVote", [smallPanelId]);
+    await write(j, "commitVote", [smallPanelId, generateCommitHash(smallPanelId, j.address, VOTE_FOR, "small-panel")]);
+  }
+  await write(capJudges[0], "revealVotes", [smallPanelId, VOTE_FOR, secretBytes("small-panel")]);
+  const smallRevealGas = await revealGas(capJudges[1], [smallPanelId, VOTE_FOR, secretBytes("small-panel")]);
+  await settlePanel(smallPanelId);
+
+  const pagedId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [111n, judge3.address, "Paged settlement", 9n]);
+  // 12 against, 13 for: the losers reveal first so every vote lands before 13 decides it
+  const pagedBallots: [any, number][] = capJudges.map((j, i) => [j, i < 12 ? VOTE_AGAINST : VOTE_FOR]);
+  for (const [j, ballot] of pagedBallots) {
+    await write(j, "registerToVote", [pagedId]);
+    await write(j, "commitVote", [pagedId, generateCommitHash(pagedId, j.address, ballot, "paged-secret")]);
   }
+  for (const [j, ballot] of pagedBallots.slice(0, 24)) {
+    await write(j, "revealVotes", [pagedId, ballot, secretBytes("paged-secret")]);
+  }
+  const largeRevealGas = await revealGas(capJudges[24], [pagedId, VOTE_FOR, secretBytes("paged-secret")]);
+  console.log(`  Resolving reveal gas: ${smallRevealGas} with 2 seats, ${largeRevealGas} with 25 seats`);
+  console.log(
+    largeRevealGas < (smallRevealGas * 3n) / 2n
+      ? "✅ Resolving reveal cost doesn't grow with the panel"
+      : "❌ Resolving reveal cost grows with the panel",
+  );
+
+  const readPagedState = async () =>
+    Promise.all(capJudges.map(async j => [await readBalanceOf(j.address), Number(await readReputation(j))] as const));
+  const pagedBefore = await readPagedState();
+  const treasuryBeforePages = (await readProtocol("contractBalance")) as bigint;
+  const [, pagedPool] = await readProtocol("getPendingSettlement", [pagedId]);
+  // Equal tiers and no stake weighting: each of the 13 winners is owed a thirteenth of the pool
+  const expectedShare = pagedPool / 13n;
+  const firstPage = await write(judge4, "settleJudges", [pagedId, 0n, 9n]);
+  const overlappingPage = await write(judge4, "settleJudges", [pagedId, 5n, 13n]);
+  const [stillPendingMidway] = await readProtocol("getPendingSettlement", [pagedId]);
+  const lastPage = await write(judge4, "settleJudges", [pagedId, 18n, 10n]);
+  const [pendingAfterPages, leftover] = await readProtocol("getPendingSettlement", [pagedId]);
+  const extraPage = await write(judge4, "settleJudges", [pagedId, 0n, 25n]);
+  const pagedAfter = await readPagedState();
+  const pagedCredits = pagedAfter.map(([b], i) => b - pagedBefore[i][0]);
+  const pagedRemainder = ((await readProtocol("contractBalance")) as bigint) - treasuryBeforePages;
+  const pagedPaid = pagedCredits.reduce((sum, c) => sum + c, 0n);
+  console.log(
+    firstPage && overlappingPage && lastPage && stillPendingMidway && !pendingAfterPages && leftover === 0n && !extraPage
+      ? "✅ Panel of 25 settled over three calls, overlap skipped, completed once"
+      : "❌ Paginated settlement flow failed",
+  );
+  console.log(
+    pagedCredits.every((c, i) => c === (i < 12 ? 0n : expectedShare)) &&
+      pagedPaid + pagedRemainder === pagedPool &&
+      pagedRemainder === pagedPool % 13n
+      ? `✅ Paged payouts match single-shot shares (${formatUnits(expectedShare, 6)} USDC each, ${pagedRemainder} to the treasury)`
+      : `❌ Paged payouts ${pagedPaid} plus remainder ${pagedRemainder} don't make the pool ${pagedPool}`,
+  );
+  console.log(
+    pagedAfter.every(([, rep], i) => rep === pagedBefore[i][1] + (i < 12 ? -1 : 1))
+      ? "✅ Reputation moved once per seat"
+      : "❌ Reputation changes wrong",
+  );
+  await checkSolvency("paged panel settlement");
+
+  // Step 43: Judge history
+  console.log("\nStep 43: Judge History");
+  // Judge4 lost in Step 30 and sat silent in Step 41; here they vote with the majority
+  const historyWinId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [120n, judge3.address, "History win", 3n]);
+  const historyBallots: [any, number][] = [[judge4, VOTE_AGAINST], [judge1, VOTE_AGAINST], [judge2, VOTE_FOR]];
+  for (const [j, ballot] of historyBallots) {
+    await write(j, "registerToVote", [historyWinId]);
+    await write(j, "commitVote", [historyWinId, generateCommitHash(historyWinId, j.address, ballot, "history-secret")]);
+  }
+  for (const [j, ballot] of [historyBallots[2], historyBallots[0], historyBallots[1]]) {
+    await write(j, "revealVotes", [historyWinId, ballot, secretBytes("history-secret")]);
+  }
+  await settlePanel(historyWinId);
+  const historyLength = (await readProtocol("getJudgeHistoryLength", [judge4.address])) as bigint;
+  const history: any[] = [];
+  for (let offset = 0n; offset < historyLength; offset += 2n) {
+    history.push(...(await readProtocol("getJudgeHistory", [judge4.address, offset, 2n])));
+  }
+  const entriesFor = (id: bigint) => history.filter(entry => entry.dispute_id === id);
+  const [silentEntry] = entriesFor(raceId);
+  const [lostEntry] = entriesFor(slashId);
+  const [wonEntry] = entriesFor(historyWinId);
+  console.log(
+    BigInt(history.length) === historyLength && [slashId, raceId, historyWinId].every(id => entriesFor(id).length === 1)
+      ? `✅ Paged through ${history.length} history entries, one per settled dispute`
+      : "❌ Judge history missing or duplicated entries",
+  );
+  console.log(
+    silentEntry && !silentEntry.vote_revealed && !silentEntry.was_majority && silentEntry.reward_amount === 0n && silentEntry.reputation_delta === 0 &&
+      lostEntry && lostEntry.vote_revealed && !lostEntry.was_majority && lostEntry.reward_amount === 0n && lostEntry.reputation_delta === -1 &&
+      wonEntry && wonEntry.vote_revealed && wonEntry.was_majority && wonEntry.reward_amount > 0n && wonEntry.reputation_delta === 1
+      ? "✅ Silent, losing and winning seats recorded with their payout and reputation change"
+      : "❌ Judge history entries wrong",
+  );
+  const pastEnd = await readProtocol("getJudgeHistory", [judge4.address, historyLength, 2n]);
+  const capped = await readProtocol("getJudgeHistory", [judge4.address, 0n, 1000n]);
+  console.log(
+    pastEnd.length === 0 && capped.length === Math.min(Number(historyLength), 100)
+      ? "✅ Page past the end is empty, oversized pages capped"
+      : "❌ History paging bounds wrong",
+  );
+
+  // Step 44: Guardian role
+  console.log("\nStep 44: Guardian");
+  const guardian = judge5;
+  const strangerGuardian = await write(requester, "setGuardian", [requester.address]);
+  const guardianSet = await write(deployer, "setGuardian", [guardian.address]);
+  console.log(
+    !strangerGuardian && guardianSet && (await readProtocol("guardian")) === guardian.address
+      ? "✅ Only the owner sets the guardian"
+      : "❌ Guardian setter access wrong",
+  );
+  // Brakes: a queued withdrawal can be cancelled and an idle judge swapped out
+  await write(deployer, "queueWithdrawal", []);
+  const guardianExecute = await write(guardian, "executeWithdrawal", []);
+  const guardianCancel = await write(guardian, "cancelWithdrawal", []);
+  const guardianSwapId = await readNextDisputeId();
+  await write(requester, "createDisputeDirect", [130n, judge3.address, "Guardian swap", 1n]);
+  await write(judge1, "registerToVote", [guardianSwapId]);
+  const guardianSwap = await write(guardian, "replaceJudge", [guardianSwapId, judge1.address, judge2.address]);
+  console.log(
+    !guardianExecute && guardianCancel && guardianSwap
+      ? "✅ Guardian cancelled a withdrawal and replaced an idle judge"
+      : "❌ Guardian emergency actions failed",
+  );
+  // ...but no funds or economics
+  const guardianQueue = await write(guardian, "queueWithdrawal", []);
+  const guardianPrice = await write(guardian, "setCategory", [1n, 1n, 2]);
+  const guardianRewardBps = await write(guardian, "updateJudgeRewardBps", [10000]);
+  const guardianRotate = await write(guardian, "setGuardian", [requester.address]);
+  console.log(
+    !guardianQueue && !guardianPrice && !guardianRewardBps && !guardianRotate
+      ? "✅ Guardian can't withdraw, change the price or fees, or appoint a guardian"
+      : "❌ Guardian exceeded its role",
+  );
+  await write(deployer, "setGuardian", ["0x0000000000000000000000000000000000000000"]);
+
+  // Step 45: Judge staking
+  console.log("\nStep 45: Judge Staking");
+  await write(deployer, "updateMinReputation", [-1000]);
+  await write(deployer, "setStakeWeightedRewards", [true]);
+  const outsiderStake = await write(requester, "judgeStake", [1n * 10n ** 6n]);
+  await write(judge1, "judgeStake", [3n * 10n ** 6n]);
+  await write(judge4, "judgeStake", [1n * 10n ** 6n]);
+  const [bigStake, smallStake] = await Promise.all([judge1, judge4].map(j => readProtocol("getJudgeInfo", [j.address])));
+  console.log(
+    !outsiderStake && bigStake.stake === 3n * 10n ** 6n && smallStake.stake === 1n * 10n ** 6n
+      ? "✅ Judges staked, non-judge rejected"
+      : "❌ Staking failed",
+  );
+  const stakedId = await readNextDisputeId();
+  const stakedRewardBps = BigInt(await readProtocol("judgeRewardBps"));
+  await write(requester, "createDisputeDirect", [140n, judge3.address, "Stake weighted", 1n]);
+  for (const j of [judge1, judge4]) {
+    await write(j, "registerToVote", [stakedId]);
+    await write(j, "commitVote", [stakedId, generateCommitHash(stakedId, j.address, VOTE_FOR, "staked")]);
+  }
+  const seatedUnstake = await write(judge1, "judgeUnstake", [1n]);
+  const stakedCredits = await Promise.all([judge1, judge4].map(j => readBalanceOf(j.address)));
+  for (const j of [judge1, judge4]) {
+    await write(j, "revealVotes", [stakedId, VOTE_FOR, secretBytes("staked")]);
+  }
+  await settlePanel(stakedId);
+  // 3:1 stakes split the pool 3:1
+  const stakedPool = (1n * 10n ** 6n * stakedRewardBps) / 10_000n;
+  const [bigShare, smallShare] = await Promise.all(
+    [judge1, judge4].map(async (j, i) => (await readBalanceOf(j.address)) - stakedCredits[i]),
+  );
+  console.log(!seatedUnstake ? "✅ Stake locked while the judge sits on a panel" : "❌ Unstaked from a panel seat");
+  console.log(
+    bigShare === (stakedPool * 3n) / 4n && smallShare === stakedPool / 4n
+      ? "✅ Unequal stakes got unequal shares"
+      : `❌ Shares ${bigShare} and ${smallShare} of ${stakedPool}`,
+  );
+  const unstakeBefore = await readTokenBalance(judge1.address);
+  const overUnstake = await write(judge1, "judgeUnstake", [3n * 10n ** 6n + 1n]);
+  await write(judge1, "judgeUnstake", [3n * 10n ** 6n]);
+  await write(judge4, "judgeUnstake", [1n * 10n ** 6n]);
+  const unstaked = (await readTokenBalance(judge1.address)) - unstakeBefore;
+  console.log(
+    !overUnstake && unstaked === 3n * 10n ** 6n && (await readProtocol("totalStaked")) === 0n
+      ? "✅ Stake withdrawn once the seat was settled"
+      : `❌ Unstake returned ${unstaked}`,
+  );
+  await write(deployer, "setStakeWeightedRewards", [false]);
+  await write(deployer, "updateMinReputation", [0]);
+  await checkSolvency("staking");
+
+  // Step 46: Dispute creation with an EIP-2612 permit
+  console.log("\nStep 46: Dispute With Permit");
+  // A fresh requester with tokens but no allowance pays the fee through a signed permit
+  const permitPayer = privateKeyToAccount(keccak256(toBytes(`${DEPLOYER_PRIVATE_KEY}permit`)));
+  await publicClient.waitForTransactionReceipt({
+    hash: await deployerWalletFund.sendTransaction({ to: permitPayer.address, value: 100n * 10n ** 15n }),
+  });
+  await tokenWrite(deployer, usdc, "mint", [permitPayer.address, 10n * 10n ** 6n]);
+  const permitPrice = 1n * 10n ** 6n;
+  const readTokenNonce = async () =>
+    (await publicClient.readContract({ address: usdc, abi: tokenAbi, functionName: "nonces", args: [permitPayer.address] })) as bigint;
+  const signPermit = async (signer: any, value: bigint, deadline: bigint) =>
+    parseSignature(
+      await signer.signTypedData({
+        domain: { name: "Mock USD", version: "1", chainId: chain.id, verifyingContract: usdc },
+        types: {
+          Permit: [
+            { name: "owner", type: "address" },
+            { name: "spender", type: "address" },
+            { name: "value", type: "uint256" },
+            { name: "nonce", type: "uint256" },
+            { name: "deadline", type: "uint256" },
+          ],
+        },
+        primaryType: "Permit",
+        message: { owner: permitPayer.address, spender: protocolAddress, value, deadline, nonce: await readTokenNonce() },
+      }),
+    );
+  const createWithPermit = (dealId: bigint, deadline: bigint, sig: any) =>
+    write(permitPayer, "createDisputeWithPermit", [dealId, judge3.address, "Permit dispute", 1n, deadline, Number(sig.v), sig.r, sig.s]);
+  const permitDeadline = BigInt(Math.floor(Date.now() / 1000) + 3600);
+
+  const noAllowance = await write(permitPayer, "createDisputeDirect", [160n, judge3.address, "No allowance", 1n]);
+  const expiredPermit = await createWithPermit(160n, 1n, await signPermit(permitPayer, permitPrice, 1n));
+  const foreignPermit = await createWithPermit(160n, permitDeadline, await signPermit(requester, permitPrice, permitDeadline));
+  console.log(
+    !noAllowance && !expiredPermit && !foreignPermit
+      ? "✅ Expired permit and permit signed by another account rejected"
+      : "❌ Dispute created without a valid permit",
+  );
+
+  const permitId = await readNextDisputeId();
+  const permitNonce = await readTokenNonce();
+  const payerBefore = await readTokenBalance(permitPayer.address);
+  const permitSig = await signPermit(permitPayer, permitPrice, permitDeadline);
+  const permitted = await createWithPermit(160n, permitDeadline, permitSig);
+  const permitInfo = await readInfo(permitId);
+  const allowanceLeft = (await publicClient.readContract({
+    address: usdc,
+    abi: tokenAbi,
+    functionName: "allowance",
+    args: [permitPayer.address, protocolAddress],
+  })) as bigint;
+  console.log(
+    permitted &&
+      permitInfo.price === permitPrice &&
+      payerBefore - (await readTokenBalance(permitPayer.address)) === permitPrice &&
+      allowanceLeft === 0n &&
+      (await readTokenNonce()) === permitNonce + 1n
+      ? "✅ Dispute created and paid through the permit in one transaction"
+      : "❌ Permit dispute failed",
+  );
+  const replayedPermit = await createWithPermit(161n, permitDeadline, permitSig);
+  console.log(!replayedPermit ? "✅ Used permit can't be replayed" : "❌ Permit replayed");
+
+  // A permit front-run straight to the token leaves the allowance in place; the dispute still goes through
+  const frontRunSig = await signPermit(permitPayer, permitPrice, permitDeadline);
+  await tokenWrite(deployer, usdc, "permit", [
+    permitPayer.address,
+    protocolAddress,
+    permitPrice,
+    permitDeadline,
+    Number(frontRunSig.v),
+    frontRunSig.r,
+    frontRunSig.s,
+  ]);
+  const frontRun = await createWithPermit(162n, permitDeadline, frontRunSig);
+  console.log(frontRun ? "✅ Front-run permit falls back to the existing allowance" : "❌ Front-run permit blocked the dispute");
+  await checkSolvency("permit dispute");
 
   console.log("\n✅ Test Complete!");
 }
@@ -12,6 +12,10 @@ export async function buildDeployCommand(
 ) {
   let baseCommand = `cargo stylus deploy --endpoint='${getRpcUrlFromChain(config.chain)}' --private-key='${config.privateKey}'`;
 
+  if (deployOptions.features) {
+    baseCommand += ` --features=${deployOptions.features}`;
+  }
+
   if (deployOptions.estimateGas) {
     return `${baseCommand} --estimate-gas`;
   }
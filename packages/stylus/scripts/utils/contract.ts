@@ -20,6 +20,30 @@ import { GenericContractsDeclaration } from "../utils/scaffold-eth/contract";
  * Throws if the file or name field is missing.
  */
 export function getContractNameFromCargoToml(contractFolder: string): string {
+  const cargoTomlPath = path.join(contractFolder, "Cargo.toml");
+  const parsed = parseCargoToml(contractFolder);
+  if (!parsed.package || !parsed.package.name) {
+    throw new Error(
+      `Missing [package] or name field in Cargo.toml at ${cargoTomlPath}`,
+    );
+  }
+  return parsed.package.name;
+}
+
+/**
+ * Reads the facet features from Cargo.toml in the given contract folder: every feature that
+ * enables `facet` builds one facet contract. Empty if the contract isn't split into facets.
+ */
+export function getFacetFeaturesFromCargoToml(contractFolder: string): string[] {
+  const features: Record<string, string[]> =
+    parseCargoToml(contractFolder).features || {};
+  return Object.keys(features).filter((feature) =>
+    features[feature].includes("facet"),
+  );
+}
+
+// eslint-disable-next-line @typescript-eslint/no-explicit-any
+function parseCargoToml(contractFolder: string): any {
   const cargoTomlPath = path.join(contractFolder, "Cargo.toml");
   if (!fs.existsSync(cargoTomlPath)) {
     throw new Error(
@@ -34,12 +58,7 @@ export function getContractNameFromCargoToml(contractFolder: string): string {
   } catch (e) {
     throw new Error(`Failed to parse Cargo.toml: ${e}`);
   }
-  if (!parsed.package || !parsed.package.name) {
-    throw new Error(
-      `Missing [package] or name field in Cargo.toml at ${cargoTomlPath}`,
-    );
-  }
-  return parsed.package.name;
+  return parsed;
 }
 
 export function isContractHasConstructor(contractFolder: string): boolean {
@@ -149,8 +168,7 @@ export async function generateTsAbi(
   txHash: string,
   chainId: string,
 ) {
-  const TARGET_DIR = "../nextjs/contracts/";
-  const TARGET_FILE = `${TARGET_DIR}deployedContracts.ts`;
+  const TARGET_FILE = "../nextjs/contracts/deployedContracts.ts";
   const abiTxt = fs.readFileSync(abiFilePath, "utf8");
 
   // Extract from 4th row to the end
@@ -166,7 +184,6 @@ export async function generateTsAbi(
 
   // eslint-disable-next-line @typescript-eslint/no-explicit-any
   let deployedContractsObj: any = {};
-  const fileHeader = generatedContractComment + "\n\n";
 
   if (fs.existsSync(TARGET_FILE)) {
     const fileContent = fs.readFileSync(TARGET_FILE, "utf8");
@@ -185,6 +202,39 @@ export async function generateTsAbi(
   }
   deployedContractsObj[chainId][contractName] = newContractEntry;
 
+  await writeDeployedContracts(deployedContractsObj);
+}
+
+/**
+ * Add ABI items to a deployed contract's entry, skipping any it already has.
+ * Used to give the protocol router the ABI of the facets it forwards to.
+ */
+export async function extendTsAbi(
+  chainId: string,
+  contractName: string,
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  abi: any[],
+) {
+  const deployedContractsObj = loadDeployedContracts();
+  const contractData = getContractData(chainId, contractName);
+  deployedContractsObj[chainId][contractName] = contractData;
+
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  const key = (item: any) => JSON.stringify([item.type, item.name, item.inputs]);
+  const known = new Set(contractData.abi.map(key));
+  contractData.abi = [
+    ...contractData.abi,
+    ...abi.filter((item) => !known.has(key(item))),
+  ];
+
+  await writeDeployedContracts(deployedContractsObj);
+}
+
+// eslint-disable-next-line @typescript-eslint/no-explicit-any
+async function writeDeployedContracts(deployedContractsObj: any) {
+  const TARGET_DIR = "../nextjs/contracts/";
+  const TARGET_FILE = `${TARGET_DIR}deployedContracts.ts`;
+  const fileHeader = generatedContractComment + "\n\n";
   const contractsString = JSON.stringify(deployedContractsObj, null, 2);
 
   const output = `${fileHeader}const deployedContracts = ${contractsString} as const;\n\nexport default deployedContracts satisfies GenericContractsDeclaration;\n`;